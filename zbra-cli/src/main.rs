@@ -1,12 +1,19 @@
 use clap::{Parser, Subcommand};
 use eyre::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
+use std::io::Write;
+use std::ops::RangeInclusive;
 use std::path::PathBuf;
 
 use zbra_core::binary;
+use zbra_core::compression::{
+    zstd_levels, CompressionAlgorithm, CompressionConfig, OptimizationTarget, BROTLI_LEVELS,
+};
 use zbra_core::data::{BinaryEncoding, Default, Encoding, Field, IntEncoding, Table, Value};
-use zbra_core::logical::{FieldSchema, TableSchema, ValueSchema, VariantSchema};
+use zbra_core::logical::{Compatibility, FieldSchema, TableSchema, ValueSchema, VariantSchema};
+use zbra_core::stats::ValueStats;
 use zbra_core::striped;
 
 #[derive(Parser)]
@@ -37,6 +44,20 @@ enum Commands {
         /// Output format (json, logical, striped, binary)
         #[arg(long, default_value = "striped")]
         to: String,
+
+        /// Compression for binary output (none, zstd[:level], lz4, snappy,
+        /// brotli[:level]), zstd:3 by default
+        #[arg(long)]
+        compression: Option<String>,
+
+        /// Encoding preference for binary output (space, balanced, speed),
+        /// space by default
+        #[arg(long)]
+        optimize: Option<String>,
+
+        /// Format version for binary output (2, 3)
+        #[arg(long, default_value_t = binary::FormatVersion::CURRENT.number())]
+        format_version: u32,
     },
     /// Show information about a data file
     Info {
@@ -61,6 +82,49 @@ enum Commands {
         #[arg(short, long)]
         schema: PathBuf,
     },
+    /// Check the header and block checksums of a binary file without
+    /// decoding its blocks
+    Verify {
+        /// Binary (.zbra) file
+        file: PathBuf,
+    },
+    /// Rewrite a binary file in a newer format version, copying its blocks
+    /// as they are or re-encoding them for version 1 files
+    Upgrade {
+        /// Input binary (.zbra) file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output binary (.zbra) file
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Format version to write
+        #[arg(long, default_value_t = 3)]
+        format_version: u32,
+
+        /// Header metadata entry as key=value, repeatable (format version 3)
+        #[arg(long = "metadata", value_name = "KEY=VALUE")]
+        metadata: Vec<String>,
+    },
+    /// Inspect and compare schemas
+    Schema {
+        #[command(subcommand)]
+        command: SchemaCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum SchemaCommands {
+    /// Classify every change between two schemas as backward-compatible,
+    /// forward-compatible or breaking
+    Diff {
+        /// Old schema: a JSON schema, a JSON data file or a binary (.zbra) file
+        old: PathBuf,
+
+        /// New schema: a JSON schema, a JSON data file or a binary (.zbra) file
+        new: PathBuf,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -79,6 +143,8 @@ struct JsonSchema {
     fields: Option<Vec<JsonField>>,
     variants: Option<Vec<JsonVariant>>,
     inner: Option<Box<JsonSchema>>,
+    key: Option<Box<JsonSchema>>,
+    value: Option<Box<JsonSchema>>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -103,8 +169,19 @@ fn main() -> Result<()> {
             output,
             from,
             to,
+            compression,
+            optimize,
+            format_version,
         } => {
-            convert_file(input, output, from, to)?;
+            convert_file(
+                input,
+                output,
+                from,
+                to,
+                compression.as_deref(),
+                optimize.as_deref(),
+                *format_version,
+            )?;
         }
         Commands::Info { file } => {
             show_info(file)?;
@@ -115,12 +192,36 @@ fn main() -> Result<()> {
         Commands::Validate { data, schema } => {
             validate_data(data, schema)?;
         }
+        Commands::Verify { file } => {
+            verify_file(file)?;
+        }
+        Commands::Upgrade {
+            input,
+            output,
+            format_version,
+            metadata,
+        } => {
+            upgrade_file(input, output, *format_version, metadata)?;
+        }
+        Commands::Schema {
+            command: SchemaCommands::Diff { old, new },
+        } => {
+            diff_schemas(old, new)?;
+        }
     }
 
     Ok(())
 }
 
-fn convert_file(input: &PathBuf, output: &PathBuf, from: &str, to: &str) -> Result<()> {
+fn convert_file(
+    input: &PathBuf,
+    output: &PathBuf,
+    from: &str,
+    to: &str,
+    compression: Option<&str>,
+    optimize: Option<&str>,
+    format_version: u32,
+) -> Result<()> {
     println!(
         "Converting {} -> {} ({} to {})",
         input.display(),
@@ -129,6 +230,12 @@ fn convert_file(input: &PathBuf, output: &PathBuf, from: &str, to: &str) -> Resu
         to
     );
 
+    if to != "binary" && (compression.is_some() || optimize.is_some()) {
+        return Err(eyre::eyre!(
+            "--compression and --optimize only apply to binary output"
+        ));
+    }
+
     match (from, to) {
         ("json", "logical") => {
             let json_content = fs::read_to_string(input)?;
@@ -197,11 +304,20 @@ fn convert_file(input: &PathBuf, output: &PathBuf, from: &str, to: &str) -> Resu
 
             // Create binary file
             let row_count = striped_table.row_count();
-            let binary_file = binary::BinaryFile::new(schema, striped_table);
+            let algorithm = parse_compression(compression.unwrap_or("zstd:3"))?;
+            let compression = CompressionConfig {
+                binary_data: algorithm.clone(),
+                strings: algorithm,
+                columns: Vec::new(),
+                optimization_target: parse_optimization_target(optimize.unwrap_or("space"))?,
+            };
+            let binary_file =
+                binary::BinaryFile::new_with_compression(schema, striped_table, compression);
+            let version = binary::FormatVersion::from_number(format_version)?;
 
             // Write to output file
             let mut file = fs::File::create(output)?;
-            binary_file.write_to(&mut file)?;
+            binary_file.write_version_to(&mut file, version)?;
 
             println!("Converted to binary format with {} rows", row_count);
         }
@@ -248,29 +364,90 @@ fn convert_file(input: &PathBuf, output: &PathBuf, from: &str, to: &str) -> Resu
     Ok(())
 }
 
+fn format_stats_path(path: &[String]) -> String {
+    if path.is_empty() {
+        "<row>".to_string()
+    } else {
+        path.join(".")
+    }
+}
+
+fn format_value_stats(stats: &ValueStats) -> String {
+    match stats {
+        ValueStats::Int {
+            min,
+            max,
+            default_count,
+        } => format!("int min={} max={} defaults={}", min, max, default_count),
+        ValueStats::Double {
+            min,
+            max,
+            default_count,
+        } => format!("double min={} max={} defaults={}", min, max, default_count),
+        ValueStats::Binary {
+            min_length,
+            max_length,
+            default_count,
+        } => format!(
+            "binary min_length={} max_length={} defaults={}",
+            min_length, max_length, default_count
+        ),
+        ValueStats::Enum { tag_counts } => {
+            let counts: Vec<String> = tag_counts
+                .iter()
+                .map(|(tag, count)| format!("{}={}", tag, count))
+                .collect();
+            format!("enum tags [{}]", counts.join(", "))
+        }
+    }
+}
+
 fn show_info(file: &PathBuf) -> Result<()> {
     println!("File info for: {}", file.display());
 
     // Check if this is a binary file (ends with .zbra)
     if file.extension().and_then(|s| s.to_str()) == Some("zbra") {
         // Handle binary file
-        let mut file_handle = fs::File::open(file)?;
-        let binary_file = binary::BinaryFile::read_from(&mut file_handle)?;
-
-        let schema = &binary_file.header.schema;
-        let total_rows: usize = binary_file
-            .blocks
-            .iter()
-            .map(|b| b.row_count as usize)
-            .sum();
+        let file_handle = fs::File::open(file)?;
+        let reader = binary::BinaryReader::new(std::io::BufReader::new(file_handle))?;
 
         println!("Format: Binary (.zbra)");
-        println!("Schema type: {:?}", schema);
-        println!("Total rows: {}", total_rows);
-        println!("Block count: {}", binary_file.blocks.len());
-
-        for (i, block) in binary_file.blocks.iter().enumerate() {
-            println!("Block {}: {} rows", i, block.row_count);
+        println!("Format version: {}", reader.version().number());
+        for (key, value) in &reader.header().metadata {
+            println!("Metadata: {} = {}", key, value);
+        }
+        println!("Schema type: {:?}", reader.header().schema);
+
+        if reader.version() == binary::FormatVersion::V1 {
+            // Version 1 files have no block index or statistics, so every
+            // block is decoded to count its rows
+            let mut row_count = 0;
+            let mut block_count = 0;
+            for block in reader {
+                let block = block?;
+                println!("Block {}: {} rows", block_count, block.row_count);
+                row_count += block.row_count as u64;
+                block_count += 1;
+            }
+            println!("Total rows: {}", row_count);
+            println!("Block count: {}", block_count);
+            println!("Schema validation: PASS (binary files are pre-validated)");
+            return Ok(());
+        }
+
+        let mut reader = binary::IndexedReader::new(reader.into_inner())?;
+        println!("Total rows: {}", reader.row_count());
+        println!("Block count: {}", reader.block_count());
+
+        for i in 0..reader.block_count() {
+            println!("Block {}: {} rows", i, reader.index()[i].row_count);
+            for column in reader.read_block_stats(i)?.columns {
+                println!(
+                    "  {}: {}",
+                    format_stats_path(&column.path),
+                    format_value_stats(&column.values)
+                );
+            }
         }
 
         println!("Schema validation: PASS (binary files are pre-validated)");
@@ -401,6 +578,134 @@ fn create_examples(output_dir: &PathBuf) -> Result<()> {
     Ok(())
 }
 
+fn verify_file(file: &PathBuf) -> Result<()> {
+    println!("Verifying {}", file.display());
+
+    // Creating the reader checks the header checksum
+    let file_handle = fs::File::open(file)?;
+    let mut reader = binary::IndexedReader::new(std::io::BufReader::new(file_handle))?;
+    println!("Header: PASS");
+
+    let mut failures = 0;
+    for i in 0..reader.block_count() {
+        match reader.verify_block(i) {
+            Ok(()) => println!("Block {}: PASS ({} rows)", i, reader.index()[i].row_count),
+            Err(e) => {
+                println!("Block {}: FAIL - {}", i, e);
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        return Err(eyre::eyre!(
+            "{} of {} blocks failed verification",
+            failures,
+            reader.block_count()
+        ));
+    }
+    println!(
+        "Verification: PASS ({} blocks, {} rows)",
+        reader.block_count(),
+        reader.row_count()
+    );
+    Ok(())
+}
+
+fn upgrade_file(
+    input: &PathBuf,
+    output: &PathBuf,
+    format_version: u32,
+    metadata: &[String],
+) -> Result<()> {
+    let version = binary::FormatVersion::from_number(format_version)?;
+
+    let input_handle = fs::File::open(input)?;
+    let mut reader = binary::BinaryReader::new(std::io::BufReader::new(input_handle))?;
+    println!(
+        "Upgrading {} (format version {}) -> {} (format version {})",
+        input.display(),
+        reader.version().number(),
+        output.display(),
+        version.number()
+    );
+    if version.number() < reader.version().number() {
+        return Err(eyre::eyre!(
+            "Cannot downgrade from format version {} to {}",
+            reader.version().number(),
+            version.number()
+        ));
+    }
+
+    let mut entries = BTreeMap::new();
+    for entry in metadata {
+        let (key, value) = entry
+            .split_once('=')
+            .ok_or_else(|| eyre::eyre!("Metadata must be given as key=value: {}", entry))?;
+        entries.insert(key.to_string(), value.to_string());
+    }
+
+    let output_handle = fs::File::create(output)?;
+    let writer = std::io::BufWriter::new(output_handle);
+
+    if reader.version() == binary::FormatVersion::V1 {
+        // Version 1 blocks predate the current block layout, so they are
+        // decoded and encoded again rather than copied
+        reader.copy_to_version(writer, version, entries)?.flush()?;
+        println!("Re-encoded {} blocks", reader.blocks_read());
+        return Ok(());
+    }
+
+    let mut reader = binary::IndexedReader::new(reader.into_inner())?;
+    reader.copy_to_version(writer, version, entries)?.flush()?;
+
+    println!(
+        "Upgraded {} blocks with {} rows",
+        reader.block_count(),
+        reader.row_count()
+    );
+    Ok(())
+}
+
+fn diff_schemas(old: &PathBuf, new: &PathBuf) -> Result<()> {
+    println!("Comparing schemas {} -> {}", old.display(), new.display());
+
+    let old_schema = read_schema(old)?;
+    let new_schema = read_schema(new)?;
+
+    let changes = old_schema.diff(&new_schema);
+    if changes.is_empty() {
+        println!("No changes");
+    }
+    for change in &changes {
+        println!("{}", change);
+    }
+
+    let compatibility = old_schema.compatibility(&new_schema);
+    println!("Compatibility: {}", compatibility);
+    if compatibility == Compatibility::Breaking {
+        return Err(eyre::eyre!("Schema change is breaking"));
+    }
+    Ok(())
+}
+
+/// Load a table schema from a binary file header, a JSON data file or a bare
+/// JSON schema
+fn read_schema(file: &PathBuf) -> Result<TableSchema> {
+    if file.extension().and_then(|s| s.to_str()) == Some("zbra") {
+        let file_handle = fs::File::open(file)?;
+        let reader = binary::BinaryReader::new(std::io::BufReader::new(file_handle))?;
+        return Ok(reader.header().schema.clone());
+    }
+
+    let mut json: serde_json::Value = serde_json::from_str(&fs::read_to_string(file)?)?;
+    if let Some(schema) = json.get_mut("schema") {
+        json = schema.take();
+    }
+    let json_schema: JsonSchema = serde_json::from_value(json)?;
+    convert_json_schema_to_table_schema(&json_schema)
+}
+
 fn validate_data(data_file: &PathBuf, schema_file: &PathBuf) -> Result<()> {
     println!(
         "Validating {} against {}",
@@ -428,9 +733,10 @@ fn validate_data(data_file: &PathBuf, schema_file: &PathBuf) -> Result<()> {
                 striped_table.row_count()
             );
 
-            // Try roundtrip
+            // Try roundtrip. Struct fields come back in schema order, so
+            // compare the striped forms rather than the input field order
             let roundtrip = striped_table.to_logical()?;
-            if logical_data == roundtrip {
+            if striped::Table::from_logical(&schema, &roundtrip)? == striped_table {
                 println!("Roundtrip test: PASS");
             } else {
                 println!("Roundtrip test: FAIL");
@@ -448,7 +754,7 @@ fn validate_data(data_file: &PathBuf, schema_file: &PathBuf) -> Result<()> {
 fn convert_json_schema_to_table_schema(json_schema: &JsonSchema) -> Result<TableSchema> {
     match json_schema.schema_type.as_str() {
         "array" => {
-            let default = parse_default(&json_schema.default.as_deref().unwrap_or("allow"))?;
+            let default = parse_default(json_schema.default.as_deref().unwrap_or("allow"))?;
             let element = json_schema
                 .element
                 .as_ref()
@@ -460,13 +766,18 @@ fn convert_json_schema_to_table_schema(json_schema: &JsonSchema) -> Result<Table
             })
         }
         "binary" => {
-            let default = parse_default(&json_schema.default.as_deref().unwrap_or("allow"))?;
+            let default = parse_default(json_schema.default.as_deref().unwrap_or("allow"))?;
             let encoding = parse_encoding(json_schema.encoding.as_deref().unwrap_or("binary"))?;
             Ok(TableSchema::Binary { default, encoding })
         }
         "map" => {
-            // This is simplified - would need key/value schemas
-            Err(eyre::eyre!("Map table schema not yet implemented in CLI"))
+            let default = parse_default(json_schema.default.as_deref().unwrap_or("allow"))?;
+            let (key, value) = convert_json_map_schemas(json_schema)?;
+            Ok(TableSchema::Map {
+                default,
+                key: Box::new(key),
+                value: Box::new(value),
+            })
         }
         _ => Err(eyre::eyre!(
             "Unknown table schema type: {}",
@@ -476,7 +787,7 @@ fn convert_json_schema_to_table_schema(json_schema: &JsonSchema) -> Result<Table
 }
 
 fn convert_json_schema_to_value_schema(json_schema: &JsonSchema) -> Result<ValueSchema> {
-    let default = parse_default(&json_schema.default.as_deref().unwrap_or("allow"))?;
+    let default = parse_default(json_schema.default.as_deref().unwrap_or("allow"))?;
 
     match json_schema.schema_type.as_str() {
         "unit" => Ok(ValueSchema::Unit),
@@ -519,6 +830,14 @@ fn convert_json_schema_to_value_schema(json_schema: &JsonSchema) -> Result<Value
                 fields: field_schemas?,
             })
         }
+        "map" => {
+            let (key, value) = convert_json_map_schemas(json_schema)?;
+            Ok(ValueSchema::Map {
+                default,
+                key: Box::new(key),
+                value: Box::new(value),
+            })
+        }
         _ => Err(eyre::eyre!(
             "Unknown value schema type: {}",
             json_schema.schema_type
@@ -526,6 +845,21 @@ fn convert_json_schema_to_value_schema(json_schema: &JsonSchema) -> Result<Value
     }
 }
 
+fn convert_json_map_schemas(json_schema: &JsonSchema) -> Result<(ValueSchema, ValueSchema)> {
+    let key = json_schema
+        .key
+        .as_ref()
+        .ok_or_else(|| eyre::eyre!("Map schema missing key"))?;
+    let value = json_schema
+        .value
+        .as_ref()
+        .ok_or_else(|| eyre::eyre!("Map schema missing value"))?;
+    Ok((
+        convert_json_schema_to_value_schema(key)?,
+        convert_json_schema_to_value_schema(value)?,
+    ))
+}
+
 fn parse_default(default_str: &str) -> Result<Default> {
     match default_str {
         "allow" => Ok(Default::Allow),
@@ -534,6 +868,48 @@ fn parse_default(default_str: &str) -> Result<Default> {
     }
 }
 
+fn parse_compression(compression_str: &str) -> Result<CompressionAlgorithm> {
+    let (name, level) = match compression_str.split_once(':') {
+        Some((name, level)) => (name, Some(level)),
+        None => (compression_str, None),
+    };
+    fn parse_level<T>(level: &str, levels: RangeInclusive<T>) -> Result<T>
+    where
+        T: std::str::FromStr + PartialOrd + std::fmt::Display,
+    {
+        match level.parse() {
+            Ok(parsed) if levels.contains(&parsed) => Ok(parsed),
+            _ => Err(eyre::eyre!(
+                "Invalid compression level: {} (expected {} to {})",
+                level,
+                levels.start(),
+                levels.end()
+            )),
+        }
+    }
+    match (name, level) {
+        ("none", None) => Ok(CompressionAlgorithm::None),
+        ("lz4", None) => Ok(CompressionAlgorithm::Lz4),
+        ("snappy", None) => Ok(CompressionAlgorithm::Snappy),
+        ("zstd", _) => Ok(CompressionAlgorithm::Zstd {
+            level: parse_level(level.unwrap_or("3"), zstd_levels())?,
+        }),
+        ("brotli", _) => Ok(CompressionAlgorithm::Brotli {
+            level: parse_level(level.unwrap_or("11"), BROTLI_LEVELS)?,
+        }),
+        _ => Err(eyre::eyre!("Unknown compression: {}", compression_str)),
+    }
+}
+
+fn parse_optimization_target(target_str: &str) -> Result<OptimizationTarget> {
+    match target_str {
+        "space" => Ok(OptimizationTarget::MinimizeSpace),
+        "balanced" => Ok(OptimizationTarget::Balanced),
+        "speed" => Ok(OptimizationTarget::MaximizeSpeed),
+        _ => Err(eyre::eyre!("Unknown optimization target: {}", target_str)),
+    }
+}
+
 fn parse_encoding(encoding_str: &str) -> Result<Encoding> {
     match encoding_str {
         "int" => Ok(Encoding::Int(IntEncoding::Int)),
@@ -554,6 +930,15 @@ fn convert_json_value_to_table(json_value: &serde_json::Value) -> Result<Table>
             Ok(Table::Array(values?))
         }
         serde_json::Value::String(s) => Ok(Table::Binary(s.as_bytes().to_vec())),
+        serde_json::Value::Object(obj) if obj.contains_key("map") => {
+            match convert_json_value_to_value(json_value)? {
+                Value::Map(pairs) => Ok(Table::Map(pairs)),
+                value => Err(eyre::eyre!(
+                    "Cannot convert JSON value to map table: {:?}",
+                    value
+                )),
+            }
+        }
         _ => Err(eyre::eyre!(
             "Cannot convert JSON value to table: {:?}",
             json_value
@@ -579,19 +964,34 @@ fn convert_json_value_to_value(json_value: &serde_json::Value) -> Result<Value>
             Ok(Value::Array(values?))
         }
         serde_json::Value::Object(obj) => {
-            if let Some(struct_obj) = obj.get("struct") {
-                if let serde_json::Value::Object(fields) = struct_obj {
-                    let field_values: Result<Vec<_>> = fields
-                        .iter()
-                        .map(|(name, value)| {
-                            Ok(Field {
-                                name: name.clone(),
-                                value: convert_json_value_to_value(value)?,
-                            })
+            if let Some(serde_json::Value::Object(fields)) = obj.get("struct") {
+                let field_values: Result<Vec<_>> = fields
+                    .iter()
+                    .map(|(name, value)| {
+                        Ok(Field {
+                            name: name.clone(),
+                            value: convert_json_value_to_value(value)?,
                         })
-                        .collect();
-                    return Ok(Value::Struct(field_values?));
-                }
+                    })
+                    .collect();
+                return Ok(Value::Struct(field_values?));
+            }
+            // Maps are lists of [key, value] pairs, since keys need not be strings
+            if let Some(serde_json::Value::Array(entries)) = obj.get("map") {
+                let pairs: Result<Vec<_>> = entries
+                    .iter()
+                    .map(|entry| match entry.as_array().map(Vec::as_slice) {
+                        Some([key, value]) => Ok((
+                            convert_json_value_to_value(key)?,
+                            convert_json_value_to_value(value)?,
+                        )),
+                        _ => Err(eyre::eyre!(
+                            "Map entry must be a [key, value] pair: {}",
+                            entry
+                        )),
+                    })
+                    .collect();
+                return Ok(Value::Map(pairs?));
             }
             Err(eyre::eyre!(
                 "Cannot convert JSON object to value: {:?}",
@@ -657,6 +1057,18 @@ fn value_schema_to_json(schema: &ValueSchema) -> serde_json::Value {
                 "encoding": encoding_to_string(encoding)
             })
         }
+        ValueSchema::Map {
+            default,
+            key,
+            value,
+        } => {
+            serde_json::json!({
+                "type": "map",
+                "default": default_to_string(default),
+                "key": value_schema_to_json(key),
+                "value": value_schema_to_json(value)
+            })
+        }
         _ => serde_json::json!({"type": "complex"}), // Simplified for now
     }
 }
@@ -671,7 +1083,7 @@ fn table_to_json(table: &Table) -> serde_json::Value {
             let text = String::from_utf8_lossy(data);
             serde_json::Value::String(text.to_string())
         }
-        Table::Map(_) => serde_json::json!("map_not_implemented"),
+        Table::Map(pairs) => map_to_json(pairs),
     }
 }
 
@@ -695,10 +1107,19 @@ fn value_to_json(value: &Value) -> serde_json::Value {
             }
             serde_json::json!({"struct": obj})
         }
+        Value::Map(pairs) => map_to_json(pairs),
         _ => serde_json::json!("complex_value"),
     }
 }
 
+fn map_to_json(pairs: &[(Value, Value)]) -> serde_json::Value {
+    let entries: Vec<_> = pairs
+        .iter()
+        .map(|(key, value)| serde_json::json!([value_to_json(key), value_to_json(value)]))
+        .collect();
+    serde_json::json!({ "map": entries })
+}
+
 fn default_to_string(default: &Default) -> &'static str {
     match default {
         Default::Allow => "allow",
@@ -914,6 +1335,20 @@ fn striped_column_to_json(column: &striped::Column) -> serde_json::Value {
                 "inner": striped_column_to_json(inner)
             })
         }
+        striped::Column::Map {
+            default,
+            lengths,
+            key,
+            value,
+        } => {
+            serde_json::json!({
+                "type": "map",
+                "default": default_to_string(default),
+                "lengths": lengths,
+                "key": striped_column_to_json(key),
+                "value": striped_column_to_json(value)
+            })
+        }
     }
 }
 
@@ -1016,8 +1451,7 @@ fn json_to_striped_column(json_value: &serde_json::Value) -> Result<striped::Col
                 .as_array()
                 .ok_or_else(|| eyre::eyre!("Binary data must be an array"))?
                 .iter()
-                .map(|v| v.as_str().unwrap_or("").bytes().collect::<Vec<u8>>())
-                .flatten()
+                .flat_map(|v| v.as_str().unwrap_or("").bytes().collect::<Vec<u8>>())
                 .collect();
 
             Ok(striped::Column::Binary {
@@ -1104,6 +1538,24 @@ fn json_to_striped_column(json_value: &serde_json::Value) -> Result<striped::Col
                 inner: Box::new(inner),
             })
         }
+        "map" => {
+            let default = string_to_default(json_value["default"].as_str().unwrap_or("allow"))?;
+            let lengths = json_value["lengths"]
+                .as_array()
+                .ok_or_else(|| eyre::eyre!("Map lengths must be an array"))?
+                .iter()
+                .map(|v| v.as_u64().unwrap_or(0) as usize)
+                .collect();
+            let key = json_to_striped_column(&json_value["key"])?;
+            let value = json_to_striped_column(&json_value["value"])?;
+
+            Ok(striped::Column::Map {
+                default,
+                lengths,
+                key: Box::new(key),
+                value: Box::new(value),
+            })
+        }
         _ => Err(eyre::eyre!(
             "Unsupported striped column type: {}",
             column_type
@@ -1216,5 +1668,19 @@ fn infer_schema_from_striped_column(column: &striped::Column) -> Result<ValueSch
                 inner: Box::new(inner_schema),
             })
         }
+        striped::Column::Map {
+            default,
+            key,
+            value,
+            ..
+        } => {
+            let key_schema = infer_schema_from_striped_column(key)?;
+            let value_schema = infer_schema_from_striped_column(value)?;
+            Ok(ValueSchema::Map {
+                default: default.clone(),
+                key: Box::new(key_schema),
+                value: Box::new(value_schema),
+            })
+        }
     }
 }
@@ -326,11 +326,15 @@ fn bench_binary_format_roundtrip(c: &mut Criterion) {
         let no_compression = CompressionConfig {
             binary_data: CompressionAlgorithm::None,
             strings: CompressionAlgorithm::None,
+            columns: Vec::new(),
+            optimization_target: OptimizationTarget::MinimizeSpace,
         };
 
         let zstd_compression = CompressionConfig {
             binary_data: CompressionAlgorithm::Zstd { level: 3 },
             strings: CompressionAlgorithm::Zstd { level: 3 },
+            columns: Vec::new(),
+            optimization_target: OptimizationTarget::MinimizeSpace,
         };
 
         group.bench_with_input(
@@ -219,6 +219,8 @@ fn bench_streaming_write(c: &mut Criterion) {
             let no_compression = CompressionConfig {
                 binary_data: CompressionAlgorithm::None,
                 strings: CompressionAlgorithm::None,
+                columns: Vec::new(),
+                optimization_target: OptimizationTarget::MinimizeSpace,
             };
 
             group.bench_with_input(
@@ -245,6 +247,8 @@ fn bench_streaming_write(c: &mut Criterion) {
             let zstd_compression = CompressionConfig {
                 binary_data: CompressionAlgorithm::Zstd { level: 3 },
                 strings: CompressionAlgorithm::Zstd { level: 3 },
+                columns: Vec::new(),
+                optimization_target: OptimizationTarget::MinimizeSpace,
             };
 
             group.bench_with_input(
@@ -286,6 +290,8 @@ fn bench_streaming_read(c: &mut Criterion) {
             let no_compression = CompressionConfig {
                 binary_data: CompressionAlgorithm::None,
                 strings: CompressionAlgorithm::None,
+                columns: Vec::new(),
+                optimization_target: OptimizationTarget::MinimizeSpace,
             };
 
             let mut no_compression_data = Vec::new();
@@ -318,6 +324,8 @@ fn bench_streaming_read(c: &mut Criterion) {
             let zstd_compression = CompressionConfig {
                 binary_data: CompressionAlgorithm::Zstd { level: 3 },
                 strings: CompressionAlgorithm::Zstd { level: 3 },
+                columns: Vec::new(),
+                optimization_target: OptimizationTarget::MinimizeSpace,
             };
 
             let mut zstd_compression_data = Vec::new();
@@ -364,6 +372,8 @@ fn bench_time_series_streaming(c: &mut Criterion) {
             let compression = CompressionConfig {
                 binary_data: CompressionAlgorithm::Zstd { level: 3 },
                 strings: CompressionAlgorithm::Zstd { level: 3 },
+                columns: Vec::new(),
+                optimization_target: OptimizationTarget::MinimizeSpace,
             };
 
             // Write benchmark
@@ -434,6 +444,8 @@ fn bench_log_streaming(c: &mut Criterion) {
         let compression = CompressionConfig {
             binary_data: CompressionAlgorithm::Zstd { level: 3 },
             strings: CompressionAlgorithm::Zstd { level: 3 },
+            columns: Vec::new(),
+            optimization_target: OptimizationTarget::MinimizeSpace,
         };
 
         // Write benchmark
@@ -488,7 +500,7 @@ fn bench_log_streaming(c: &mut Criterion) {
         );
 
         // Measure compression effectiveness
-        let uncompressed_size: usize = tables.iter().map(|t| estimate_uncompressed_size(t)).sum();
+        let uncompressed_size: usize = tables.iter().map(estimate_uncompressed_size).sum();
         let compressed_size: usize = serialized_data.iter().map(|d| d.len()).sum();
         let compression_ratio = uncompressed_size as f64 / compressed_size as f64;
 
@@ -609,8 +621,8 @@ fn estimate_uncompressed_size(table: &Table) -> usize {
 
 fn estimate_column_size(column: &Column) -> usize {
     match column {
-        Column::Unit { count } => *count * 0, // Unit takes no space
-        Column::Int { values, .. } => values.len() * 8, // 8 bytes per i64
+        Column::Unit { .. } => 0,                          // Unit takes no space
+        Column::Int { values, .. } => values.len() * 8,    // 8 bytes per i64
         Column::Double { values, .. } => values.len() * 8, // 8 bytes per f64
         Column::Binary { data, .. } => data.len(),
         Column::Array {
@@ -634,6 +646,12 @@ fn estimate_column_size(column: &Column) -> usize {
             total_elements * 8 + estimate_uncompressed_size(table)
         }
         Column::Reversed { inner } => estimate_column_size(inner),
+        Column::Map {
+            lengths,
+            key,
+            value,
+            ..
+        } => lengths.len() * 8 + estimate_column_size(key) + estimate_column_size(value),
     }
 }
 
@@ -1,57 +1,243 @@
 // Binary layer - compressed disk/wire format
 
-use crate::compression::{compress_int_array, decompress_int_array, CompressionConfig};
-use crate::data::{BinaryEncoding, Default, Encoding, IntEncoding};
-use crate::error::{BinaryError, Result};
-use crate::logical::TableSchema;
+use crate::compression::{
+    choose_dictionary, compress_binary, compress_int_array_adaptive, decode_int_array,
+    decode_int_runs, decompress_binary_bounded, decompress_int_array,
+    decompress_int_array_untagged, dictionary_decode, encode_int_array, max_encoded_int_array_len,
+    BinaryDictionary, ColumnCompressionConfig, CompressionAlgorithm, CompressionConfig,
+    IntArrayEncoding, IntPipeline, IntRun, OptimizationTarget, DEFAULT_INT_ENCODINGS,
+    DOUBLE_ENCODINGS, TIME_INT_ENCODINGS,
+};
+use crate::data::{
+    BinaryEncoding, Default, Encoding, IntEncoding, Table as LogicalTable, Value as LogicalValue,
+};
+use crate::error::{BinaryError, ConversionError, Result};
+use crate::logical::{FieldSchema, TableSchema, ValueSchema, VariantSchema};
+use crate::predicate::Predicate;
+use crate::stats::{BlockStats, ColumnStats, ValueStats};
 use crate::striped::{Column, FieldColumn, Table, VariantColumn};
-use std::io::{Read, Write};
+use std::collections::BTreeMap;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::Range;
 
 /// Binary format constants
 ///
 /// Magic number format: "||_ZBRA||vvvvv||" where vvvvv is the 5-digit version
-/// - Version 1: "||_ZBRA||00001||" (current)
+/// - Version 1: "||_ZBRA||00001||" (read only, header stored as raw JSON)
+/// - Version 2: "||_ZBRA||00002||" (current, Zstd-compressed header and
+///   reserved bytes)
+/// - Version 3: "||_ZBRA||00003||" (opt-in, unified binary header with
+///   metadata)
 ///
 /// The version is embedded directly in the magic number, so no separate version
 /// field is needed.
-const MAGIC_NUMBER: &[u8; 16] = b"||_ZBRA||00001||";
+const MAGIC_NUMBER_V1: &[u8; 16] = b"||_ZBRA||00001||";
+const MAGIC_NUMBER_V2: &[u8; 16] = b"||_ZBRA||00002||";
+const MAGIC_NUMBER_V3: &[u8; 16] = b"||_ZBRA||00003||";
+
+/// Magic number bytes surrounding the 5-digit version
+const MAGIC_PREFIX: &[u8; 9] = b"||_ZBRA||";
+const MAGIC_SUFFIX: &[u8; 2] = b"||";
+
+/// Compression applied to the schema and compression config in the header
+const HEADER_COMPRESSION: CompressionAlgorithm = CompressionAlgorithm::Zstd { level: 3 };
+
+/// Size of the zeroed region closing a version 2 header, reserved for future
+/// metadata
+const HEADER_RESERVED_SIZE_V2: usize = 32;
+
+/// Size of the zeroed region closing a version 3 header, reserved for future
+/// extensions
+const HEADER_RESERVED_SIZE_V3: usize = 64;
+
+/// Deepest schema nesting read from a binary header, the same limit
+/// serde_json puts on JSON headers
+const MAX_SCHEMA_DEPTH: usize = 128;
+
+/// Block count written by `BinaryWriter`, which only knows the real count once
+/// the last block is flushed. Readers seeing it read blocks up to the trailer.
+const STREAMING_BLOCK_COUNT: u32 = u32::MAX;
+
+/// Written in place of a block row count to mark the trailer of a streamed file
+const TRAILER_MARKER: u32 = u32::MAX;
+
+/// Last bytes of a file carrying a block index footer
+const INDEX_MAGIC: &[u8; 8] = b"||_ZIX||";
+
+/// Size of the fixed tail locating the block index: index offset + index magic
+const INDEX_TAIL_SIZE: u64 = 16;
+
+/// Size of one block index entry: offset + length + row_count
+const INDEX_ENTRY_SIZE: u64 = 20;
+
+/// Size of the CRC32 closing every block
+const CHECKSUM_SIZE: usize = 4;
+
+/// Versions of the binary format this library reads and writes
+///
+/// Readers detect the version from the magic number and decode the header
+/// accordingly. Writers produce `FormatVersion::CURRENT` unless asked for
+/// another version, e.g. for readers that have not been upgraded yet.
+/// Version 1 can only be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatVersion {
+    /// Schema and compression config stored as raw JSON, read only
+    V1,
+    /// Zstd-compressed header followed by reserved bytes
+    V2,
+    /// Unified header with binary-encoded schema, compression config and
+    /// key/value metadata
+    V3,
+}
+
+impl FormatVersion {
+    /// Version written by default
+    pub const CURRENT: FormatVersion = FormatVersion::V2;
+
+    /// Look up a version by its number, as found in the magic number
+    pub fn from_number(number: u32) -> Result<Self> {
+        match number {
+            1 => Ok(FormatVersion::V1),
+            2 => Ok(FormatVersion::V2),
+            3 => Ok(FormatVersion::V3),
+            _ => Err(BinaryError::UnsupportedVersion(number)),
+        }
+    }
+
+    /// Version number embedded in the magic number
+    pub fn number(self) -> u32 {
+        match self {
+            FormatVersion::V1 => 1,
+            FormatVersion::V2 => 2,
+            FormatVersion::V3 => 3,
+        }
+    }
+
+    /// Fail unless files can be written in this version
+    ///
+    /// Version 1 blocks have none of the statistics, checksums or encodings
+    /// every writer here produces, so a file claiming to be version 1 would
+    /// not open in version 1 readers.
+    fn check_writable(self) -> Result<()> {
+        if self == FormatVersion::V1 {
+            return Err(BinaryError::SerializationError(
+                "Format version 1 is read only; write version 2 or later".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn magic_number(self) -> &'static [u8; 16] {
+        match self {
+            FormatVersion::V1 => MAGIC_NUMBER_V1,
+            FormatVersion::V2 => MAGIC_NUMBER_V2,
+            FormatVersion::V3 => MAGIC_NUMBER_V3,
+        }
+    }
+}
+
+/// Parse the version number out of a magic number, without checking that
+/// this library supports it
+pub fn detect_version(magic: &[u8; 16]) -> Result<u32> {
+    if !magic.starts_with(MAGIC_PREFIX) || !magic.ends_with(MAGIC_SUFFIX) {
+        return Err(BinaryError::InvalidMagicNumber);
+    }
+    let digits = &magic[MAGIC_PREFIX.len()..magic.len() - MAGIC_SUFFIX.len()];
+    if !digits.iter().all(u8::is_ascii_digit) {
+        return Err(BinaryError::InvalidMagicNumber);
+    }
+    // Five ASCII digits always parse as a u32
+    Ok(digits
+        .iter()
+        .fold(0, |number, digit| number * 10 + (digit - b'0') as u32))
+}
 
 /// Binary format header
 #[derive(Debug, Clone)]
 pub struct Header {
     pub schema: TableSchema,
     pub compression: CompressionConfig,
+    /// Free-form key/value metadata, only stored by version 3 headers
+    pub metadata: BTreeMap<String, String>,
 }
 
 /// Binary format file structure
 ///
 /// File layout:
 /// ```text
-/// [Magic Number: 16 bytes] "||_ZBRA||00001||"
-/// [Header Length: 4 bytes] total header size (little-endian u32)
-/// [Header CRC32: 4 bytes] header checksum (little-endian u32)
+/// [Magic Number: 16 bytes] "||_ZBRA||00002||"
+/// [Header Length: 4 bytes] size of the rest of the header up to the block count (little-endian u32)
+/// [Header CRC32: 4 bytes] CRC32 of the rest of the header (little-endian u32)
 /// [Schema Size: 4 bytes] uncompressed_size (little-endian u32)
-/// [Schema Size: 4 bytes] compressed_size (little-endian u32)  
+/// [Schema Size: 4 bytes] compressed_size (little-endian u32)
 /// [Schema Data: N bytes] JSON-encoded TableSchema (compressed with Zstd)
 /// [Compression Config Size: 4 bytes] uncompressed_size (little-endian u32)
 /// [Compression Config Size: 4 bytes] compressed_size (little-endian u32)
 /// [Compression Config Data: N bytes] JSON-encoded CompressionConfig (compressed with Zstd)
 /// [Reserved: 32 bytes] reserved for future metadata (zeros)
 /// [Block Count: 4 bytes] number of blocks (little-endian u32)
-/// [Block 0: Variable] row_count + striped table data
+/// [Block 0: Variable] row_count + column statistics + striped table data + CRC32
 /// [Block 1: Variable] ...
 /// ```
 ///
-/// FUTURE: Consider consolidating schema + compression into single header block:
+/// Each block starts with its row_count (little-endian u32), followed by the
+/// length-prefixed `BlockStats` computed when the block was written, so
+/// readers can inspect or skip them before decoding the table.
+///
+/// Each block ends with the CRC32 of all its preceding bytes (little-endian
+/// u32). Readers verify the header checksum when parsing the header and a
+/// block checksum before decoding any part of the block's table; reading
+/// only the statistics of a block leaves its checksum unchecked.
+///
+/// Every column inside a block is prefixed with its encoded length in bytes
+/// (little-endian u32), so readers can skip columns they do not need.
+///
+/// Files written by `BinaryWriter` store `0xFFFFFFFF` as the block count and
+/// end the block sequence with a trailer instead:
+/// ```text
+/// [Trailer Marker: 4 bytes] 0xFFFFFFFF in place of a block row_count
+/// [Block Count: 4 bytes] final number of blocks (little-endian u32)
+/// ```
+///
+/// Both forms are followed by a block index footer for random block access:
 /// ```text
-/// [Magic Number: 16 bytes] "||_ZBRA||00002||"
-/// [Header Length: 4 bytes] total header size
-/// [Header CRC32: 4 bytes] header checksum
-/// [Header Data: N bytes] protobuf-encoded unified header
-/// [Reserved: 64 bytes] reserved for future extensions
-/// [Block Count: 4 bytes] number of blocks
-/// [Block Data: Variable] compressed blocks
+/// [Index Entry Count: 4 bytes] number of entries (little-endian u32)
+/// [Index Entry: 20 bytes] per block: byte offset of the block from the start
+///                         of the file (u64), block length in bytes (u64),
+///                         row_count (u32), all little-endian
+/// [Index Offset: 8 bytes] byte offset of the index from the start of the file
+/// [Index Magic: 8 bytes] "||_ZIX||"
+/// ```
+///
+/// Version 3 files replace everything between the magic number and the block
+/// count with a single binary-encoded header:
+/// ```text
+/// [Magic Number: 16 bytes] "||_ZBRA||00003||"
+/// [Header Length: 4 bytes] size of the header data and reserved bytes (little-endian u32)
+/// [Header CRC32: 4 bytes] CRC32 of the header data and reserved bytes (little-endian u32)
+/// [Header Data: N bytes] binary-encoded TableSchema, CompressionConfig and
+///                        metadata entry count (u32) followed by key/value
+///                        string pairs
+/// [Reserved: 64 bytes] reserved for future extensions (zeros)
 /// ```
+/// Schemas use the table and column tags of the block data, strings are
+/// length-prefixed UTF-8 and counts are little-endian u32.
+///
+/// Version 1 files predate everything above but the magic number, the block
+/// count and the table encoding:
+/// ```text
+/// [Magic Number: 16 bytes] "||_ZBRA||00001||"
+/// [Schema Size: 4 + 4 bytes] uncompressed_size, compressed_size (equal)
+/// [Schema Data: N bytes] JSON-encoded TableSchema
+/// [Compression Config Size: 4 + 4 bytes] uncompressed_size, compressed_size (equal)
+/// [Compression Config Data: N bytes] JSON-encoded CompressionConfig
+/// [Block Count: 4 bytes] number of blocks (little-endian u32)
+/// [Block 0: Variable] row_count + striped table data
+/// [Block 1: Variable] ...
+/// ```
+/// Their columns carry no length prefix, their int arrays no encoding tag and
+/// only column tags 0 to 8 exist. There are no statistics, checksums, trailer
+/// or block index, so version 1 files can only be read block by block.
 #[derive(Debug, Clone)]
 pub struct BinaryFile {
     pub header: Header,
@@ -65,13 +251,149 @@ pub struct Block {
     pub table: Table,
 }
 
+/// Location of a block within a binary file, as recorded in the block index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockIndexEntry {
+    /// Byte offset of the block from the start of the file
+    pub offset: u64,
+    /// Encoded length of the block in bytes
+    pub length: u64,
+    pub row_count: u32,
+}
+
+/// Struct field paths to decode when reading a block
+///
+/// Paths descend through struct fields by name, and pass through array
+/// elements, nested tables, reversed columns and the value column of map
+/// tables. Columns outside every path are skipped without being decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Projection {
+    /// Decode the whole column
+    All,
+    /// Decode only the named struct fields, each with its own projection
+    Fields(Vec<(String, Projection)>),
+}
+
+impl Projection {
+    /// Build a projection from field paths, e.g. `&[&["metrics", "cpu"]]`
+    pub fn new(paths: &[&[&str]]) -> Result<Self> {
+        if paths.is_empty() {
+            return Err(BinaryError::InvalidProjection(
+                "No field paths given".to_string(),
+            ));
+        }
+        let mut projection = Projection::Fields(Vec::new());
+        for path in paths {
+            projection.insert(path);
+        }
+        Ok(projection)
+    }
+
+    /// The schema of the tables this projection reads from a file with the
+    /// given schema
+    pub fn table_schema(&self, schema: &TableSchema) -> Result<TableSchema> {
+        if *self == Projection::All {
+            return Ok(schema.clone());
+        }
+        match schema {
+            TableSchema::Binary { .. } => Err(BinaryError::InvalidProjection(
+                "Binary tables have no fields".to_string(),
+            )),
+            TableSchema::Array { default, element } => Ok(TableSchema::Array {
+                default: default.clone(),
+                element: Box::new(self.value_schema(element)?),
+            }),
+            TableSchema::Map {
+                default,
+                key,
+                value,
+            } => Ok(TableSchema::Map {
+                default: default.clone(),
+                key: key.clone(),
+                value: Box::new(self.value_schema(value)?),
+            }),
+        }
+    }
+
+    fn value_schema(&self, schema: &ValueSchema) -> Result<ValueSchema> {
+        let Projection::Fields(selected) = self else {
+            return Ok(schema.clone());
+        };
+        match schema {
+            ValueSchema::Array { default, element } => Ok(ValueSchema::Array {
+                default: default.clone(),
+                element: Box::new(self.value_schema(element)?),
+            }),
+            ValueSchema::Struct { default, fields } => {
+                if let Some((missing, _)) = selected
+                    .iter()
+                    .find(|(name, _)| !fields.iter().any(|field| field.name == *name))
+                {
+                    return Err(BinaryError::InvalidProjection(format!(
+                        "Unknown field: {}",
+                        missing
+                    )));
+                }
+                // Fields keep their order in the file, as when reading
+                let mut projected = Vec::new();
+                for field in fields {
+                    if let Some((_, child)) = selected.iter().find(|(name, _)| *name == field.name)
+                    {
+                        projected.push(FieldSchema {
+                            name: field.name.clone(),
+                            schema: child.value_schema(&field.schema)?,
+                        });
+                    }
+                }
+                Ok(ValueSchema::Struct {
+                    default: default.clone(),
+                    fields: projected,
+                })
+            }
+            ValueSchema::Nested { table } => Ok(ValueSchema::Nested {
+                table: Box::new(self.table_schema(table)?),
+            }),
+            ValueSchema::Reversed { inner } => Ok(ValueSchema::Reversed {
+                inner: Box::new(self.value_schema(inner)?),
+            }),
+            ValueSchema::Map {
+                default,
+                key,
+                value,
+            } => Ok(ValueSchema::Map {
+                default: default.clone(),
+                key: key.clone(),
+                value: Box::new(self.value_schema(value)?),
+            }),
+            _ => Err(BinaryError::InvalidProjection(format!(
+                "Field path continues past a leaf column: {:?}",
+                self
+            ))),
+        }
+    }
+
+    fn insert(&mut self, path: &[&str]) {
+        let Some((name, rest)) = path.split_first() else {
+            *self = Projection::All;
+            return;
+        };
+        if let Projection::Fields(fields) = self {
+            match fields.iter_mut().find(|(field, _)| field == name) {
+                Some((_, child)) => child.insert(rest),
+                None => {
+                    let mut child = Projection::Fields(Vec::new());
+                    child.insert(rest);
+                    fields.push((name.to_string(), child));
+                }
+            }
+        }
+    }
+}
+
 impl BinaryFile {
     /// Create a new binary file from a schema and striped table
     pub fn new(schema: TableSchema, table: Table) -> Self {
-        let header = Header {
-            schema,
-            compression: CompressionConfig::default(),
-        };
+        let header = Header::new(schema, CompressionConfig::default());
         let row_count = table.row_count() as u32;
         let blocks = vec![Block { row_count, table }];
         BinaryFile { header, blocks }
@@ -83,10 +405,7 @@ impl BinaryFile {
         table: Table,
         compression: CompressionConfig,
     ) -> Self {
-        let header = Header {
-            schema,
-            compression,
-        };
+        let header = Header::new(schema, compression);
         let row_count = table.row_count() as u32;
         let blocks = vec![Block { row_count, table }];
         BinaryFile { header, blocks }
@@ -94,38 +413,46 @@ impl BinaryFile {
 
     /// Serialize to bytes
     pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        self.to_bytes_version(FormatVersion::CURRENT)
+    }
+
+    /// Serialize to bytes in the given format version
+    pub fn to_bytes_version(&self, version: FormatVersion) -> Result<Vec<u8>> {
         let mut writer = Vec::new();
-        self.write_to(&mut writer)?;
+        self.write_version_to(&mut writer, version)?;
         Ok(writer)
     }
 
     /// Write to a writer
     pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
-        // Write magic number
-        writer.write_all(MAGIC_NUMBER)?;
-
-        // Serialize schema to JSON
-        let schema_json = serde_json::to_string(&self.header.schema)
-            .map_err(|e| BinaryError::SerializationError(e.to_string()))?;
-        let schema_bytes = schema_json.as_bytes();
+        self.write_version_to(writer, FormatVersion::CURRENT)
+    }
 
-        // Write schema as sized byte array
-        write_sized_byte_array(writer, schema_bytes)?;
+    /// Write to a writer in the given format version
+    pub fn write_version_to<W: Write>(&self, writer: &mut W, version: FormatVersion) -> Result<()> {
+        version.check_writable()?;
+        let mut writer = CountingWriter::new(writer);
 
-        // Serialize compression config to JSON
-        let compression_json = serde_json::to_string(&self.header.compression)
-            .map_err(|e| BinaryError::SerializationError(e.to_string()))?;
-        let compression_bytes = compression_json.as_bytes();
+        // Write magic number
+        writer.write_all(version.magic_number())?;
 
-        // Write compression config as sized byte array
-        write_sized_byte_array(writer, compression_bytes)?;
+        self.header.write_version_to(&mut writer, version)?;
 
         // Write blocks
-        write_u32(writer, self.blocks.len() as u32)?;
+        write_u32(&mut writer, self.blocks.len() as u32)?;
+        let mut index = Vec::with_capacity(self.blocks.len());
         for block in &self.blocks {
-            block.write_to(writer, &self.header.compression)?;
+            let offset = writer.position;
+            block.write_to(&mut writer, &self.header.compression)?;
+            index.push(BlockIndexEntry {
+                offset,
+                length: writer.position - offset,
+                row_count: block.row_count,
+            });
         }
 
+        write_block_index(&mut writer, &index)?;
+
         Ok(())
     }
 
@@ -137,577 +464,4071 @@ impl BinaryFile {
 
     /// Read from a reader
     pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-        // Check magic number
-        let mut magic = [0u8; 16];
-        reader.read_exact(&mut magic)?;
-        if &magic != MAGIC_NUMBER {
-            return Err(BinaryError::InvalidMagicNumber);
+        let mut block_reader = BinaryReader::new(reader)?;
+        let blocks = block_reader.by_ref().collect::<Result<Vec<_>>>()?;
+        Ok(BinaryFile {
+            header: block_reader.header,
+            blocks,
+        })
+    }
+
+    /// Get the table from the first block (for simple cases)
+    pub fn table(&self) -> Option<&Table> {
+        self.blocks.first().map(|block| &block.table)
+    }
+}
+
+/// Read every row of a file under a newer reader schema
+///
+/// The blocks are decoded under the schema stored in the file header and each
+/// row is resolved against `reader_schema`: added fields are filled with
+/// defaults where their `Default` policy allows it and removed fields are
+/// dropped. Schemas that cannot be resolved fail before any block is read.
+pub fn read_with_schema<R: Read>(
+    file: &mut R,
+    reader_schema: &TableSchema,
+) -> std::result::Result<LogicalTable, ConversionError> {
+    let mut reader = BinaryReader::new(file)?;
+    let writer_schema = reader.header().schema.clone();
+    writer_schema.check_resolvable(reader_schema)?;
+
+    let mut resolved = LogicalTable::default_for_schema(reader_schema);
+    for block in reader.by_ref() {
+        let rows = block?.table.to_logical()?;
+        resolved = match (resolved, rows.resolve(&writer_schema, reader_schema)?) {
+            (LogicalTable::Array(mut values), LogicalTable::Array(rows)) => {
+                values.extend(rows);
+                LogicalTable::Array(values)
+            }
+            (LogicalTable::Binary(mut data), LogicalTable::Binary(rows)) => {
+                data.extend(rows);
+                LogicalTable::Binary(data)
+            }
+            (resolved, rows) => resolved.merge(&rows)?,
+        };
+    }
+    Ok(resolved)
+}
+
+impl Header {
+    /// Create a header without metadata
+    pub fn new(schema: TableSchema, compression: CompressionConfig) -> Self {
+        Header {
+            schema,
+            compression,
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    /// Write header to writer, preceded by its length and checksum
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.write_version_to(writer, FormatVersion::CURRENT)
+    }
+
+    /// Write header to writer in the given format version
+    pub fn write_version_to<W: Write>(&self, writer: &mut W, version: FormatVersion) -> Result<()> {
+        version.check_writable()?;
+        if version != FormatVersion::V3 && !self.metadata.is_empty() {
+            return Err(BinaryError::SerializationError(format!(
+                "Header metadata cannot be stored in format version {}",
+                version.number()
+            )));
+        }
+
+        let mut content = Vec::new();
+        match version {
+            FormatVersion::V1 => unreachable!("version 1 is rejected above"),
+            FormatVersion::V2 => self.write_content_v2(&mut content)?,
+            FormatVersion::V3 => self.write_content_v3(&mut content)?,
+        }
+        write_u32(writer, content.len() as u32)?;
+        write_u32(writer, crc32fast::hash(&content))?;
+        writer.write_all(&content)?;
+        Ok(())
+    }
+
+    fn write_content_v2<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let (schema_json, compression_json) = self.to_json()?;
+        write_sized_byte_array_compressed(writer, schema_json.as_bytes(), &HEADER_COMPRESSION)?;
+        write_sized_byte_array_compressed(
+            writer,
+            compression_json.as_bytes(),
+            &HEADER_COMPRESSION,
+        )?;
+        writer.write_all(&[0u8; HEADER_RESERVED_SIZE_V2])?;
+        Ok(())
+    }
+
+    fn write_content_v3<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.schema.write_to(writer)?;
+        self.compression.write_to(writer)?;
+        write_u32(writer, self.metadata.len() as u32)?;
+        for (key, value) in &self.metadata {
+            write_string(writer, key)?;
+            write_string(writer, value)?;
+        }
+        writer.write_all(&[0u8; HEADER_RESERVED_SIZE_V3])?;
+        Ok(())
+    }
+
+    /// Serialize the schema and compression config to JSON
+    fn to_json(&self) -> Result<(String, String)> {
+        let schema_json = serde_json::to_string(&self.schema)
+            .map_err(|e| BinaryError::SerializationError(e.to_string()))?;
+        let compression_json = serde_json::to_string(&self.compression)
+            .map_err(|e| BinaryError::SerializationError(e.to_string()))?;
+        Ok((schema_json, compression_json))
+    }
+
+    /// Read header from reader, verifying its checksum before parsing it
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        Self::read_version_from(reader, FormatVersion::CURRENT)
+    }
+
+    /// Read a header written by the given format version
+    pub fn read_version_from<R: Read>(reader: &mut R, version: FormatVersion) -> Result<Self> {
+        // Version 1 headers have no length or checksum in front of them
+        if version == FormatVersion::V1 {
+            return Self::read_content_v1(reader);
+        }
+
+        let length = read_u32(reader)? as u64;
+        let expected = read_u32(reader)?;
+        let mut content = Vec::new();
+        reader.take(length).read_to_end(&mut content)?;
+        if content.len() as u64 != length {
+            return Err(BinaryError::CorruptedData(format!(
+                "Header of {} bytes truncated after {} bytes",
+                length,
+                content.len()
+            )));
+        }
+        verify_checksum("header", &content, expected)?;
+
+        let mut reader = content.as_slice();
+        let header = match version {
+            FormatVersion::V1 => unreachable!("version 1 is read above"),
+            FormatVersion::V2 => Self::read_content_v2(&mut reader)?,
+            FormatVersion::V3 => Self::read_content_v3(&mut reader)?,
+        };
+        if !reader.is_empty() {
+            return Err(BinaryError::CorruptedData(format!(
+                "Header of {} bytes has {} trailing bytes",
+                length,
+                reader.len()
+            )));
         }
+        Ok(header)
+    }
 
-        // Read schema
+    fn read_content_v1<R: Read>(reader: &mut R) -> Result<Self> {
         let schema_bytes = read_sized_byte_array(reader)?;
+        let compression_bytes = read_sized_byte_array(reader)?;
+        Self::from_json(schema_bytes, compression_bytes)
+    }
+
+    fn read_content_v2<R: Read>(reader: &mut R) -> Result<Self> {
+        let schema_bytes = read_sized_byte_array_compressed(reader, &HEADER_COMPRESSION)?;
+        let compression_bytes = read_sized_byte_array_compressed(reader, &HEADER_COMPRESSION)?;
+        let mut reserved = [0u8; HEADER_RESERVED_SIZE_V2];
+        reader.read_exact(&mut reserved)?;
+        check_reserved(&reserved)?;
+        Self::from_json(schema_bytes, compression_bytes)
+    }
+
+    fn read_content_v3<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut header = Header::new(
+            TableSchema::read_from(reader)?,
+            CompressionConfig::read_from(reader)?,
+        );
+        let entries = read_u32(reader)?;
+        for _ in 0..entries {
+            let key = read_string(reader)?;
+            let value = read_string(reader)?;
+            if header.metadata.insert(key.clone(), value).is_some() {
+                return Err(BinaryError::CorruptedData(format!(
+                    "Header metadata key '{}' appears twice",
+                    key
+                )));
+            }
+        }
+        let mut reserved = [0u8; HEADER_RESERVED_SIZE_V3];
+        reader.read_exact(&mut reserved)?;
+        check_reserved(&reserved)?;
+        Ok(header)
+    }
+
+    /// Parse the JSON-encoded schema and compression config
+    fn from_json(schema_bytes: Vec<u8>, compression_bytes: Vec<u8>) -> Result<Self> {
         let schema_json = String::from_utf8(schema_bytes)
             .map_err(|e| BinaryError::DeserializationError(e.to_string()))?;
         let schema: TableSchema = serde_json::from_str(&schema_json)
             .map_err(|e| BinaryError::DeserializationError(e.to_string()))?;
 
-        // Read compression config
-        let compression_bytes = read_sized_byte_array(reader)?;
         let compression_json = String::from_utf8(compression_bytes)
             .map_err(|e| BinaryError::DeserializationError(e.to_string()))?;
         let compression: CompressionConfig = serde_json::from_str(&compression_json)
             .map_err(|e| BinaryError::DeserializationError(e.to_string()))?;
 
-        let header = Header {
-            schema,
-            compression,
-        };
+        Ok(Header::new(schema, compression))
+    }
+}
 
-        // Read blocks
-        let block_count = read_u32(reader)?;
-        let mut blocks = Vec::with_capacity(block_count as usize);
-        for _ in 0..block_count {
-            blocks.push(Block::read_from(reader, &header.compression)?);
-        }
+/// Reject reserved header bytes that are not zero
+///
+/// Reserved bytes are zero in every file written so far, so a later version
+/// can give them a meaning without old readers misreading its files.
+fn check_reserved(reserved: &[u8]) -> Result<()> {
+    if reserved.iter().any(|&byte| byte != 0) {
+        return Err(BinaryError::CorruptedData(
+            "Reserved header bytes are not zero".to_string(),
+        ));
+    }
+    Ok(())
+}
 
-        Ok(BinaryFile { header, blocks })
+/// Streaming binary file writer
+///
+/// Writes the header up front and flushes every table it is given as its own
+/// block, so memory use is bounded by the block size rather than the file size.
+/// The final block count goes into a trailer written by `finish`.
+#[derive(Debug)]
+pub struct BinaryWriter<W: Write> {
+    writer: CountingWriter<W>,
+    header: Header,
+    version: FormatVersion,
+    index: Vec<BlockIndexEntry>,
+}
+
+impl<W: Write> BinaryWriter<W> {
+    /// Create a writer and write the file header
+    pub fn new(writer: W, schema: TableSchema, compression: CompressionConfig) -> Result<Self> {
+        Self::with_version(writer, schema, compression, FormatVersion::CURRENT)
     }
 
-    /// Get the table from the first block (for simple cases)
-    pub fn table(&self) -> Option<&Table> {
-        self.blocks.first().map(|block| &block.table)
+    /// Create a writer producing the given format version and write the file
+    /// header
+    pub fn with_version(
+        writer: W,
+        schema: TableSchema,
+        compression: CompressionConfig,
+        version: FormatVersion,
+    ) -> Result<Self> {
+        Self::with_header(writer, Header::new(schema, compression), version)
     }
-}
 
-impl Block {
-    /// Write block to writer
-    pub fn write_to<W: Write>(
-        &self,
-        writer: &mut W,
-        compression: &CompressionConfig,
-    ) -> Result<()> {
-        write_u32(writer, self.row_count)?;
-        self.table.write_to(writer, compression)?;
+    /// Create a writer producing the given format version and write a
+    /// prepared header, e.g. one carrying metadata
+    pub fn with_header(writer: W, header: Header, version: FormatVersion) -> Result<Self> {
+        version.check_writable()?;
+        let mut writer = CountingWriter::new(writer);
+        writer.write_all(version.magic_number())?;
+        header.write_version_to(&mut writer, version)?;
+        write_u32(&mut writer, STREAMING_BLOCK_COUNT)?;
+
+        Ok(BinaryWriter {
+            writer,
+            header,
+            version,
+            index: Vec::new(),
+        })
+    }
+
+    /// Header written at the start of the file
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Format version being written
+    pub fn version(&self) -> FormatVersion {
+        self.version
+    }
+
+    /// Number of blocks written so far
+    pub fn block_count(&self) -> u32 {
+        self.index.len() as u32
+    }
+
+    /// Write a striped table as the next block
+    pub fn write_table(&mut self, table: &Table) -> Result<()> {
+        let schema = table.schema();
+        if schema != self.header.schema {
+            return Err(BinaryError::SerializationError(format!(
+                "Table schema {:?} does not match the file schema {:?}",
+                schema, self.header.schema
+            )));
+        }
+        let row_count = table.row_count();
+        if row_count >= TRAILER_MARKER as usize {
+            return Err(BinaryError::SerializationError(format!(
+                "Block of {} rows exceeds the maximum block size",
+                row_count
+            )));
+        }
+        self.check_block_limit()?;
+
+        let offset = self.writer.position;
+        write_block(
+            &mut self.writer,
+            row_count as u32,
+            table,
+            &self.header.compression,
+        )?;
+        self.index.push(BlockIndexEntry {
+            offset,
+            length: self.writer.position - offset,
+            row_count: row_count as u32,
+        });
         Ok(())
     }
 
-    /// Read block from reader
-    pub fn read_from<R: Read>(reader: &mut R, compression: &CompressionConfig) -> Result<Self> {
-        let row_count = read_u32(reader)?;
-        let table = Table::read_from(reader, compression)?;
-        Ok(Block { row_count, table })
+    /// Write a block exactly as it was encoded in another file
+    fn write_encoded_block(&mut self, bytes: &[u8], row_count: u32) -> Result<()> {
+        self.check_block_limit()?;
+        let offset = self.writer.position;
+        self.writer.write_all(bytes)?;
+        self.index.push(BlockIndexEntry {
+            offset,
+            length: bytes.len() as u64,
+            row_count,
+        });
+        Ok(())
     }
-}
 
-impl Table {
-    /// Write table to writer
-    pub fn write_to<W: Write>(
-        &self,
-        writer: &mut W,
-        compression: &CompressionConfig,
-    ) -> Result<()> {
-        match self {
-            Table::Binary {
-                default,
-                encoding,
-                data,
-            } => {
-                write_u8(writer, 0)?; // Binary table tag
-                default.write_to(writer)?;
-                encoding.write_to(writer)?;
-                write_sized_byte_array_compressed(writer, data, &compression.binary_data)?;
-            }
-            Table::Array { default, column } => {
-                write_u8(writer, 1)?; // Array table tag
-                default.write_to(writer)?;
-                column.write_to(writer, compression)?;
-            }
-            Table::Map {
-                default,
-                key_column,
-                value_column,
-            } => {
-                write_u8(writer, 2)?; // Map table tag
-                default.write_to(writer)?;
-                key_column.write_to(writer, compression)?;
-                value_column.write_to(writer, compression)?;
-            }
+    fn check_block_limit(&self) -> Result<()> {
+        if self.block_count() == STREAMING_BLOCK_COUNT - 1 {
+            return Err(BinaryError::SerializationError(
+                "Too many blocks in a single file".to_string(),
+            ));
         }
         Ok(())
     }
 
-    /// Read table from reader
-    pub fn read_from<R: Read>(reader: &mut R, compression: &CompressionConfig) -> Result<Self> {
-        let tag = read_u8(reader)?;
-        match tag {
-            0 => {
-                let default = Default::read_from(reader)?;
-                let encoding = Encoding::read_from(reader)?;
-                let data = read_sized_byte_array_compressed(reader, &compression.binary_data)?;
-                Ok(Table::Binary {
-                    default,
-                    encoding,
-                    data,
-                })
-            }
-            1 => {
-                let default = Default::read_from(reader)?;
-                let column = Box::new(Column::read_from(reader, compression)?);
-                Ok(Table::Array { default, column })
-            }
-            2 => {
-                let default = Default::read_from(reader)?;
-                let key_column = Box::new(Column::read_from(reader, compression)?);
-                let value_column = Box::new(Column::read_from(reader, compression)?);
-                Ok(Table::Map {
-                    default,
-                    key_column,
-                    value_column,
-                })
-            }
-            _ => Err(BinaryError::InvalidTableTag(tag)),
-        }
+    /// Stripe a batch of logical rows against the file schema and write it as
+    /// the next block
+    pub fn write_rows(&mut self, rows: &LogicalTable) -> std::result::Result<(), ConversionError> {
+        let table = Table::from_logical(&self.header.schema, rows)?;
+        self.write_table(&table)?;
+        Ok(())
     }
-}
 
-impl Column {
-    /// Write column to writer
-    pub fn write_to<W: Write>(
-        &self,
-        writer: &mut W,
-        compression: &CompressionConfig,
-    ) -> Result<()> {
-        match self {
-            Column::Unit { count } => {
-                write_u8(writer, 0)?; // Unit column tag
-                write_u32(writer, *count as u32)?;
-            }
-            Column::Int {
-                default,
-                encoding,
-                values,
-            } => {
-                write_u8(writer, 1)?; // Int column tag
-                default.write_to(writer)?;
-                encoding.write_to(writer)?;
-                write_int_array_compressed(writer, values)?;
-            }
-            Column::Double { default, values } => {
-                write_u8(writer, 2)?; // Double column tag
-                default.write_to(writer)?;
-                // Convert f64 to i64 bits for compression
-                let int_values: Vec<i64> = values.iter().map(|f| f.to_bits() as i64).collect();
-                write_int_array_compressed(writer, &int_values)?;
-            }
-            Column::Binary {
-                default,
-                encoding,
-                lengths,
-                data,
-            } => {
-                write_u8(writer, 3)?; // Binary column tag
-                default.write_to(writer)?;
-                encoding.write_to(writer)?;
-                write_int_array_usize_compressed(writer, lengths)?;
-                write_sized_byte_array_compressed(writer, data, &compression.strings)?;
-            }
-            Column::Array {
-                default,
-                lengths,
-                element,
-            } => {
-                write_u8(writer, 4)?; // Array column tag
-                default.write_to(writer)?;
-                write_int_array_usize_compressed(writer, lengths)?;
-                element.write_to(writer, compression)?;
-            }
-            Column::Struct { default, fields } => {
-                write_u8(writer, 5)?; // Struct column tag
-                default.write_to(writer)?;
-                write_u32(writer, fields.len() as u32)?;
-                for field in fields {
-                    field.write_to(writer, compression)?;
-                }
-            }
-            Column::Enum {
-                default,
-                tags,
-                variants,
-            } => {
-                write_u8(writer, 6)?; // Enum column tag
-                default.write_to(writer)?;
-                write_u32_array_compressed(writer, tags)?;
-                write_u32(writer, variants.len() as u32)?;
-                for variant in variants {
-                    variant.write_to(writer, compression)?;
-                }
-            }
-            Column::Nested { lengths, table } => {
-                write_u8(writer, 7)?; // Nested column tag
-                write_int_array_usize_compressed(writer, lengths)?;
-                table.write_to(writer, compression)?;
-            }
-            Column::Reversed { inner } => {
-                write_u8(writer, 8)?; // Reversed column tag
-                inner.write_to(writer, compression)?;
-            }
-        }
-        Ok(())
+    /// Write the trailer and block index and return the underlying writer
+    pub fn finish(mut self) -> Result<W> {
+        let block_count = self.block_count();
+        write_u32(&mut self.writer, TRAILER_MARKER)?;
+        write_u32(&mut self.writer, block_count)?;
+        write_block_index(&mut self.writer, &self.index)?;
+        self.writer.flush()?;
+        Ok(self.writer.inner)
     }
+}
 
-    /// Read column from reader
-    pub fn read_from<R: Read>(reader: &mut R, compression: &CompressionConfig) -> Result<Self> {
-        let tag = read_u8(reader)?;
-        match tag {
-            0 => {
-                let count = read_u32(reader)? as usize;
-                Ok(Column::Unit { count })
-            }
-            1 => {
-                let default = Default::read_from(reader)?;
-                let encoding = Encoding::read_from(reader)?;
-                let values = read_int_array_compressed(reader)?;
-                Ok(Column::Int {
-                    default,
-                    encoding,
-                    values,
-                })
-            }
-            2 => {
-                let default = Default::read_from(reader)?;
-                let int_values = read_int_array_compressed(reader)?;
-                let values: Vec<f64> = int_values
-                    .iter()
-                    .map(|i| f64::from_bits(*i as u64))
-                    .collect();
-                Ok(Column::Double { default, values })
-            }
-            3 => {
-                let default = Default::read_from(reader)?;
-                let encoding = Encoding::read_from(reader)?;
-                let lengths = read_int_array_usize_compressed(reader)?;
-                let data = read_sized_byte_array_compressed(reader, &compression.strings)?;
-                Ok(Column::Binary {
-                    default,
-                    encoding,
-                    lengths,
-                    data,
-                })
-            }
-            4 => {
-                let default = Default::read_from(reader)?;
-                let lengths = read_int_array_usize_compressed(reader)?;
-                let element = Box::new(Column::read_from(reader, compression)?);
-                Ok(Column::Array {
-                    default,
-                    lengths,
-                    element,
-                })
-            }
-            5 => {
-                let default = Default::read_from(reader)?;
-                let field_count = read_u32(reader)? as usize;
-                let mut fields = Vec::with_capacity(field_count);
-                for _ in 0..field_count {
-                    fields.push(FieldColumn::read_from(reader, compression)?);
-                }
-                Ok(Column::Struct { default, fields })
-            }
-            6 => {
-                let default = Default::read_from(reader)?;
-                let tags = read_u32_array_compressed(reader)?;
-                let variant_count = read_u32(reader)? as usize;
-                let mut variants = Vec::with_capacity(variant_count);
-                for _ in 0..variant_count {
-                    variants.push(VariantColumn::read_from(reader, compression)?);
-                }
-                Ok(Column::Enum {
-                    default,
-                    tags,
-                    variants,
-                })
-            }
-            7 => {
-                let lengths = read_int_array_usize_compressed(reader)?;
-                let table = Box::new(Table::read_from(reader, compression)?);
-                Ok(Column::Nested { lengths, table })
-            }
-            8 => {
-                let inner = Box::new(Column::read_from(reader, compression)?);
-                Ok(Column::Reversed { inner })
-            }
-            _ => Err(BinaryError::InvalidColumnTag(tag)),
-        }
+/// Writer adapter tracking the number of bytes written, used to record block
+/// offsets for the block index
+#[derive(Debug)]
+struct CountingWriter<W: Write> {
+    inner: W,
+    position: u64,
+}
+
+impl<W: Write> CountingWriter<W> {
+    fn new(inner: W) -> Self {
+        CountingWriter { inner, position: 0 }
     }
 }
 
-impl FieldColumn {
-    /// Write field column to writer
-    pub fn write_to<W: Write>(
-        &self,
-        writer: &mut W,
-        compression: &CompressionConfig,
-    ) -> Result<()> {
-        write_string(writer, &self.name)?;
-        self.column.write_to(writer, compression)?;
-        Ok(())
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.position += written as u64;
+        Ok(written)
     }
 
-    /// Read field column from reader
-    pub fn read_from<R: Read>(reader: &mut R, compression: &CompressionConfig) -> Result<Self> {
-        let name = read_string(reader)?;
-        let column = Column::read_from(reader, compression)?;
-        Ok(FieldColumn { name, column })
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
     }
 }
 
-impl VariantColumn {
-    /// Write variant column to writer
-    pub fn write_to<W: Write>(
-        &self,
-        writer: &mut W,
-        compression: &CompressionConfig,
-    ) -> Result<()> {
-        write_string(writer, &self.name)?;
-        write_u32(writer, self.tag)?;
-        self.column.write_to(writer, compression)?;
-        Ok(())
+/// Writer adapter computing the CRC32 of everything written through it
+struct ChecksumWriter<W: Write> {
+    inner: W,
+    hasher: crc32fast::Hasher,
+}
+
+impl<W: Write> ChecksumWriter<W> {
+    fn new(inner: W) -> Self {
+        ChecksumWriter {
+            inner,
+            hasher: crc32fast::Hasher::new(),
+        }
     }
 
-    /// Read variant column from reader
-    pub fn read_from<R: Read>(reader: &mut R, compression: &CompressionConfig) -> Result<Self> {
-        let name = read_string(reader)?;
-        let tag = read_u32(reader)?;
-        let column = Column::read_from(reader, compression)?;
-        Ok(VariantColumn { name, tag, column })
+    fn checksum(self) -> u32 {
+        self.hasher.finalize()
     }
 }
 
-impl Default {
-    /// Write default to writer
-    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
-        match self {
-            Default::Allow => write_u8(writer, 0),
-            Default::Deny => write_u8(writer, 1),
-        }
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
     }
 
-    /// Read default from reader
-    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-        match read_u8(reader)? {
-            0 => Ok(Default::Allow),
-            1 => Ok(Default::Deny),
-            tag => Err(BinaryError::InvalidDefaultTag(tag)),
-        }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
     }
 }
 
-impl Encoding {
-    /// Write encoding to writer
-    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
-        match self {
-            Encoding::Int(int_enc) => {
-                write_u8(writer, 0)?;
-                int_enc.write_to(writer)?;
-            }
-            Encoding::Binary(bin_enc) => {
-                write_u8(writer, 1)?;
-                bin_enc.write_to(writer)?;
-            }
-        }
-        Ok(())
+/// Reader adapter keeping a copy of everything read through it, used to
+/// collect the bytes of a block so its checksum is verified before decoding
+struct RecordingReader<R: Read> {
+    inner: R,
+    recorded: Vec<u8>,
+}
+
+impl<R: Read> RecordingReader<R> {
+    fn new(inner: R, recorded: Vec<u8>) -> Self {
+        RecordingReader { inner, recorded }
     }
+}
 
-    /// Read encoding from reader
-    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-        match read_u8(reader)? {
-            0 => Ok(Encoding::Int(IntEncoding::read_from(reader)?)),
-            1 => Ok(Encoding::Binary(BinaryEncoding::read_from(reader)?)),
-            tag => Err(BinaryError::InvalidEncodingTag(tag)),
-        }
+impl<R: Read> Read for RecordingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.recorded.extend_from_slice(&buf[..read]);
+        Ok(read)
     }
 }
 
-impl IntEncoding {
-    /// Write int encoding to writer
-    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
-        match self {
-            IntEncoding::Int => write_u8(writer, 0),
-            IntEncoding::Date => write_u8(writer, 1),
-            IntEncoding::TimeSeconds => write_u8(writer, 2),
-            IntEncoding::TimeMilliseconds => write_u8(writer, 3),
-            IntEncoding::TimeMicroseconds => write_u8(writer, 4),
-        }
+fn checksum_result(section: &str, expected: u32, actual: u32) -> Result<()> {
+    if expected != actual {
+        return Err(BinaryError::ChecksumMismatch {
+            section: section.to_string(),
+            expected,
+            actual,
+        });
     }
+    Ok(())
+}
 
-    /// Read int encoding from reader
-    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-        match read_u8(reader)? {
-            0 => Ok(IntEncoding::Int),
-            1 => Ok(IntEncoding::Date),
-            2 => Ok(IntEncoding::TimeSeconds),
-            3 => Ok(IntEncoding::TimeMilliseconds),
-            4 => Ok(IntEncoding::TimeMicroseconds),
-            tag => Err(BinaryError::InvalidIntEncodingTag(tag)),
+fn verify_checksum(section: &str, bytes: &[u8], expected: u32) -> Result<()> {
+    checksum_result(section, expected, crc32fast::hash(bytes))
+}
+
+/// Check the trailing checksum of an encoded block and return the bytes it
+/// covers
+pub(crate) fn split_block_checksum(block: usize, bytes: &[u8]) -> Result<&[u8]> {
+    if bytes.len() < CHECKSUM_SIZE {
+        return Err(BinaryError::CorruptedData(format!(
+            "Block {} of {} bytes is too short for its checksum",
+            block,
+            bytes.len()
+        )));
+    }
+    let (body, checksum) = bytes.split_at(bytes.len() - CHECKSUM_SIZE);
+    let expected = read_u32(&mut &checksum[..])?;
+    verify_checksum(&format!("block {}", block), body, expected)?;
+    Ok(body)
+}
+
+/// Read the magic number at the start of a file and return its format version
+pub(crate) fn read_magic<R: Read>(reader: &mut R) -> Result<FormatVersion> {
+    let mut magic = [0u8; 16];
+    reader.read_exact(&mut magic)?;
+    FormatVersion::from_number(detect_version(&magic)?)
+}
+
+/// Write the block index footer
+fn write_block_index<W: Write>(
+    writer: &mut CountingWriter<W>,
+    index: &[BlockIndexEntry],
+) -> Result<()> {
+    let index_offset = writer.position;
+    write_u32(writer, index.len() as u32)?;
+    for entry in index {
+        write_u64(writer, entry.offset)?;
+        write_u64(writer, entry.length)?;
+        write_u32(writer, entry.row_count)?;
+    }
+    write_u64(writer, index_offset)?;
+    writer.write_all(INDEX_MAGIC)?;
+    Ok(())
+}
+
+/// Random-access binary file reader backed by the block index footer
+///
+/// Any block can be decoded without touching the blocks before it, which is
+/// the basis for selective reads over large files.
+#[derive(Debug)]
+pub struct IndexedReader<R: Read + Seek> {
+    reader: R,
+    header: Header,
+    version: FormatVersion,
+    index: Vec<BlockIndexEntry>,
+    /// First row of each block, in file row order
+    first_rows: Vec<u64>,
+}
+
+impl<R: Read + Seek> IndexedReader<R> {
+    /// Create a reader, parsing the header and the block index
+    pub fn new(mut reader: R) -> Result<Self> {
+        reader.seek(SeekFrom::Start(0))?;
+        let BinaryReader {
+            header, version, ..
+        } = BinaryReader::new(&mut reader)?;
+        if version == FormatVersion::V1 {
+            return Err(BinaryError::MissingBlockIndex);
+        }
+
+        let file_len = reader.seek(SeekFrom::End(0))?;
+        if file_len < INDEX_TAIL_SIZE {
+            return Err(BinaryError::MissingBlockIndex);
+        }
+        reader.seek(SeekFrom::End(-(INDEX_TAIL_SIZE as i64)))?;
+        let index_offset = read_u64(&mut reader)?;
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != INDEX_MAGIC {
+            return Err(BinaryError::MissingBlockIndex);
+        }
+
+        let index_end = file_len - INDEX_TAIL_SIZE;
+        if index_offset > index_end {
+            return Err(BinaryError::CorruptedData(format!(
+                "Block index offset {} is past the end of the file",
+                index_offset
+            )));
+        }
+        reader.seek(SeekFrom::Start(index_offset))?;
+        let entry_count = read_u32(&mut reader)? as u64;
+        if index_offset + 4 + entry_count * INDEX_ENTRY_SIZE != index_end {
+            return Err(BinaryError::CorruptedData(format!(
+                "Block index of {} entries does not fit its footer",
+                entry_count
+            )));
+        }
+
+        let mut index = Vec::with_capacity(entry_count as usize);
+        let mut first_rows = Vec::with_capacity(entry_count as usize);
+        let mut next_row = 0u64;
+        for _ in 0..entry_count {
+            let entry = BlockIndexEntry {
+                offset: read_u64(&mut reader)?,
+                length: read_u64(&mut reader)?,
+                row_count: read_u32(&mut reader)?,
+            };
+            if entry
+                .offset
+                .checked_add(entry.length)
+                .map_or(true, |end| end > index_offset)
+            {
+                return Err(BinaryError::CorruptedData(format!(
+                    "Block at offset {} overlaps the block index",
+                    entry.offset
+                )));
+            }
+            first_rows.push(next_row);
+            next_row += entry.row_count as u64;
+            index.push(entry);
+        }
+
+        Ok(IndexedReader {
+            reader,
+            header,
+            version,
+            index,
+            first_rows,
+        })
+    }
+
+    /// Header parsed from the start of the file
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Format version detected from the magic number
+    pub fn version(&self) -> FormatVersion {
+        self.version
+    }
+
+    /// Block index entries, in file order
+    pub fn index(&self) -> &[BlockIndexEntry] {
+        &self.index
+    }
+
+    /// Number of blocks in the file
+    pub fn block_count(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Total number of rows across all blocks
+    pub fn row_count(&self) -> u64 {
+        self.index.iter().map(|entry| entry.row_count as u64).sum()
+    }
+
+    /// Seek to and decode a single block
+    pub fn read_block(&mut self, block: usize) -> Result<Block> {
+        self.read_block_projected(block, &Projection::All)
+    }
+
+    /// Seek to a single block and decode only the projected columns
+    pub fn read_block_projected(&mut self, block: usize, projection: &Projection) -> Result<Block> {
+        let bytes = self.read_block_bytes(block)?;
+        // Checksum and row_count are checked, which leaves the block body
+        let mut body = &bytes[4..bytes.len() - CHECKSUM_SIZE];
+        read_block_body(
+            &mut body,
+            self.index[block].row_count,
+            &self.header.compression,
+            projection,
+        )
+    }
+
+    /// Seek to a single block and read its column statistics without
+    /// decoding the table
+    pub fn read_block_stats(&mut self, block: usize) -> Result<BlockStats> {
+        let entry = self.entry(block)?;
+        self.reader.seek(SeekFrom::Start(entry.offset))?;
+        let (row_count, stats) = Block::read_stats(&mut self.reader)?;
+        if row_count != entry.row_count {
+            return Err(BinaryError::CorruptedData(format!(
+                "Block {} has {} rows but the index records {}",
+                block, row_count, entry.row_count
+            )));
+        }
+        Ok(stats)
+    }
+
+    /// Check the checksum and row count of a single block without decoding it
+    pub fn verify_block(&mut self, block: usize) -> Result<()> {
+        self.read_block_bytes(block).map(|_| ())
+    }
+
+    /// Read the encoded bytes of a single block, including its trailing
+    /// checksum, after checking the checksum and row count
+    pub fn read_block_bytes(&mut self, block: usize) -> Result<Vec<u8>> {
+        let entry = self.entry(block)?;
+        self.reader.seek(SeekFrom::Start(entry.offset))?;
+        let mut bytes = Vec::new();
+        (&mut self.reader)
+            .take(entry.length)
+            .read_to_end(&mut bytes)?;
+        if bytes.len() as u64 != entry.length {
+            return Err(BinaryError::CorruptedData(format!(
+                "Block {} of {} bytes truncated after {} bytes",
+                block,
+                entry.length,
+                bytes.len()
+            )));
+        }
+
+        let body = split_block_checksum(block, &bytes)?;
+        let row_count = read_u32(&mut &body[..])?;
+        if row_count != entry.row_count {
+            return Err(BinaryError::CorruptedData(format!(
+                "Block {} has {} rows but the index records {}",
+                block, row_count, entry.row_count
+            )));
+        }
+        Ok(bytes)
+    }
+
+    /// Check the checksum of every block, returning the first mismatch
+    ///
+    /// The header checksum is already verified when the reader is created.
+    pub fn verify(&mut self) -> Result<()> {
+        for block in 0..self.index.len() {
+            self.verify_block(block)?;
+        }
+        Ok(())
+    }
+
+    /// Rewrite the file in another format version, adding metadata entries
+    /// to its header
+    ///
+    /// Only the header is re-encoded. Blocks are copied as they are after
+    /// checking their checksums, so upgrading is cheap even for large files.
+    pub fn copy_to_version<W: Write>(
+        &mut self,
+        writer: W,
+        version: FormatVersion,
+        metadata: BTreeMap<String, String>,
+    ) -> Result<W> {
+        let mut header = self.header.clone();
+        header.metadata.extend(metadata);
+        let mut output = BinaryWriter::with_header(writer, header, version)?;
+        for block in 0..self.index.len() {
+            let bytes = self.read_block_bytes(block)?;
+            output.write_encoded_block(&bytes, self.index[block].row_count)?;
+        }
+        output.finish()
+    }
+
+    /// Indices of the blocks whose statistics do not rule out the predicate
+    pub fn blocks_matching(&mut self, predicate: &Predicate) -> Result<Vec<usize>> {
+        let mut blocks = Vec::new();
+        for block in 0..self.index.len() {
+            if predicate.may_match(&self.read_block_stats(block)?) {
+                blocks.push(block);
+            }
+        }
+        Ok(blocks)
+    }
+
+    fn entry(&self, block: usize) -> Result<BlockIndexEntry> {
+        self.index.get(block).copied().ok_or_else(|| {
+            BinaryError::CorruptedData(format!(
+                "Block {} is out of range for a file with {} blocks",
+                block,
+                self.index.len()
+            ))
+        })
+    }
+
+    /// Indices of the blocks holding any of the given rows
+    pub fn blocks_for_rows(&self, rows: Range<u64>) -> Range<usize> {
+        if rows.start >= rows.end {
+            return 0..0;
+        }
+        let total_rows = match (self.first_rows.last(), self.index.last()) {
+            (Some(first), Some(entry)) => first + entry.row_count as u64,
+            _ => return 0..0,
+        };
+        if rows.start >= total_rows {
+            return 0..0;
         }
+        // Blocks are in row order, so both ends are found by binary search
+        let start = self
+            .first_rows
+            .partition_point(|&first| first <= rows.start)
+            - 1;
+        let end = self
+            .first_rows
+            .partition_point(|&first| first < rows.end.min(total_rows));
+        start..end
     }
 }
 
-impl BinaryEncoding {
-    /// Write binary encoding to writer
-    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
-        match self {
-            BinaryEncoding::Binary => write_u8(writer, 0),
-            BinaryEncoding::Utf8 => write_u8(writer, 1),
+/// Lazy block-by-block binary file reader
+///
+/// Parses the header once and then decodes one block per call to `next`, so
+/// files larger than memory can be scanned and reading can stop at any point.
+/// Both block-counted files and streamed files written by `BinaryWriter` are
+/// supported. Iteration ends after the first error.
+#[derive(Debug)]
+pub struct BinaryReader<R: Read> {
+    reader: R,
+    header: Header,
+    /// Format version read from the magic number
+    version: FormatVersion,
+    /// Blocks left to read, or `None` for a streamed file ending in a trailer
+    remaining: Option<u32>,
+    blocks_read: u32,
+    blocks_skipped: u32,
+    done: bool,
+    projection: Projection,
+    predicate: Option<Predicate>,
+}
+
+impl<R: Read> BinaryReader<R> {
+    /// Create a reader and parse the file header
+    pub fn new(mut reader: R) -> Result<Self> {
+        // Check magic number and dispatch on the version it carries
+        let version = read_magic(&mut reader)?;
+        let header = Header::read_version_from(&mut reader, version)?;
+
+        // Version 1 files were never streamed, so their block count is exact
+        let block_count = read_u32(&mut reader)?;
+        let remaining = if block_count == STREAMING_BLOCK_COUNT && version != FormatVersion::V1 {
+            None
+        } else {
+            Some(block_count)
+        };
+
+        Ok(BinaryReader {
+            reader,
+            header,
+            version,
+            remaining,
+            blocks_read: 0,
+            blocks_skipped: 0,
+            done: false,
+            projection: Projection::All,
+            predicate: None,
+        })
+    }
+
+    /// Only yield rows matching a predicate
+    ///
+    /// Blocks whose statistics rule out every row are skipped without being
+    /// decoded, and blocks left without matching rows are not yielded. The
+    /// predicate's columns must be part of the projection.
+    pub fn with_predicate(mut self, predicate: Predicate) -> Self {
+        self.predicate = Some(predicate);
+        self
+    }
+
+    /// Decode only the projected columns of each block
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Header parsed from the start of the file
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Format version detected from the magic number
+    pub fn version(&self) -> FormatVersion {
+        self.version
+    }
+
+    /// Number of blocks decoded so far, not counting skipped blocks
+    pub fn blocks_read(&self) -> u32 {
+        self.blocks_read
+    }
+
+    /// Number of blocks skipped so far because their statistics ruled out
+    /// the predicate
+    pub fn blocks_skipped(&self) -> u32 {
+        self.blocks_skipped
+    }
+
+    /// Return the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn read_next(&mut self) -> Result<Option<Block>> {
+        if self.version == FormatVersion::V1 {
+            return self.read_next_v1();
+        }
+        loop {
+            let row_count = match self.remaining {
+                Some(0) => return Ok(None),
+                Some(_) => read_u32(&mut self.reader)?,
+                None => {
+                    let row_count = read_u32(&mut self.reader)?;
+                    if row_count == TRAILER_MARKER {
+                        let block_count = read_u32(&mut self.reader)?;
+                        let blocks_seen = self.blocks_read + self.blocks_skipped;
+                        if block_count != blocks_seen {
+                            return Err(BinaryError::CorruptedData(format!(
+                                "Trailer records {} blocks but {} were read",
+                                block_count, blocks_seen
+                            )));
+                        }
+                        return Ok(None);
+                    }
+                    row_count
+                }
+            };
+
+            let section = format!("block {}", self.blocks_read + self.blocks_skipped);
+            let bytes = read_checked_block(&mut self.reader, row_count, &section)?;
+            let mut reader = &bytes[4..];
+            let block = match &self.predicate {
+                None => Some(read_block_body(
+                    &mut reader,
+                    row_count,
+                    &self.header.compression,
+                    &self.projection,
+                )?),
+                Some(predicate) => read_filtered_block_body(
+                    &mut reader,
+                    row_count,
+                    &self.header.compression,
+                    &self.projection,
+                    predicate,
+                )?,
+            };
+            if let Some(remaining) = self.remaining.as_mut() {
+                *remaining -= 1;
+            }
+
+            match block {
+                Some(block) => {
+                    self.blocks_read += 1;
+                    if self.predicate.is_none() || block.row_count > 0 {
+                        return Ok(Some(block));
+                    }
+                }
+                None => self.blocks_skipped += 1,
+            }
         }
     }
 
-    /// Read binary encoding from reader
-    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-        match read_u8(reader)? {
-            0 => Ok(BinaryEncoding::Binary),
-            1 => Ok(BinaryEncoding::Utf8),
-            tag => Err(BinaryError::InvalidBinaryEncodingTag(tag)),
+    /// Decode the next block of a version 1 file
+    ///
+    /// Version 1 blocks have no statistics to skip them by and no column
+    /// lengths to skip columns by, so every block is decoded whole and
+    /// predicates only filter its rows.
+    fn read_next_v1(&mut self) -> Result<Option<Block>> {
+        if self.projection != Projection::All {
+            return Err(BinaryError::InvalidProjection(
+                "Version 1 files cannot skip columns".to_string(),
+            ));
+        }
+        loop {
+            match self.remaining.as_mut() {
+                Some(0) | None => return Ok(None),
+                Some(remaining) => *remaining -= 1,
+            }
+            let block = read_block_v1(&mut self.reader, &self.header.compression)?;
+            self.blocks_read += 1;
+
+            let Some(predicate) = &self.predicate else {
+                return Ok(Some(block));
+            };
+            let table = block.table.select(&predicate.matches(&block.table)?);
+            if table.row_count() > 0 {
+                return Ok(Some(Block {
+                    row_count: table.row_count() as u32,
+                    table,
+                }));
+            }
+        }
+    }
+
+    /// Rewrite the rest of the file in another format version, adding
+    /// metadata entries to its header
+    ///
+    /// Every block is decoded and encoded again, which also works for files
+    /// without a block index such as version 1 files. Predicates and
+    /// projections apply as for iteration, and the copy's header records the
+    /// projected schema.
+    pub fn copy_to_version<W: Write>(
+        &mut self,
+        writer: W,
+        version: FormatVersion,
+        metadata: BTreeMap<String, String>,
+    ) -> Result<W> {
+        let mut header = self.header.clone();
+        header.schema = self.projection.table_schema(&header.schema)?;
+        header.metadata.extend(metadata);
+        let mut output = BinaryWriter::with_header(writer, header, version)?;
+        for block in self.by_ref() {
+            output.write_table(&block?.table)?;
         }
+        output.finish()
     }
 }
 
-// Basic I/O primitives
+impl<R: Read> Iterator for BinaryReader<R> {
+    type Item = Result<Block>;
 
-fn write_u8<W: Write>(writer: &mut W, value: u8) -> Result<()> {
-    writer.write_all(&[value])?;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.read_next() {
+            Ok(Some(block)) => Some(Ok(block)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl Block {
+    /// Write block to writer
+    pub fn write_to<W: Write>(
+        &self,
+        writer: &mut W,
+        compression: &CompressionConfig,
+    ) -> Result<()> {
+        write_block(writer, self.row_count, &self.table, compression)
+    }
+
+    /// Read block from reader
+    ///
+    /// Expects the block layout of format version 2 and up. Version 1
+    /// blocks carry no statistics or checksum and are read through
+    /// `BinaryReader`.
+    pub fn read_from<R: Read>(reader: &mut R, compression: &CompressionConfig) -> Result<Self> {
+        Self::read_projected(reader, compression, &Projection::All)
+    }
+
+    /// Read block from reader and verify its checksum, then decode only the
+    /// projected columns
+    pub fn read_projected<R: Read>(
+        reader: &mut R,
+        compression: &CompressionConfig,
+        projection: &Projection,
+    ) -> Result<Self> {
+        let row_count = read_u32(reader)?;
+        let bytes = read_checked_block(reader, row_count, "block")?;
+        read_block_body(&mut &bytes[4..], row_count, compression, projection)
+    }
+
+    /// Read only the row count and column statistics of a block, leaving the
+    /// reader positioned at the start of its table
+    ///
+    /// Only blocks of format version 2 and up have statistics.
+    pub fn read_stats<R: Read>(reader: &mut R) -> Result<(u32, BlockStats)> {
+        let row_count = read_u32(reader)?;
+        let stats = read_stats_section(reader)?;
+        Ok((row_count, stats))
+    }
+}
+
+fn read_stats_section<R: Read>(reader: &mut R) -> Result<BlockStats> {
+    let length = read_u32(reader)? as u64;
+    let mut section = reader.take(length);
+    let stats = BlockStats::read_from(&mut section)?;
+    if section.limit() != 0 {
+        return Err(BinaryError::CorruptedData(format!(
+            "Block statistics of {} bytes have {} trailing bytes",
+            length,
+            section.limit()
+        )));
+    }
+    Ok(stats)
+}
+
+/// Write a block: row_count, length-prefixed statistics, the table and the
+/// checksum of all three
+fn write_block<W: Write>(
+    writer: &mut W,
+    row_count: u32,
+    table: &Table,
+    compression: &CompressionConfig,
+) -> Result<()> {
+    let mut stats = Vec::new();
+    BlockStats::compute(table).write_to(&mut stats)?;
+
+    let mut checked = ChecksumWriter::new(&mut *writer);
+    write_u32(&mut checked, row_count)?;
+    write_u32(&mut checked, stats.len() as u32)?;
+    checked.write_all(&stats)?;
+    table.write_to(&mut checked, compression)?;
+    let checksum = checked.checksum();
+    write_u32(writer, checksum)?;
     Ok(())
 }
 
-fn read_u8<R: Read>(reader: &mut R) -> Result<u8> {
-    let mut buf = [0u8; 1];
-    reader.read_exact(&mut buf)?;
-    Ok(buf[0])
+/// Read the rest of a block after its row_count, skipping the statistics
+fn read_block_body<R: Read>(
+    reader: &mut R,
+    row_count: u32,
+    compression: &CompressionConfig,
+    projection: &Projection,
+) -> Result<Block> {
+    skip_sized(reader)?;
+    let table = read_block_table(reader, row_count, compression, projection)?;
+    check_block_rows(row_count, &table)?;
+    Ok(Block { row_count, table })
 }
 
-fn write_u32<W: Write>(writer: &mut W, value: u32) -> Result<()> {
-    writer.write_all(&value.to_le_bytes())?;
+/// Read the rest of a block after its row_count, keeping only the rows
+/// matching a predicate, or `None` if the statistics rule the block out
+fn read_filtered_block_body<R: Read>(
+    reader: &mut R,
+    row_count: u32,
+    compression: &CompressionConfig,
+    projection: &Projection,
+    predicate: &Predicate,
+) -> Result<Option<Block>> {
+    let stats = read_stats_section(reader)?;
+    if !predicate.may_match(&stats) {
+        return Ok(None);
+    }
+
+    let table = read_block_table(reader, row_count, compression, projection)?;
+    check_block_rows(row_count, &table)?;
+    let table = table.select(&predicate.matches(&table)?);
+    Ok(Some(Block {
+        row_count: table.row_count() as u32,
+        table,
+    }))
+}
+
+/// Check the row_count recorded for a block against its decoded table
+fn check_block_rows(row_count: u32, table: &Table) -> Result<()> {
+    if table.row_count() != row_count as usize {
+        return Err(BinaryError::CorruptedData(format!(
+            "Block records {} rows but its table has {}",
+            row_count,
+            table.row_count()
+        )));
+    }
+    Ok(())
+}
+
+/// Decode the table of a block and check its row-count invariants, so that
+/// corrupted lengths surface as errors rather than panics further on
+pub(crate) fn read_block_table<R: Read>(
+    reader: &mut R,
+    row_count: u32,
+    compression: &CompressionConfig,
+    projection: &Projection,
+) -> Result<Table> {
+    let table = Table::read_rows(reader, compression, projection, Some(row_count as usize))?;
+    table
+        .validate()
+        .map_err(|e| BinaryError::CorruptedData(e.to_string()))?;
+    Ok(table)
+}
+
+/// Read the rest of a block after its row_count up to its checksum and
+/// verify the checksum, without decoding the statistics or the table
+///
+/// Returns the bytes the checksum covers, starting with the row_count.
+fn read_checked_block<R: Read>(reader: &mut R, row_count: u32, section: &str) -> Result<Vec<u8>> {
+    let mut recording = RecordingReader::new(&mut *reader, row_count.to_le_bytes().to_vec());
+    skip_sized(&mut recording)?;
+    skip_table(&mut recording)?;
+    let bytes = recording.recorded;
+    let expected = read_u32(reader)?;
+    verify_checksum(section, &bytes, expected)?;
+    Ok(bytes)
+}
+
+/// Skip over an encoded table, reading only the tags and sizes needed to
+/// find its end
+fn skip_table<R: Read>(reader: &mut R) -> Result<()> {
+    let tag = read_u8(reader)?;
+    match tag {
+        0 => {
+            Default::read_from(reader)?;
+            Encoding::read_from(reader)?;
+            // Uncompressed size, then the compressed data behind its size
+            read_u32(reader)?;
+            skip_sized(reader)?;
+        }
+        1 => {
+            Default::read_from(reader)?;
+            skip_sized(reader)?;
+        }
+        2 => {
+            Default::read_from(reader)?;
+            skip_sized(reader)?;
+            skip_sized(reader)?;
+        }
+        _ => return Err(BinaryError::InvalidTableTag(tag)),
+    }
     Ok(())
 }
 
-fn read_u32<R: Read>(reader: &mut R) -> Result<u32> {
-    let mut buf = [0u8; 4];
-    reader.read_exact(&mut buf)?;
-    Ok(u32::from_le_bytes(buf))
-}
+/// Read a version 1 block: its row_count followed by the table, with no
+/// statistics or checksum
+///
+/// The version 1 decoders below are frozen: later format changes go into the
+/// decoders for version 2 and up.
+fn read_block_v1<R: Read>(reader: &mut R, compression: &CompressionConfig) -> Result<Block> {
+    let row_count = read_u32(reader)?;
+    let table = read_table_v1(reader, compression)?;
+    table
+        .validate()
+        .map_err(|e| BinaryError::CorruptedData(e.to_string()))?;
+    if table.row_count() != row_count as usize {
+        return Err(BinaryError::CorruptedData(format!(
+            "Block records {} rows but its table has {}",
+            row_count,
+            table.row_count()
+        )));
+    }
+    Ok(Block { row_count, table })
+}
+
+/// Read a table written by version 1
+fn read_table_v1<R: Read>(reader: &mut R, compression: &CompressionConfig) -> Result<Table> {
+    let tag = read_u8(reader)?;
+    match tag {
+        0 => {
+            let default = Default::read_from(reader)?;
+            let encoding = Encoding::read_from(reader)?;
+            let data = read_sized_byte_array_compressed(reader, &compression.binary_data)?;
+            Ok(Table::Binary {
+                default,
+                encoding,
+                data,
+            })
+        }
+        1 => {
+            let default = Default::read_from(reader)?;
+            let column = Box::new(read_column_v1(reader, compression)?);
+            Ok(Table::Array { default, column })
+        }
+        2 => {
+            let default = Default::read_from(reader)?;
+            let key_column = Box::new(read_column_v1(reader, compression)?);
+            let value_column = Box::new(read_column_v1(reader, compression)?);
+            Ok(Table::Map {
+                default,
+                key_column,
+                value_column,
+            })
+        }
+        _ => Err(BinaryError::InvalidTableTag(tag)),
+    }
+}
+
+/// Read a column written by version 1, which has no length prefix and only
+/// column tags 0 to 8
+fn read_column_v1<R: Read>(reader: &mut R, compression: &CompressionConfig) -> Result<Column> {
+    let tag = read_u8(reader)?;
+    match tag {
+        0 => {
+            let count = read_u32(reader)? as usize;
+            Ok(Column::Unit { count })
+        }
+        1 => {
+            let default = Default::read_from(reader)?;
+            let encoding = Encoding::read_from(reader)?;
+            let values = read_int_array_v1(reader)?;
+            Ok(Column::Int {
+                default,
+                encoding,
+                values,
+            })
+        }
+        2 => {
+            let default = Default::read_from(reader)?;
+            let values = read_int_array_v1(reader)?
+                .iter()
+                .map(|i| f64::from_bits(*i as u64))
+                .collect();
+            Ok(Column::Double { default, values })
+        }
+        3 => {
+            let default = Default::read_from(reader)?;
+            let encoding = Encoding::read_from(reader)?;
+            let lengths = usize_values(read_int_array_v1(reader)?)?;
+            let data = read_sized_byte_array_compressed(reader, &compression.strings)?;
+            Ok(Column::Binary {
+                default,
+                encoding,
+                lengths,
+                data,
+            })
+        }
+        4 => {
+            let default = Default::read_from(reader)?;
+            let lengths = usize_values(read_int_array_v1(reader)?)?;
+            let element = Box::new(read_column_v1(reader, compression)?);
+            Ok(Column::Array {
+                default,
+                lengths,
+                element,
+            })
+        }
+        5 => {
+            let default = Default::read_from(reader)?;
+            let field_count = read_u32(reader)?;
+            let mut fields = Vec::new();
+            for _ in 0..field_count {
+                let name = read_string(reader)?;
+                let column = read_column_v1(reader, compression)?;
+                fields.push(FieldColumn { name, column });
+            }
+            Ok(Column::Struct { default, fields })
+        }
+        6 => {
+            let default = Default::read_from(reader)?;
+            let tags = u32_values(read_int_array_v1(reader)?)?;
+            let variant_count = read_u32(reader)?;
+            let mut variants = Vec::new();
+            for _ in 0..variant_count {
+                let name = read_string(reader)?;
+                let tag = read_u32(reader)?;
+                let column = read_column_v1(reader, compression)?;
+                variants.push(VariantColumn { name, tag, column });
+            }
+            Ok(Column::Enum {
+                default,
+                tags,
+                variants,
+            })
+        }
+        7 => {
+            let lengths = usize_values(read_int_array_v1(reader)?)?;
+            let table = Box::new(read_table_v1(reader, compression)?);
+            Ok(Column::Nested { lengths, table })
+        }
+        8 => {
+            let inner = Box::new(read_column_v1(reader, compression)?);
+            Ok(Column::Reversed { inner })
+        }
+        _ => Err(BinaryError::InvalidColumnTag(tag)),
+    }
+}
+
+impl BlockStats {
+    /// Write block statistics to writer
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_u32(writer, self.columns.len() as u32)?;
+        for column in &self.columns {
+            column.write_to(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Read block statistics from reader
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let count = read_u32(reader)? as usize;
+        let mut columns = Vec::with_capacity(count.min(1024));
+        for _ in 0..count {
+            columns.push(ColumnStats::read_from(reader)?);
+        }
+        Ok(BlockStats { columns })
+    }
+}
+
+impl ColumnStats {
+    /// Write column statistics to writer
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_u32(writer, self.path.len() as u32)?;
+        for name in &self.path {
+            write_string(writer, name)?;
+        }
+        match &self.values {
+            ValueStats::Int {
+                min,
+                max,
+                default_count,
+            } => {
+                write_u8(writer, 0)?;
+                write_u64(writer, *min as u64)?;
+                write_u64(writer, *max as u64)?;
+                write_u64(writer, *default_count)?;
+            }
+            ValueStats::Double {
+                min,
+                max,
+                default_count,
+            } => {
+                write_u8(writer, 1)?;
+                write_u64(writer, min.to_bits())?;
+                write_u64(writer, max.to_bits())?;
+                write_u64(writer, *default_count)?;
+            }
+            ValueStats::Binary {
+                min_length,
+                max_length,
+                default_count,
+            } => {
+                write_u8(writer, 2)?;
+                write_u64(writer, *min_length)?;
+                write_u64(writer, *max_length)?;
+                write_u64(writer, *default_count)?;
+            }
+            ValueStats::Enum { tag_counts } => {
+                write_u8(writer, 3)?;
+                write_u32(writer, tag_counts.len() as u32)?;
+                for (tag, count) in tag_counts {
+                    write_u32(writer, *tag)?;
+                    write_u64(writer, *count)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read column statistics from reader
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let depth = read_u32(reader)? as usize;
+        let mut path = Vec::with_capacity(depth.min(64));
+        for _ in 0..depth {
+            path.push(read_string(reader)?);
+        }
+        let tag = read_u8(reader)?;
+        let values = match tag {
+            0 => ValueStats::Int {
+                min: read_u64(reader)? as i64,
+                max: read_u64(reader)? as i64,
+                default_count: read_u64(reader)?,
+            },
+            1 => ValueStats::Double {
+                min: f64::from_bits(read_u64(reader)?),
+                max: f64::from_bits(read_u64(reader)?),
+                default_count: read_u64(reader)?,
+            },
+            2 => ValueStats::Binary {
+                min_length: read_u64(reader)?,
+                max_length: read_u64(reader)?,
+                default_count: read_u64(reader)?,
+            },
+            3 => {
+                let count = read_u32(reader)? as usize;
+                let mut tag_counts = Vec::with_capacity(count.min(1024));
+                for _ in 0..count {
+                    tag_counts.push((read_u32(reader)?, read_u64(reader)?));
+                }
+                ValueStats::Enum { tag_counts }
+            }
+            _ => return Err(BinaryError::InvalidStatsTag(tag)),
+        };
+        Ok(ColumnStats { path, values })
+    }
+}
+
+impl Table {
+    /// Write table to writer
+    pub fn write_to<W: Write>(
+        &self,
+        writer: &mut W,
+        compression: &CompressionConfig,
+    ) -> Result<()> {
+        match self {
+            Table::Binary {
+                default,
+                encoding,
+                data,
+            } => {
+                write_u8(writer, 0)?; // Binary table tag
+                default.write_to(writer)?;
+                encoding.write_to(writer)?;
+                write_sized_byte_array_compressed(
+                    writer,
+                    data,
+                    compression.binary_data_algorithm(),
+                )?;
+            }
+            Table::Array { default, column } => {
+                write_u8(writer, 1)?; // Array table tag
+                default.write_to(writer)?;
+                column.write_to(writer, compression)?;
+            }
+            Table::Map {
+                default,
+                key_column,
+                value_column,
+            } => {
+                write_u8(writer, 2)?; // Map table tag
+                default.write_to(writer)?;
+                key_column.write_to(writer, compression)?;
+                value_column.write_to(writer, compression)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read table from reader
+    pub fn read_from<R: Read>(reader: &mut R, compression: &CompressionConfig) -> Result<Self> {
+        Self::read_projected(reader, compression, &Projection::All)
+    }
+
+    /// Read table from reader, decoding only the projected columns
+    pub fn read_projected<R: Read>(
+        reader: &mut R,
+        compression: &CompressionConfig,
+        projection: &Projection,
+    ) -> Result<Self> {
+        Self::read_rows(reader, compression, projection, None)
+    }
+
+    /// Read table from reader, decoding only the projected columns, given
+    /// its row count when it is known, e.g. from the block header
+    pub(crate) fn read_rows<R: Read>(
+        reader: &mut R,
+        compression: &CompressionConfig,
+        projection: &Projection,
+        rows: Option<usize>,
+    ) -> Result<Self> {
+        let tag = read_u8(reader)?;
+        match tag {
+            0 => {
+                if *projection != Projection::All {
+                    return Err(BinaryError::InvalidProjection(
+                        "Binary tables have no fields".to_string(),
+                    ));
+                }
+                let default = Default::read_from(reader)?;
+                let encoding = Encoding::read_from(reader)?;
+                let data =
+                    read_sized_byte_array_compressed(reader, compression.binary_data_algorithm())?;
+                Ok(Table::Binary {
+                    default,
+                    encoding,
+                    data,
+                })
+            }
+            1 => {
+                let default = Default::read_from(reader)?;
+                let column = Box::new(Column::read_rows(reader, compression, projection, rows)?);
+                Ok(Table::Array { default, column })
+            }
+            2 => {
+                let default = Default::read_from(reader)?;
+                let key_column = Box::new(Column::read_rows(
+                    reader,
+                    compression,
+                    &Projection::All,
+                    rows,
+                )?);
+                let value_column =
+                    Box::new(Column::read_rows(reader, compression, projection, rows)?);
+                Ok(Table::Map {
+                    default,
+                    key_column,
+                    value_column,
+                })
+            }
+            _ => Err(BinaryError::InvalidTableTag(tag)),
+        }
+    }
+}
+
+impl Column {
+    /// Write column to writer, prefixed with its encoded length
+    pub fn write_to<W: Write>(
+        &self,
+        writer: &mut W,
+        compression: &CompressionConfig,
+    ) -> Result<()> {
+        let mut body = Vec::new();
+        self.write_body(&mut body, compression)?;
+        let length = u32::try_from(body.len()).map_err(|_| {
+            BinaryError::SerializationError(format!(
+                "Column of {} bytes exceeds the maximum column size",
+                body.len()
+            ))
+        })?;
+        write_u32(writer, length)?;
+        writer.write_all(&body)?;
+        Ok(())
+    }
+
+    fn write_body<W: Write>(&self, writer: &mut W, compression: &CompressionConfig) -> Result<()> {
+        // Leaf columns holding nothing but defaults are stored as their schema
+        // alone. Columns with children are left to mark their own leaves, so
+        // projections never have to look inside a defaults column.
+        if matches!(
+            self,
+            Column::Int { .. }
+                | Column::Double { .. }
+                | Column::Binary { .. }
+                | Column::Enum { .. }
+        ) && self.is_all_default()
+        {
+            write_u8(writer, 10)?; // All-default column tag
+            write_u32(writer, self.row_count() as u32)?;
+            return self.schema().write_to(writer);
+        }
+
+        match self {
+            Column::Unit { count } => {
+                write_u8(writer, 0)?; // Unit column tag
+                write_u32(writer, *count as u32)?;
+            }
+            Column::Int {
+                default,
+                encoding,
+                values,
+            } => {
+                write_u8(writer, 1)?; // Int column tag
+                default.write_to(writer)?;
+                encoding.write_to(writer)?;
+                write_int_values(writer, values, int_encodings(encoding), compression)?;
+            }
+            Column::Double { default, values } => {
+                write_u8(writer, 2)?; // Double column tag
+                default.write_to(writer)?;
+                // Convert f64 to i64 bits for compression
+                let int_values: Vec<i64> = values.iter().map(|f| f.to_bits() as i64).collect();
+                write_int_values(writer, &int_values, DOUBLE_ENCODINGS, compression)?;
+            }
+            Column::Binary {
+                default,
+                encoding,
+                lengths,
+                data,
+            } => match choose_dictionary(
+                lengths,
+                data,
+                compression.strings_algorithm(),
+                compression.optimization_target,
+            )? {
+                Some(dictionary) => {
+                    write_u8(writer, 9)?; // Dictionary binary column tag
+                    default.write_to(writer)?;
+                    encoding.write_to(writer)?;
+                    write_dictionary(writer, &dictionary, compression)?;
+                }
+                None => {
+                    write_u8(writer, 3)?; // Binary column tag
+                    default.write_to(writer)?;
+                    encoding.write_to(writer)?;
+                    write_int_array_usize_compressed(
+                        writer,
+                        lengths,
+                        compression.optimization_target,
+                    )?;
+                    write_sized_byte_array_compressed(
+                        writer,
+                        data,
+                        compression.strings_algorithm(),
+                    )?;
+                }
+            },
+            Column::Array {
+                default,
+                lengths,
+                element,
+            } => {
+                write_u8(writer, 4)?; // Array column tag
+                default.write_to(writer)?;
+                write_int_array_usize_compressed(writer, lengths, compression.optimization_target)?;
+                element.write_to(writer, compression)?;
+            }
+            Column::Struct { default, fields } => {
+                write_u8(writer, 5)?; // Struct column tag
+                default.write_to(writer)?;
+                write_u32(writer, fields.len() as u32)?;
+                for field in fields {
+                    field.write_to(writer, compression)?;
+                }
+            }
+            Column::Enum {
+                default,
+                tags,
+                variants,
+            } => {
+                write_u8(writer, 6)?; // Enum column tag
+                default.write_to(writer)?;
+                write_u32_array_compressed(writer, tags, compression.optimization_target)?;
+                write_u32(writer, variants.len() as u32)?;
+                for variant in variants {
+                    variant.write_to(writer, compression)?;
+                }
+            }
+            Column::Nested { lengths, table } => {
+                write_u8(writer, 7)?; // Nested column tag
+                write_int_array_usize_compressed(writer, lengths, compression.optimization_target)?;
+                table.write_to(writer, compression)?;
+            }
+            Column::Reversed { inner } => {
+                write_u8(writer, 8)?; // Reversed column tag
+                inner.write_to(writer, compression)?;
+            }
+            Column::Map {
+                default,
+                lengths,
+                key,
+                value,
+            } => {
+                write_u8(writer, 11)?; // Map column tag
+                default.write_to(writer)?;
+                write_int_array_usize_compressed(writer, lengths, compression.optimization_target)?;
+                key.write_to(writer, compression)?;
+                value.write_to(writer, compression)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read column from reader
+    pub fn read_from<R: Read>(reader: &mut R, compression: &CompressionConfig) -> Result<Self> {
+        Self::read_projected(reader, compression, &Projection::All)
+    }
+
+    /// Read column from reader, decoding only the projected struct fields
+    ///
+    /// Decoding is bounded by the column's length prefix, which must be used
+    /// up exactly.
+    pub fn read_projected<R: Read>(
+        reader: &mut R,
+        compression: &CompressionConfig,
+        projection: &Projection,
+    ) -> Result<Self> {
+        Self::read_rows(reader, compression, projection, None)
+    }
+
+    /// Read column from reader, decoding only the projected struct fields,
+    /// given the number of values it holds when that is known from its
+    /// parent
+    ///
+    /// Columns storing only defaults or dictionary indices are checked
+    /// against that number before they are expanded.
+    fn read_rows<R: Read>(
+        reader: &mut R,
+        compression: &CompressionConfig,
+        projection: &Projection,
+        rows: Option<usize>,
+    ) -> Result<Self> {
+        let length = read_u32(reader)? as u64;
+        let mut body = reader.take(length);
+        let column = Self::read_body(&mut body, compression, projection, rows)?;
+        if body.limit() != 0 {
+            return Err(BinaryError::CorruptedData(format!(
+                "Column of {} bytes has {} trailing bytes",
+                length,
+                body.limit()
+            )));
+        }
+        Ok(column)
+    }
+
+    /// Read a column after its length prefix
+    ///
+    /// Takes a trait object so that nested columns, each bounded by its own
+    /// length, do not instantiate a new reader type per nesting level.
+    fn read_body(
+        mut reader: &mut dyn Read,
+        compression: &CompressionConfig,
+        projection: &Projection,
+        rows: Option<usize>,
+    ) -> Result<Self> {
+        let reader = &mut reader;
+        let tag = read_u8(reader)?;
+
+        // Only struct, array, nested, reversed and map columns have fields to
+        // select; map projections apply to the values, as for map tables
+        if *projection != Projection::All && !matches!(tag, 4 | 5 | 7 | 8 | 11) {
+            return Err(BinaryError::InvalidProjection(format!(
+                "Field path continues past a leaf column: {:?}",
+                projection
+            )));
+        }
+
+        match tag {
+            0 => {
+                let count = read_u32(reader)? as usize;
+                Ok(Column::Unit { count })
+            }
+            1 => {
+                let default = Default::read_from(reader)?;
+                let encoding = Encoding::read_from(reader)?;
+                let values = read_int_values(reader, compression)?;
+                Ok(Column::Int {
+                    default,
+                    encoding,
+                    values,
+                })
+            }
+            2 => {
+                let default = Default::read_from(reader)?;
+                let int_values = read_int_values(reader, compression)?;
+                let values: Vec<f64> = int_values
+                    .iter()
+                    .map(|i| f64::from_bits(*i as u64))
+                    .collect();
+                Ok(Column::Double { default, values })
+            }
+            3 => {
+                let default = Default::read_from(reader)?;
+                let encoding = Encoding::read_from(reader)?;
+                let lengths = read_int_array_usize_compressed(reader)?;
+                let data =
+                    read_sized_byte_array_compressed(reader, compression.strings_algorithm())?;
+                Ok(Column::Binary {
+                    default,
+                    encoding,
+                    lengths,
+                    data,
+                })
+            }
+            4 => {
+                let default = Default::read_from(reader)?;
+                let lengths = read_int_array_usize_compressed(reader)?;
+                let element = Box::new(Column::read_rows(
+                    reader,
+                    compression,
+                    projection,
+                    Some(total_length(&lengths)?),
+                )?);
+                Ok(Column::Array {
+                    default,
+                    lengths,
+                    element,
+                })
+            }
+            5 => {
+                let default = Default::read_from(reader)?;
+                let field_count = read_u32(reader)? as usize;
+                let mut fields = Vec::with_capacity(field_count);
+                for _ in 0..field_count {
+                    let name = read_string(reader)?;
+                    let field_projection = match projection {
+                        Projection::All => Some(projection),
+                        Projection::Fields(selected) => selected
+                            .iter()
+                            .find(|(field, _)| *field == name)
+                            .map(|(_, child)| child),
+                    };
+                    match field_projection {
+                        Some(field_projection) => {
+                            let column = Column::read_rows(
+                                reader,
+                                &compression.for_field(&name),
+                                field_projection,
+                                rows,
+                            )?;
+                            fields.push(FieldColumn { name, column });
+                        }
+                        None => skip_sized(reader)?,
+                    }
+                }
+
+                if let Projection::Fields(selected) = projection {
+                    if let Some((missing, _)) = selected
+                        .iter()
+                        .find(|(field, _)| !fields.iter().any(|f| f.name == *field))
+                    {
+                        return Err(BinaryError::InvalidProjection(format!(
+                            "Unknown field: {}",
+                            missing
+                        )));
+                    }
+                }
+                Ok(Column::Struct { default, fields })
+            }
+            6 => {
+                let default = Default::read_from(reader)?;
+                let tags = read_u32_array_compressed(reader)?;
+                let variant_count = read_u32(reader)? as usize;
+                let mut variants = Vec::with_capacity(variant_count);
+                for _ in 0..variant_count {
+                    let name = read_string(reader)?;
+                    let tag = read_u32(reader)?;
+                    let rows = tags.iter().filter(|&&row_tag| row_tag == tag).count();
+                    let column =
+                        Column::read_rows(reader, compression, &Projection::All, Some(rows))?;
+                    variants.push(VariantColumn { name, tag, column });
+                }
+                Ok(Column::Enum {
+                    default,
+                    tags,
+                    variants,
+                })
+            }
+            7 => {
+                let lengths = read_int_array_usize_compressed(reader)?;
+                let table = Box::new(Table::read_rows(
+                    reader,
+                    compression,
+                    projection,
+                    Some(total_length(&lengths)?),
+                )?);
+                Ok(Column::Nested { lengths, table })
+            }
+            8 => {
+                let inner = Box::new(Column::read_rows(reader, compression, projection, rows)?);
+                Ok(Column::Reversed { inner })
+            }
+            9 => {
+                let default = Default::read_from(reader)?;
+                let encoding = Encoding::read_from(reader)?;
+                let dictionary = read_dictionary(reader, compression.strings_algorithm())?;
+                let count = dictionary.indices.len();
+                if let Some(rows) = rows.filter(|&rows| rows != count) {
+                    return Err(BinaryError::CorruptedData(format!(
+                        "Dictionary column of {} values where {} are expected",
+                        count, rows
+                    )));
+                }
+                let (lengths, data) = dictionary_decode(&dictionary)?;
+                Ok(Column::Binary {
+                    default,
+                    encoding,
+                    lengths,
+                    data,
+                })
+            }
+            10 => {
+                let count = read_u32(reader)? as usize;
+                if let Some(rows) = rows.filter(|&rows| rows != count) {
+                    return Err(BinaryError::CorruptedData(format!(
+                        "Column of {} defaults where {} values are expected",
+                        count, rows
+                    )));
+                }
+                let schema = ValueSchema::read_from(reader)?;
+                let defaults = vec![LogicalValue::default_for_schema(&schema); count];
+                Column::from_values(&schema, &defaults)
+                    .map_err(|e| BinaryError::CorruptedData(e.to_string()))
+            }
+            11 => {
+                let default = Default::read_from(reader)?;
+                let lengths = read_int_array_usize_compressed(reader)?;
+                let entries = Some(total_length(&lengths)?);
+                let key = Box::new(Column::read_rows(
+                    reader,
+                    compression,
+                    &Projection::All,
+                    entries,
+                )?);
+                let value = Box::new(Column::read_rows(reader, compression, projection, entries)?);
+                Ok(Column::Map {
+                    default,
+                    lengths,
+                    key,
+                    value,
+                })
+            }
+            _ => Err(BinaryError::InvalidColumnTag(tag)),
+        }
+    }
+}
+
+impl FieldColumn {
+    /// Write field column to writer
+    pub fn write_to<W: Write>(
+        &self,
+        writer: &mut W,
+        compression: &CompressionConfig,
+    ) -> Result<()> {
+        write_string(writer, &self.name)?;
+        self.column
+            .write_to(writer, &compression.for_field(&self.name))?;
+        Ok(())
+    }
+
+    /// Read field column from reader
+    pub fn read_from<R: Read>(reader: &mut R, compression: &CompressionConfig) -> Result<Self> {
+        let name = read_string(reader)?;
+        let column = Column::read_from(reader, &compression.for_field(&name))?;
+        Ok(FieldColumn { name, column })
+    }
+}
+
+impl VariantColumn {
+    /// Write variant column to writer
+    pub fn write_to<W: Write>(
+        &self,
+        writer: &mut W,
+        compression: &CompressionConfig,
+    ) -> Result<()> {
+        write_string(writer, &self.name)?;
+        write_u32(writer, self.tag)?;
+        self.column.write_to(writer, compression)?;
+        Ok(())
+    }
+
+    /// Read variant column from reader
+    pub fn read_from<R: Read>(reader: &mut R, compression: &CompressionConfig) -> Result<Self> {
+        let name = read_string(reader)?;
+        let tag = read_u32(reader)?;
+        let column = Column::read_from(reader, compression)?;
+        Ok(VariantColumn { name, tag, column })
+    }
+}
+
+impl TableSchema {
+    /// Write table schema to writer, tagged like striped tables
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            TableSchema::Binary { default, encoding } => {
+                write_u8(writer, 0)?;
+                default.write_to(writer)?;
+                encoding.write_to(writer)?;
+            }
+            TableSchema::Array { default, element } => {
+                write_u8(writer, 1)?;
+                default.write_to(writer)?;
+                element.write_to(writer)?;
+            }
+            TableSchema::Map {
+                default,
+                key,
+                value,
+            } => {
+                write_u8(writer, 2)?;
+                default.write_to(writer)?;
+                key.write_to(writer)?;
+                value.write_to(writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read table schema from reader
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        Self::read_nested(reader, 0)
+    }
+
+    /// Read a table schema nested `depth` levels deep
+    fn read_nested<R: Read>(reader: &mut R, depth: usize) -> Result<Self> {
+        check_schema_depth(depth)?;
+        match read_u8(reader)? {
+            0 => Ok(TableSchema::Binary {
+                default: Default::read_from(reader)?,
+                encoding: Encoding::read_from(reader)?,
+            }),
+            1 => Ok(TableSchema::Array {
+                default: Default::read_from(reader)?,
+                element: Box::new(ValueSchema::read_nested(reader, depth + 1)?),
+            }),
+            2 => Ok(TableSchema::Map {
+                default: Default::read_from(reader)?,
+                key: Box::new(ValueSchema::read_nested(reader, depth + 1)?),
+                value: Box::new(ValueSchema::read_nested(reader, depth + 1)?),
+            }),
+            tag => Err(BinaryError::InvalidSchemaTag(tag)),
+        }
+    }
+}
+
+impl ValueSchema {
+    /// Write value schema to writer, tagged like striped columns
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            ValueSchema::Unit => write_u8(writer, 0)?,
+            ValueSchema::Int { default, encoding } => {
+                write_u8(writer, 1)?;
+                default.write_to(writer)?;
+                encoding.write_to(writer)?;
+            }
+            ValueSchema::Double { default } => {
+                write_u8(writer, 2)?;
+                default.write_to(writer)?;
+            }
+            ValueSchema::Binary { default, encoding } => {
+                write_u8(writer, 3)?;
+                default.write_to(writer)?;
+                encoding.write_to(writer)?;
+            }
+            ValueSchema::Array { default, element } => {
+                write_u8(writer, 4)?;
+                default.write_to(writer)?;
+                element.write_to(writer)?;
+            }
+            ValueSchema::Struct { default, fields } => {
+                write_u8(writer, 5)?;
+                default.write_to(writer)?;
+                write_u32(writer, fields.len() as u32)?;
+                for field in fields {
+                    write_string(writer, &field.name)?;
+                    field.schema.write_to(writer)?;
+                }
+            }
+            ValueSchema::Enum { default, variants } => {
+                write_u8(writer, 6)?;
+                default.write_to(writer)?;
+                write_u32(writer, variants.len() as u32)?;
+                for variant in variants {
+                    write_string(writer, &variant.name)?;
+                    write_u32(writer, variant.tag)?;
+                    variant.schema.write_to(writer)?;
+                }
+            }
+            ValueSchema::Nested { table } => {
+                write_u8(writer, 7)?;
+                table.write_to(writer)?;
+            }
+            ValueSchema::Reversed { inner } => {
+                write_u8(writer, 8)?;
+                inner.write_to(writer)?;
+            }
+            ValueSchema::Map {
+                default,
+                key,
+                value,
+            } => {
+                write_u8(writer, 11)?;
+                default.write_to(writer)?;
+                key.write_to(writer)?;
+                value.write_to(writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read value schema from reader
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        Self::read_nested(reader, 0)
+    }
+
+    /// Read a value schema nested `depth` levels deep
+    fn read_nested<R: Read>(reader: &mut R, depth: usize) -> Result<Self> {
+        check_schema_depth(depth)?;
+        match read_u8(reader)? {
+            0 => Ok(ValueSchema::Unit),
+            1 => Ok(ValueSchema::Int {
+                default: Default::read_from(reader)?,
+                encoding: Encoding::read_from(reader)?,
+            }),
+            2 => Ok(ValueSchema::Double {
+                default: Default::read_from(reader)?,
+            }),
+            3 => Ok(ValueSchema::Binary {
+                default: Default::read_from(reader)?,
+                encoding: Encoding::read_from(reader)?,
+            }),
+            4 => Ok(ValueSchema::Array {
+                default: Default::read_from(reader)?,
+                element: Box::new(ValueSchema::read_nested(reader, depth + 1)?),
+            }),
+            5 => {
+                let default = Default::read_from(reader)?;
+                let count = read_u32(reader)?;
+                let mut fields = Vec::new();
+                for _ in 0..count {
+                    fields.push(FieldSchema {
+                        name: read_string(reader)?,
+                        schema: ValueSchema::read_nested(reader, depth + 1)?,
+                    });
+                }
+                Ok(ValueSchema::Struct { default, fields })
+            }
+            6 => {
+                let default = Default::read_from(reader)?;
+                let count = read_u32(reader)?;
+                let mut variants = Vec::new();
+                for _ in 0..count {
+                    variants.push(VariantSchema {
+                        name: read_string(reader)?,
+                        tag: read_u32(reader)?,
+                        schema: ValueSchema::read_nested(reader, depth + 1)?,
+                    });
+                }
+                Ok(ValueSchema::Enum { default, variants })
+            }
+            7 => Ok(ValueSchema::Nested {
+                table: Box::new(TableSchema::read_nested(reader, depth + 1)?),
+            }),
+            8 => Ok(ValueSchema::Reversed {
+                inner: Box::new(ValueSchema::read_nested(reader, depth + 1)?),
+            }),
+            11 => Ok(ValueSchema::Map {
+                default: Default::read_from(reader)?,
+                key: Box::new(ValueSchema::read_nested(reader, depth + 1)?),
+                value: Box::new(ValueSchema::read_nested(reader, depth + 1)?),
+            }),
+            tag => Err(BinaryError::InvalidSchemaTag(tag)),
+        }
+    }
+}
+
+/// Fail on schemas nested deeper than `MAX_SCHEMA_DEPTH`, before recursing
+/// any further
+fn check_schema_depth(depth: usize) -> Result<()> {
+    if depth > MAX_SCHEMA_DEPTH {
+        return Err(BinaryError::CorruptedData(format!(
+            "Schema is nested more than {} levels deep",
+            MAX_SCHEMA_DEPTH
+        )));
+    }
+    Ok(())
+}
+
+impl CompressionConfig {
+    /// Write compression config to writer, leaving out the writer-only
+    /// optimization target
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.binary_data.write_to(writer)?;
+        self.strings.write_to(writer)?;
+        write_u32(writer, self.columns.len() as u32)?;
+        for column in &self.columns {
+            write_u32(writer, column.path.len() as u32)?;
+            for name in &column.path {
+                write_string(writer, name)?;
+            }
+            column.algorithm.write_to(writer)?;
+            column.int_pipeline.write_to(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Read compression config from reader
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let binary_data = CompressionAlgorithm::read_from(reader)?;
+        let strings = CompressionAlgorithm::read_from(reader)?;
+        let count = read_u32(reader)?;
+        let mut columns = Vec::new();
+        for _ in 0..count {
+            let depth = read_u32(reader)?;
+            let mut path = Vec::new();
+            for _ in 0..depth {
+                path.push(read_string(reader)?);
+            }
+            columns.push(ColumnCompressionConfig {
+                path,
+                algorithm: CompressionAlgorithm::read_from(reader)?,
+                int_pipeline: IntPipeline::read_from(reader)?,
+            });
+        }
+        Ok(CompressionConfig {
+            binary_data,
+            strings,
+            columns,
+            optimization_target: OptimizationTarget::default(),
+        })
+    }
+}
+
+impl CompressionAlgorithm {
+    /// Write compression algorithm and its level to writer
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            CompressionAlgorithm::None => write_u8(writer, 0)?,
+            CompressionAlgorithm::Zstd { level } => {
+                write_u8(writer, 1)?;
+                write_u32(writer, *level as u32)?;
+            }
+            CompressionAlgorithm::Lz4 => write_u8(writer, 2)?,
+            CompressionAlgorithm::Snappy => write_u8(writer, 3)?,
+            CompressionAlgorithm::Brotli { level } => {
+                write_u8(writer, 4)?;
+                write_u32(writer, *level)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read compression algorithm from reader
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        match read_u8(reader)? {
+            0 => Ok(CompressionAlgorithm::None),
+            1 => Ok(CompressionAlgorithm::Zstd {
+                level: read_u32(reader)? as i32,
+            }),
+            2 => Ok(CompressionAlgorithm::Lz4),
+            3 => Ok(CompressionAlgorithm::Snappy),
+            4 => Ok(CompressionAlgorithm::Brotli {
+                level: read_u32(reader)?,
+            }),
+            tag => Err(BinaryError::InvalidCompressionTag(tag)),
+        }
+    }
+}
+
+impl IntPipeline {
+    /// Write int pipeline to writer
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            IntPipeline::BitPacked => write_u8(writer, 0),
+            IntPipeline::Delta => write_u8(writer, 1),
+            IntPipeline::DeltaOfDelta => write_u8(writer, 2),
+            IntPipeline::RunLength => write_u8(writer, 3),
+            IntPipeline::Xor => write_u8(writer, 4),
+            IntPipeline::Plain => write_u8(writer, 5),
+        }
+    }
+
+    /// Read int pipeline from reader
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        match read_u8(reader)? {
+            0 => Ok(IntPipeline::BitPacked),
+            1 => Ok(IntPipeline::Delta),
+            2 => Ok(IntPipeline::DeltaOfDelta),
+            3 => Ok(IntPipeline::RunLength),
+            4 => Ok(IntPipeline::Xor),
+            5 => Ok(IntPipeline::Plain),
+            tag => Err(BinaryError::InvalidCompressionTag(tag)),
+        }
+    }
+}
+
+impl Default {
+    /// Write default to writer
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            Default::Allow => write_u8(writer, 0),
+            Default::Deny => write_u8(writer, 1),
+        }
+    }
+
+    /// Read default from reader
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        match read_u8(reader)? {
+            0 => Ok(Default::Allow),
+            1 => Ok(Default::Deny),
+            tag => Err(BinaryError::InvalidDefaultTag(tag)),
+        }
+    }
+}
+
+impl Encoding {
+    /// Write encoding to writer
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            Encoding::Int(int_enc) => {
+                write_u8(writer, 0)?;
+                int_enc.write_to(writer)?;
+            }
+            Encoding::Binary(bin_enc) => {
+                write_u8(writer, 1)?;
+                bin_enc.write_to(writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read encoding from reader
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        match read_u8(reader)? {
+            0 => Ok(Encoding::Int(IntEncoding::read_from(reader)?)),
+            1 => Ok(Encoding::Binary(BinaryEncoding::read_from(reader)?)),
+            tag => Err(BinaryError::InvalidEncodingTag(tag)),
+        }
+    }
+}
+
+impl IntEncoding {
+    /// Write int encoding to writer
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            IntEncoding::Int => write_u8(writer, 0),
+            IntEncoding::Date => write_u8(writer, 1),
+            IntEncoding::TimeSeconds => write_u8(writer, 2),
+            IntEncoding::TimeMilliseconds => write_u8(writer, 3),
+            IntEncoding::TimeMicroseconds => write_u8(writer, 4),
+        }
+    }
+
+    /// Read int encoding from reader
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        match read_u8(reader)? {
+            0 => Ok(IntEncoding::Int),
+            1 => Ok(IntEncoding::Date),
+            2 => Ok(IntEncoding::TimeSeconds),
+            3 => Ok(IntEncoding::TimeMilliseconds),
+            4 => Ok(IntEncoding::TimeMicroseconds),
+            tag => Err(BinaryError::InvalidIntEncodingTag(tag)),
+        }
+    }
+}
+
+impl BinaryEncoding {
+    /// Write binary encoding to writer
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            BinaryEncoding::Binary => write_u8(writer, 0),
+            BinaryEncoding::Utf8 => write_u8(writer, 1),
+        }
+    }
+
+    /// Read binary encoding from reader
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        match read_u8(reader)? {
+            0 => Ok(BinaryEncoding::Binary),
+            1 => Ok(BinaryEncoding::Utf8),
+            tag => Err(BinaryError::InvalidBinaryEncodingTag(tag)),
+        }
+    }
+}
+
+// Basic I/O primitives
+
+fn write_u8<W: Write>(writer: &mut W, value: u8) -> Result<()> {
+    writer.write_all(&[value])?;
+    Ok(())
+}
+
+pub(crate) fn read_u8<R: Read>(reader: &mut R) -> Result<u8> {
+    let mut buf = [0u8; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn write_u32<W: Write>(writer: &mut W, value: u32) -> Result<()> {
+    writer.write_all(&value.to_le_bytes())?;
+    Ok(())
+}
+
+pub(crate) fn read_u32<R: Read>(reader: &mut R) -> Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn write_u64<W: Write>(writer: &mut W, value: u64) -> Result<()> {
+    writer.write_all(&value.to_le_bytes())?;
+    Ok(())
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn write_string<W: Write>(writer: &mut W, s: &str) -> Result<()> {
+    let bytes = s.as_bytes();
+    write_u32(writer, bytes.len() as u32)?;
+    writer.write_all(bytes)?;
+    Ok(())
+}
+
+pub(crate) fn read_string<R: Read>(reader: &mut R) -> Result<String> {
+    let len = read_u32(reader)? as usize;
+    let mut buf = vec![0u8; len];
+    reader.read_exact(&mut buf)?;
+    String::from_utf8(buf).map_err(|e| BinaryError::DeserializationError(e.to_string()))
+}
+
+/// Skip over a length-prefixed column or block section without decoding it
+fn skip_sized<R: Read>(reader: &mut R) -> Result<()> {
+    let length = read_u32(reader)? as u64;
+    let skipped = std::io::copy(&mut reader.take(length), &mut std::io::sink())?;
+    if skipped != length {
+        return Err(BinaryError::CorruptedData(format!(
+            "Section of {} bytes truncated after {} bytes",
+            length, skipped
+        )));
+    }
+    Ok(())
+}
+
+/// Read a sized byte array written without compression, as in version 1
+/// headers
+///
+/// Format:
+/// - uncompressed_size: u32 (little-endian)
+/// - compressed_size: u32 (little-endian), equal to uncompressed_size
+/// - data: compressed_size bytes
+fn read_sized_byte_array<R: Read>(reader: &mut R) -> Result<Vec<u8>> {
+    let _uncompressed_size = read_u32(reader)?;
+    let compressed_size = read_u32(reader)? as usize;
+    let mut buf = vec![0u8; compressed_size];
+    reader.read_exact(&mut buf)?;
+    // For now, no decompression
+    Ok(buf)
+}
+
+/// Write integer array with full compression pipeline, with the encoding
+/// chosen from a sample of the values
+fn write_int_array_compressed<W: Write>(
+    writer: &mut W,
+    values: &[i64],
+    target: OptimizationTarget,
+) -> Result<()> {
+    write_u32(writer, values.len() as u32)?;
+    let compressed = compress_int_array_adaptive(values, DEFAULT_INT_ENCODINGS, target)?;
+    write_u32(writer, compressed.len() as u32)?;
+    writer.write_all(&compressed)?;
+    Ok(())
+}
+
+/// Read integer array with full decompression pipeline
+fn read_int_array_compressed<R: Read>(reader: &mut R) -> Result<Vec<i64>> {
+    let len = read_u32(reader)? as usize;
+    let compressed_size = read_u32(reader)? as usize;
+    let mut compressed = vec![0u8; compressed_size];
+    reader.read_exact(&mut compressed)?;
+    decompress_int_array(&compressed, len)
+}
+
+/// Read an int array written by version 1, whose frame-of-reference
+/// encoding carries no tag
+fn read_int_array_v1<R: Read>(reader: &mut R) -> Result<Vec<i64>> {
+    let len = read_u32(reader)? as usize;
+    let compressed_size = read_u32(reader)? as usize;
+    let mut compressed = vec![0u8; compressed_size];
+    reader.read_exact(&mut compressed)?;
+    decompress_int_array_untagged(&compressed, len)
+}
+
+/// Total of a column's lengths, the number of values its child holds
+pub(crate) fn total_length(lengths: &[usize]) -> Result<usize> {
+    lengths
+        .iter()
+        .try_fold(0usize, |total, &length| total.checked_add(length))
+        .ok_or_else(|| BinaryError::CorruptedData("Column lengths overflow".to_string()))
+}
+
+/// Convert decoded lengths or offsets, rejecting negative values
+fn usize_values(values: Vec<i64>) -> Result<Vec<usize>> {
+    values
+        .into_iter()
+        .map(|value| {
+            usize::try_from(value).map_err(|_| {
+                BinaryError::CorruptedData(format!("Invalid length {} in int array", value))
+            })
+        })
+        .collect()
+}
+
+/// Convert decoded enum tags or dictionary indices, rejecting values outside
+/// the u32 range
+fn u32_values(values: Vec<i64>) -> Result<Vec<u32>> {
+    values
+        .into_iter()
+        .map(|value| {
+            u32::try_from(value).map_err(|_| {
+                BinaryError::CorruptedData(format!("Invalid u32 value {} in int array", value))
+            })
+        })
+        .collect()
+}
+
+/// Encodings to try for an int column: timestamps usually pack smaller as
+/// deltas
+fn int_encodings(encoding: &Encoding) -> &'static [IntArrayEncoding] {
+    match encoding {
+        Encoding::Int(
+            IntEncoding::TimeSeconds
+            | IntEncoding::TimeMilliseconds
+            | IntEncoding::TimeMicroseconds,
+        ) => TIME_INT_ENCODINGS,
+        _ => DEFAULT_INT_ENCODINGS,
+    }
+}
+
+/// Write the values of an int or double column with the column's integer
+/// pipeline and compression
+///
+/// With no column override this is the same layout as
+/// `write_int_array_compressed`, with the encoding chosen from `candidates`.
+fn write_int_values<W: Write>(
+    writer: &mut W,
+    values: &[i64],
+    candidates: &[IntArrayEncoding],
+    compression: &CompressionConfig,
+) -> Result<()> {
+    let (pipeline, algorithm) = compression.int_codec();
+    let encoded = encode_int_array(
+        values,
+        pipeline,
+        candidates,
+        compression.optimization_target,
+    )?;
+    let compressed = match algorithm {
+        CompressionAlgorithm::None => encoded,
+        _ => compress_binary(&encoded, algorithm)?,
+    };
+    write_u32(writer, values.len() as u32)?;
+    write_u32(writer, compressed.len() as u32)?;
+    writer.write_all(&compressed)?;
+    Ok(())
+}
+
+/// Read the values of an int or double column written by `write_int_values`
+fn read_int_values<R: Read>(reader: &mut R, compression: &CompressionConfig) -> Result<Vec<i64>> {
+    let (encoded, len, pipeline) = read_int_encoded(reader, compression)?;
+    decode_int_array(&encoded, len, pipeline)
+}
+
+/// Read the values of an int column written by `write_int_values` as runs
+/// of equal values, without expanding run-length encoded arrays
+pub(crate) fn read_int_runs<R: Read>(
+    reader: &mut R,
+    compression: &CompressionConfig,
+) -> Result<Vec<IntRun>> {
+    let (encoded, len, pipeline) = read_int_encoded(reader, compression)?;
+    decode_int_runs(&encoded, len, pipeline)
+}
+
+/// Read and decompress an int array written by `write_int_values`, returning
+/// the encoded values, their count and the pipeline that encoded them
+fn read_int_encoded<R: Read>(
+    reader: &mut R,
+    compression: &CompressionConfig,
+) -> Result<(Vec<u8>, usize, IntPipeline)> {
+    let (pipeline, algorithm) = compression.int_codec();
+    let len = read_u32(reader)? as usize;
+    let compressed_size = read_u32(reader)? as usize;
+    let mut compressed = vec![0u8; compressed_size];
+    reader.read_exact(&mut compressed)?;
+    let encoded = match algorithm {
+        CompressionAlgorithm::None => compressed,
+        _ => decompress_binary_bounded(&compressed, algorithm, max_encoded_int_array_len(len))?,
+    };
+    Ok((encoded, len, pipeline))
+}
+
+fn write_int_array_usize_compressed<W: Write>(
+    writer: &mut W,
+    values: &[usize],
+    target: OptimizationTarget,
+) -> Result<()> {
+    let i64_values: Vec<i64> = values.iter().map(|&v| v as i64).collect();
+    write_int_array_compressed(writer, &i64_values, target)
+}
+
+pub(crate) fn read_int_array_usize_compressed<R: Read>(reader: &mut R) -> Result<Vec<usize>> {
+    usize_values(read_int_array_compressed(reader)?)
+}
+
+fn write_u32_array_compressed<W: Write>(
+    writer: &mut W,
+    values: &[u32],
+    target: OptimizationTarget,
+) -> Result<()> {
+    let i64_values: Vec<i64> = values.iter().map(|&v| v as i64).collect();
+    write_int_array_compressed(writer, &i64_values, target)
+}
+
+fn read_u32_array_compressed<R: Read>(reader: &mut R) -> Result<Vec<u32>> {
+    u32_values(read_int_array_compressed(reader)?)
+}
+
+/// Write a binary column dictionary: entry lengths, entry data compressed
+/// like plain binary column data, then the bit-packed row indices
+fn write_dictionary<W: Write>(
+    writer: &mut W,
+    dictionary: &BinaryDictionary,
+    compression: &CompressionConfig,
+) -> Result<()> {
+    let target = compression.optimization_target;
+    write_int_array_usize_compressed(writer, &dictionary.lengths, target)?;
+    write_sized_byte_array_compressed(writer, &dictionary.data, compression.strings_algorithm())?;
+    write_u32_array_compressed(writer, &dictionary.indices, target)
+}
+
+/// Read a binary column dictionary written by `write_dictionary`
+pub(crate) fn read_dictionary<R: Read>(
+    reader: &mut R,
+    algorithm: &CompressionAlgorithm,
+) -> Result<BinaryDictionary> {
+    let lengths = read_int_array_usize_compressed(reader)?;
+    let data = read_sized_byte_array_compressed(reader, algorithm)?;
+    let indices = read_u32_array_compressed(reader)?;
+    Ok(BinaryDictionary {
+        lengths,
+        data,
+        indices,
+    })
+}
+
+/// Write a sized byte array with compression
+fn write_sized_byte_array_compressed<W: Write>(
+    writer: &mut W,
+    data: &[u8],
+    algorithm: &crate::compression::CompressionAlgorithm,
+) -> Result<()> {
+    let compressed = crate::compression::compress_binary(data, algorithm)?;
+    write_u32(writer, data.len() as u32)?; // uncompressed size
+    write_u32(writer, compressed.len() as u32)?; // compressed size
+    writer.write_all(&compressed)?;
+    Ok(())
+}
+
+/// Read a sized byte array with decompression
+fn read_sized_byte_array_compressed<R: Read>(
+    reader: &mut R,
+    algorithm: &crate::compression::CompressionAlgorithm,
+) -> Result<Vec<u8>> {
+    let uncompressed_size = read_u32(reader)? as usize;
+    let compressed_size = read_u32(reader)? as usize;
+    let mut compressed = vec![0u8; compressed_size];
+    reader.read_exact(&mut compressed)?;
+    crate::compression::decompress_binary_exact(&compressed, algorithm, uncompressed_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{BinaryEncoding, Default, Encoding, IntEncoding};
+    use crate::logical::TableSchema;
+    use crate::striped::{Column, Table};
+
+    #[test]
+    fn test_binary_roundtrip_simple() {
+        // Create a simple integer array
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(crate::logical::ValueSchema::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+            }),
+        };
+
+        let table = Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+                values: vec![1, 2, 3, 4, 5],
+            }),
+        };
+
+        // Create binary file
+        let binary_file = BinaryFile::new(schema.clone(), table.clone());
+
+        // Serialize to bytes
+        let bytes = binary_file.to_bytes().expect("Failed to serialize");
+
+        // Deserialize from bytes
+        let deserialized = BinaryFile::from_bytes(&bytes).expect("Failed to deserialize");
+
+        // Check that we got back what we put in
+        assert_eq!(deserialized.header.schema, schema);
+        assert_eq!(deserialized.blocks.len(), 1);
+        assert_eq!(deserialized.blocks[0].table, table);
+    }
+
+    #[test]
+    fn test_binary_roundtrip_struct() {
+        use crate::logical::{FieldSchema, ValueSchema};
+
+        // Create a struct array
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(ValueSchema::Struct {
+                default: Default::Allow,
+                fields: vec![
+                    FieldSchema {
+                        name: "id".to_string(),
+                        schema: ValueSchema::Int {
+                            default: Default::Allow,
+                            encoding: Encoding::Int(IntEncoding::Int),
+                        },
+                    },
+                    FieldSchema {
+                        name: "name".to_string(),
+                        schema: ValueSchema::Binary {
+                            default: Default::Allow,
+                            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+                        },
+                    },
+                ],
+            }),
+        };
+
+        let table = Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Struct {
+                default: Default::Allow,
+                fields: vec![
+                    crate::striped::FieldColumn {
+                        name: "id".to_string(),
+                        column: Column::Int {
+                            default: Default::Allow,
+                            encoding: Encoding::Int(IntEncoding::Int),
+                            values: vec![1, 2, 3],
+                        },
+                    },
+                    crate::striped::FieldColumn {
+                        name: "name".to_string(),
+                        column: Column::Binary {
+                            default: Default::Allow,
+                            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+                            lengths: vec![5, 3, 7],
+                            data: b"AliceBobCharlie".to_vec(),
+                        },
+                    },
+                ],
+            }),
+        };
+
+        // Create binary file
+        let binary_file = BinaryFile::new(schema.clone(), table.clone());
+
+        // Serialize and deserialize
+        let bytes = binary_file.to_bytes().expect("Failed to serialize");
+        let deserialized = BinaryFile::from_bytes(&bytes).expect("Failed to deserialize");
+
+        // Verify roundtrip
+        assert_eq!(deserialized.header.schema, schema);
+        assert_eq!(deserialized.blocks[0].table, table);
+    }
+
+    #[test]
+    fn test_binary_writer_streams_blocks() {
+        use crate::compression::CompressionConfig;
+        use crate::data::{Table as LogicalTable, Value};
+
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(crate::logical::ValueSchema::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+            }),
+        };
+
+        let first = Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+                values: vec![1, 2, 3],
+            }),
+        };
+
+        let mut writer =
+            BinaryWriter::new(Vec::new(), schema.clone(), CompressionConfig::default()).unwrap();
+        writer.write_table(&first).unwrap();
+        writer
+            .write_rows(&LogicalTable::Array(vec![Value::Int(4), Value::Int(5)]))
+            .unwrap();
+
+        // Tables striped against another schema are rejected
+        let doubles = Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Double {
+                default: Default::Allow,
+                values: vec![1.5],
+            }),
+        };
+        assert!(matches!(
+            writer.write_table(&doubles),
+            Err(BinaryError::SerializationError(_))
+        ));
+        assert_eq!(writer.block_count(), 2);
+        let bytes = writer.finish().unwrap();
+
+        let deserialized = BinaryFile::from_bytes(&bytes).expect("Failed to deserialize");
+        assert_eq!(deserialized.header.schema, schema);
+        assert_eq!(deserialized.blocks.len(), 2);
+        assert_eq!(deserialized.blocks[0].table, first);
+        assert_eq!(deserialized.blocks[1].row_count, 2);
+        assert_eq!(
+            deserialized.blocks[1].table.to_logical().unwrap(),
+            LogicalTable::Array(vec![Value::Int(4), Value::Int(5)])
+        );
+
+        // A stream cut off before its trailer must not read as a complete file
+        let footer_len = 4 + 2 * INDEX_ENTRY_SIZE as usize + INDEX_TAIL_SIZE as usize;
+        let truncated = &bytes[..bytes.len() - footer_len - 8];
+        assert!(BinaryFile::from_bytes(truncated).is_err());
+    }
+
+    #[test]
+    fn test_binary_reader_is_lazy() {
+        use crate::compression::CompressionConfig;
+
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(crate::logical::ValueSchema::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+            }),
+        };
+
+        let block = |values: Vec<i64>| Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+                values,
+            }),
+        };
+
+        let mut writer =
+            BinaryWriter::new(Vec::new(), schema.clone(), CompressionConfig::default()).unwrap();
+        writer.write_table(&block(vec![1, 2])).unwrap();
+        writer.write_table(&block(vec![3, 4, 5])).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        // Stop after the first block; the second is never decoded
+        let mut reader = BinaryReader::new(std::io::Cursor::new(&bytes)).unwrap();
+        assert_eq!(reader.header().schema, schema);
+        let first = reader.next().unwrap().unwrap();
+        assert_eq!(first.table, block(vec![1, 2]));
+        assert_eq!(reader.blocks_read(), 1);
+
+        // Counted files read the same way
+        let mut file = BinaryFile::new(schema, block(vec![1, 2]));
+        file.blocks.push(Block {
+            row_count: 3,
+            table: block(vec![3, 4, 5]),
+        });
+        let counted = file.to_bytes().unwrap();
+        let blocks: Vec<Block> = BinaryReader::new(std::io::Cursor::new(&counted))
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1].table, block(vec![3, 4, 5]));
+    }
+
+    #[test]
+    fn test_indexed_reader_random_access() {
+        use crate::compression::CompressionConfig;
+
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(crate::logical::ValueSchema::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+            }),
+        };
+
+        let block = |values: Vec<i64>| Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+                values,
+            }),
+        };
+
+        let mut writer =
+            BinaryWriter::new(Vec::new(), schema.clone(), CompressionConfig::default()).unwrap();
+        writer.write_table(&block(vec![0, 1, 2])).unwrap();
+        writer.write_table(&block(vec![3, 4])).unwrap();
+        writer.write_table(&block(vec![5, 6, 7, 8])).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let mut reader = IndexedReader::new(std::io::Cursor::new(&bytes)).unwrap();
+        assert_eq!(reader.header().schema, schema);
+        assert_eq!(reader.block_count(), 3);
+        assert_eq!(reader.row_count(), 9);
+        assert_eq!(reader.read_block(2).unwrap().table, block(vec![5, 6, 7, 8]));
+        assert_eq!(reader.read_block(0).unwrap().table, block(vec![0, 1, 2]));
+        assert!(reader.read_block(3).is_err());
+
+        assert_eq!(reader.blocks_for_rows(0..1), 0..1);
+        assert_eq!(reader.blocks_for_rows(2..4), 0..2);
+        assert_eq!(reader.blocks_for_rows(5..6), 2..3);
+        assert_eq!(reader.blocks_for_rows(4..100), 1..3);
+        assert_eq!(reader.blocks_for_rows(9..12), 0..0);
+
+        // Index entries whose end overflows are rejected, not wrapped
+        let mut corrupted = bytes.clone();
+        let tail = corrupted.len() - INDEX_TAIL_SIZE as usize;
+        let index_offset = u64::from_le_bytes(corrupted[tail..tail + 8].try_into().unwrap());
+        let entry = index_offset as usize + 4;
+        corrupted[entry..entry + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            IndexedReader::new(std::io::Cursor::new(&corrupted)),
+            Err(BinaryError::CorruptedData(_))
+        ));
+
+        // Files written in one go carry the same footer
+        let counted = BinaryFile::new(schema, block(vec![1, 2]))
+            .to_bytes()
+            .unwrap();
+        let mut reader = IndexedReader::new(std::io::Cursor::new(&counted)).unwrap();
+        assert_eq!(reader.read_block(0).unwrap().table, block(vec![1, 2]));
+
+        // Files without a footer are still readable sequentially only
+        let footer_len = 4 + 3 * INDEX_ENTRY_SIZE as usize + INDEX_TAIL_SIZE as usize;
+        let legacy = &bytes[..bytes.len() - footer_len];
+        assert_eq!(BinaryFile::from_bytes(legacy).unwrap().blocks.len(), 3);
+        assert!(matches!(
+            IndexedReader::new(std::io::Cursor::new(legacy)),
+            Err(BinaryError::MissingBlockIndex)
+        ));
+    }
+
+    #[test]
+    fn test_block_stats_stored_per_block() {
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(crate::logical::ValueSchema::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+            }),
+        };
+        let block = |values: Vec<i64>| Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+                values,
+            }),
+        };
+
+        let mut writer =
+            BinaryWriter::new(Vec::new(), schema, CompressionConfig::default()).unwrap();
+        writer.write_table(&block(vec![5, -3, 0])).unwrap();
+        writer.write_table(&block(vec![100, 200])).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let mut reader = IndexedReader::new(std::io::Cursor::new(&bytes)).unwrap();
+        let stats = reader.read_block_stats(1).unwrap();
+        assert_eq!(
+            stats.get(&[]),
+            Some(&ValueStats::Int {
+                min: 100,
+                max: 200,
+                default_count: 0
+            })
+        );
+        let stats = reader.read_block_stats(0).unwrap();
+        assert_eq!(stats, BlockStats::compute(&block(vec![5, -3, 0])));
+        assert!(reader.read_block_stats(2).is_err());
+
+        // Sequential reads skip the statistics
+        let blocks = BinaryFile::from_bytes(&bytes).unwrap().blocks;
+        assert_eq!(blocks[1].table, block(vec![100, 200]));
+    }
+
+    #[test]
+    fn test_predicate_skips_blocks() {
+        use crate::data::{Field, Table as LogicalTable, Value};
+        use crate::logical::{FieldSchema, ValueSchema};
+
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(ValueSchema::Struct {
+                default: Default::Allow,
+                fields: vec![
+                    FieldSchema {
+                        name: "timestamp".to_string(),
+                        schema: ValueSchema::Int {
+                            default: Default::Allow,
+                            encoding: Encoding::Int(IntEncoding::TimeMilliseconds),
+                        },
+                    },
+                    FieldSchema {
+                        name: "value".to_string(),
+                        schema: ValueSchema::Double {
+                            default: Default::Allow,
+                        },
+                    },
+                ],
+            }),
+        };
+        let rows = |timestamps: std::ops::Range<i64>| {
+            LogicalTable::Array(
+                timestamps
+                    .map(|t| {
+                        Value::Struct(vec![
+                            Field {
+                                name: "timestamp".to_string(),
+                                value: Value::Int(t),
+                            },
+                            Field {
+                                name: "value".to_string(),
+                                value: Value::Double(t as f64 / 10.0),
+                            },
+                        ])
+                    })
+                    .collect(),
+            )
+        };
+
+        let mut writer =
+            BinaryWriter::new(Vec::new(), schema, CompressionConfig::default()).unwrap();
+        for start in (0..100).step_by(10) {
+            writer.write_rows(&rows(start..start + 10)).unwrap();
+        }
+        let bytes = writer.finish().unwrap();
+
+        let predicate = Predicate::parse("timestamp >= 35 AND timestamp < 52").unwrap();
+        let mut reader = BinaryReader::new(std::io::Cursor::new(&bytes))
+            .unwrap()
+            .with_predicate(predicate.clone());
+        let blocks = reader.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(reader.blocks_read(), 3);
+        assert_eq!(reader.blocks_skipped(), 7);
+        let timestamps: Vec<Value> = blocks
+            .iter()
+            .flat_map(|block| match block.table.to_logical().unwrap() {
+                LogicalTable::Array(values) => values,
+                _ => unreachable!(),
+            })
+            .map(|row| match row {
+                Value::Struct(fields) => fields[0].value.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(timestamps, (35..52).map(Value::Int).collect::<Vec<_>>());
+
+        let mut indexed = IndexedReader::new(std::io::Cursor::new(&bytes)).unwrap();
+        assert_eq!(indexed.blocks_matching(&predicate).unwrap(), vec![3, 4, 5]);
+
+        // Predicates on columns outside the projection are rejected
+        let mut reader = BinaryReader::new(std::io::Cursor::new(&bytes))
+            .unwrap()
+            .with_projection(Projection::new(&[&["value"]]).unwrap())
+            .with_predicate(predicate);
+        assert!(matches!(
+            reader.next(),
+            Some(Err(BinaryError::InvalidPredicate(_)))
+        ));
+    }
+
+    #[test]
+    fn test_per_column_compression_roundtrip() {
+        use crate::compression::{ColumnCompressionConfig, CompressionAlgorithm, IntPipeline};
+        use crate::logical::{FieldSchema, ValueSchema};
+        use crate::striped::FieldColumn;
+
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(ValueSchema::Struct {
+                default: Default::Allow,
+                fields: vec![
+                    FieldSchema {
+                        name: "id".to_string(),
+                        schema: ValueSchema::Int {
+                            default: Default::Allow,
+                            encoding: Encoding::Int(IntEncoding::Int),
+                        },
+                    },
+                    FieldSchema {
+                        name: "payload".to_string(),
+                        schema: ValueSchema::Binary {
+                            default: Default::Allow,
+                            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+                        },
+                    },
+                ],
+            }),
+        };
+        // Distinct payloads, so the column is not dictionary encoded
+        let payloads: Vec<String> = ["a", "b", "c"]
+            .iter()
+            .map(|suffix| format!("cold data {} ", suffix).repeat(100))
+            .collect();
+        let table = Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Struct {
+                default: Default::Allow,
+                fields: vec![
+                    FieldColumn {
+                        name: "id".to_string(),
+                        column: Column::Int {
+                            default: Default::Allow,
+                            encoding: Encoding::Int(IntEncoding::Int),
+                            values: vec![1, i64::MIN, i64::MAX],
+                        },
+                    },
+                    FieldColumn {
+                        name: "payload".to_string(),
+                        column: Column::Binary {
+                            default: Default::Allow,
+                            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+                            lengths: payloads.iter().map(String::len).collect(),
+                            data: payloads.concat().into_bytes(),
+                        },
+                    },
+                ],
+            }),
+        };
+
+        let compression = CompressionConfig {
+            binary_data: CompressionAlgorithm::None,
+            strings: CompressionAlgorithm::None,
+            columns: vec![
+                ColumnCompressionConfig {
+                    path: vec!["id".to_string()],
+                    algorithm: CompressionAlgorithm::None,
+                    int_pipeline: IntPipeline::Plain,
+                },
+                ColumnCompressionConfig {
+                    path: vec!["payload".to_string()],
+                    algorithm: CompressionAlgorithm::Zstd { level: 19 },
+                    int_pipeline: IntPipeline::BitPacked,
+                },
+            ],
+            optimization_target: OptimizationTarget::MinimizeSpace,
+        };
+        let file = BinaryFile::new_with_compression(schema.clone(), table.clone(), compression);
+        let bytes = file.to_bytes().unwrap();
+
+        let read_back = BinaryFile::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            read_back.header.compression.columns,
+            file.header.compression.columns
+        );
+        assert_eq!(read_back.blocks[0].table, table);
+
+        // The payload override compresses the only large column
+        let uncompressed = BinaryFile::new_with_compression(
+            schema,
+            table,
+            CompressionConfig {
+                binary_data: CompressionAlgorithm::None,
+                strings: CompressionAlgorithm::None,
+                columns: Vec::new(),
+                optimization_target: OptimizationTarget::MinimizeSpace,
+            },
+        );
+        assert!(bytes.len() * 4 < uncompressed.to_bytes().unwrap().len());
+    }
+
+    #[test]
+    fn test_projected_read_skips_columns() {
+        use crate::data::{Field, Table as LogicalTable, Value};
+        use crate::logical::{FieldSchema, ValueSchema};
+
+        let int_schema = || ValueSchema::Int {
+            default: Default::Allow,
+            encoding: Encoding::Int(IntEncoding::Int),
+        };
+        let field = |name: &str, schema: ValueSchema| FieldSchema {
+            name: name.to_string(),
+            schema,
+        };
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(ValueSchema::Struct {
+                default: Default::Allow,
+                fields: vec![
+                    field("timestamp", int_schema()),
+                    field(
+                        "metrics",
+                        ValueSchema::Struct {
+                            default: Default::Allow,
+                            fields: vec![field("cpu", int_schema()), field("mem", int_schema())],
+                        },
+                    ),
+                    field(
+                        "host",
+                        ValueSchema::Binary {
+                            default: Default::Allow,
+                            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+                        },
+                    ),
+                ],
+            }),
+        };
+
+        let row = |timestamp: i64, cpu: i64, mem: i64, host: &str| {
+            Value::Struct(vec![
+                Field {
+                    name: "timestamp".to_string(),
+                    value: Value::Int(timestamp),
+                },
+                Field {
+                    name: "metrics".to_string(),
+                    value: Value::Struct(vec![
+                        Field {
+                            name: "cpu".to_string(),
+                            value: Value::Int(cpu),
+                        },
+                        Field {
+                            name: "mem".to_string(),
+                            value: Value::Int(mem),
+                        },
+                    ]),
+                },
+                Field {
+                    name: "host".to_string(),
+                    value: Value::Binary(host.as_bytes().to_vec()),
+                },
+            ])
+        };
+        let logical = LogicalTable::Array(vec![row(1, 50, 1024, "a"), row(2, 75, 2048, "b")]);
+        let table = Table::from_logical(&schema, &logical).unwrap();
+        let bytes = BinaryFile::new(schema, table).to_bytes().unwrap();
+
+        let projection = Projection::new(&[&["metrics", "cpu"], &["timestamp"]]).unwrap();
+        let blocks: Vec<Block> = BinaryReader::new(std::io::Cursor::new(&bytes))
+            .unwrap()
+            .with_projection(projection.clone())
+            .collect::<Result<_>>()
+            .unwrap();
+
+        let expected = LogicalTable::Array(vec![
+            Value::Struct(vec![
+                Field {
+                    name: "timestamp".to_string(),
+                    value: Value::Int(1),
+                },
+                Field {
+                    name: "metrics".to_string(),
+                    value: Value::Struct(vec![Field {
+                        name: "cpu".to_string(),
+                        value: Value::Int(50),
+                    }]),
+                },
+            ]),
+            Value::Struct(vec![
+                Field {
+                    name: "timestamp".to_string(),
+                    value: Value::Int(2),
+                },
+                Field {
+                    name: "metrics".to_string(),
+                    value: Value::Struct(vec![Field {
+                        name: "cpu".to_string(),
+                        value: Value::Int(75),
+                    }]),
+                },
+            ]),
+        ]);
+        assert_eq!(blocks[0].table.to_logical().unwrap(), expected);
+
+        let mut indexed = IndexedReader::new(std::io::Cursor::new(&bytes)).unwrap();
+        let block = indexed.read_block_projected(0, &projection).unwrap();
+        assert_eq!(block.table.to_logical().unwrap(), expected);
+
+        // Unknown fields and paths past a leaf column are rejected
+        for bad in [
+            &[&["metrics", "disk"][..]][..],
+            &[&["timestamp", "x"][..]][..],
+        ] {
+            let projection = Projection::new(bad).unwrap();
+            assert!(matches!(
+                indexed.read_block_projected(0, &projection),
+                Err(BinaryError::InvalidProjection(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_lz4_snappy_files_roundtrip() {
+        use crate::compression::CompressionAlgorithm;
+
+        let schema = TableSchema::Binary {
+            default: Default::Allow,
+            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+        };
+        let table = Table::Binary {
+            default: Default::Allow,
+            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+            data: b"log line ".repeat(200),
+        };
+
+        for algorithm in [CompressionAlgorithm::Lz4, CompressionAlgorithm::Snappy] {
+            let compression = CompressionConfig {
+                binary_data: algorithm.clone(),
+                strings: algorithm.clone(),
+                columns: Vec::new(),
+                optimization_target: OptimizationTarget::MinimizeSpace,
+            };
+            let file = BinaryFile::new_with_compression(schema.clone(), table.clone(), compression);
+            let bytes = file.to_bytes().unwrap();
+            assert!(bytes.len() < 1800);
+
+            let read_back = BinaryFile::from_bytes(&bytes).unwrap();
+            assert_eq!(read_back.header.compression.binary_data, algorithm);
+            assert_eq!(read_back.blocks[0].table, table);
+        }
+    }
+
+    #[test]
+    fn test_time_columns_delta_encoded() {
+        let file_size = |encoding: IntEncoding| {
+            let schema = TableSchema::Array {
+                default: Default::Allow,
+                element: Box::new(crate::logical::ValueSchema::Int {
+                    default: Default::Allow,
+                    encoding: Encoding::Int(encoding.clone()),
+                }),
+            };
+            let table = Table::Array {
+                default: Default::Allow,
+                column: Box::new(Column::Int {
+                    default: Default::Allow,
+                    encoding: Encoding::Int(encoding),
+                    values: (0..4096).map(|i| 1_704_067_200_000 + i * 250).collect(),
+                }),
+            };
+            let bytes = BinaryFile::new(schema, table.clone()).to_bytes().unwrap();
+            let read_back = BinaryFile::from_bytes(&bytes).unwrap();
+            assert_eq!(read_back.blocks[0].table, table);
+            bytes.len()
+        };
+
+        // Same values, but only the timestamp column tries delta encodings
+        assert!(file_size(IntEncoding::TimeMilliseconds) + 1000 < file_size(IntEncoding::Int));
+    }
+
+    #[test]
+    fn test_enum_tag_runs_roundtrip() {
+        use crate::logical::{ValueSchema, VariantSchema};
+        use crate::striped::VariantColumn;
+
+        let variant = |name: &str, tag: u32| VariantSchema {
+            name: name.to_string(),
+            tag,
+            schema: ValueSchema::Unit,
+        };
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(ValueSchema::Enum {
+                default: Default::Allow,
+                variants: vec![variant("ok", 0), variant("error", 1)],
+            }),
+        };
+        let tags: Vec<u32> = [0, 1, 0].iter().flat_map(|&t| vec![t; 4000]).collect();
+        let table = Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Enum {
+                default: Default::Allow,
+                tags,
+                variants: vec![
+                    VariantColumn {
+                        name: "ok".to_string(),
+                        tag: 0,
+                        column: Column::Unit { count: 8000 },
+                    },
+                    VariantColumn {
+                        name: "error".to_string(),
+                        tag: 1,
+                        column: Column::Unit { count: 4000 },
+                    },
+                ],
+            }),
+        };
+
+        let bytes = BinaryFile::new(schema, table.clone()).to_bytes().unwrap();
+        // Bit-packing alone would need 1500 bytes for the tags
+        assert!(bytes.len() < 1000);
+        assert_eq!(
+            BinaryFile::from_bytes(&bytes).unwrap().blocks[0].table,
+            table
+        );
+    }
+
+    #[test]
+    fn test_low_cardinality_binary_columns_use_dictionary() {
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(crate::logical::ValueSchema::Binary {
+                default: Default::Allow,
+                encoding: Encoding::Binary(BinaryEncoding::Utf8),
+            }),
+        };
+        let binary_column = |values: Vec<String>| Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Binary {
+                default: Default::Allow,
+                encoding: Encoding::Binary(BinaryEncoding::Utf8),
+                lengths: values.iter().map(String::len).collect(),
+                data: values.concat().into_bytes(),
+            }),
+        };
+        let column_tag = |table: &Table| {
+            let Table::Array { column, .. } = table else {
+                unreachable!()
+            };
+            let mut encoded = Vec::new();
+            column
+                .write_to(&mut encoded, &CompressionConfig::default())
+                .unwrap();
+            // The column tag follows the length prefix
+            encoded[4]
+        };
+
+        let statuses = ["active", "suspended", "pending"];
+        let low = binary_column((0..3000).map(|i| statuses[i % 3].to_string()).collect());
+        let uncompressed = CompressionConfig {
+            binary_data: CompressionAlgorithm::None,
+            strings: CompressionAlgorithm::None,
+            columns: Vec::new(),
+            optimization_target: OptimizationTarget::MinimizeSpace,
+        };
+        let bytes = BinaryFile::new_with_compression(schema.clone(), low.clone(), uncompressed)
+            .to_bytes()
+            .unwrap();
+        assert_eq!(column_tag(&low), 9);
+        assert!(bytes.len() < 2000);
+        assert_eq!(BinaryFile::from_bytes(&bytes).unwrap().blocks[0].table, low);
+
+        // Indices disagreeing with the row count are rejected before expanding
+        let Table::Array { column, .. } = &low else {
+            unreachable!()
+        };
+        let mut encoded = Vec::new();
+        column
+            .write_to(&mut encoded, &CompressionConfig::default())
+            .unwrap();
+        match Column::read_rows(
+            &mut encoded.as_slice(),
+            &CompressionConfig::default(),
+            &Projection::All,
+            Some(2999),
+        ) {
+            Err(BinaryError::CorruptedData(message)) => assert!(message.contains("Dictionary")),
+            other => panic!("expected corrupted data, got {:?}", other),
+        }
+
+        // Every value distinct: plain binary column
+        let high = binary_column((0..3000).map(|i| format!("host-{}", i)).collect());
+        let bytes = BinaryFile::new(schema, high.clone()).to_bytes().unwrap();
+        assert_eq!(column_tag(&high), 3);
+        assert_eq!(
+            BinaryFile::from_bytes(&bytes).unwrap().blocks[0].table,
+            high
+        );
+    }
+
+    #[test]
+    fn test_all_default_columns_store_only_schema() {
+        let rows = 10_000;
+        let int_column = |default, values| Column::Int {
+            default,
+            encoding: Encoding::Int(IntEncoding::Int),
+            values,
+        };
+        let encode = |column: &Column| {
+            let mut encoded = Vec::new();
+            column
+                .write_to(&mut encoded, &CompressionConfig::default())
+                .unwrap();
+            encoded
+        };
+
+        // Only columns allowing defaults are replaced by the marker, which
+        // follows the length prefix
+        let optional = int_column(Default::Allow, vec![0; rows]);
+        let required = int_column(Default::Deny, vec![0; rows]);
+        assert_eq!(encode(&optional)[4], 10);
+        assert_eq!(encode(&required)[4], 1);
+        assert_eq!(
+            Column::read_from(
+                &mut encode(&optional).as_slice(),
+                &CompressionConfig::default()
+            )
+            .unwrap(),
+            optional
+        );
+
+        let table = Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Struct {
+                default: Default::Allow,
+                fields: vec![
+                    FieldColumn {
+                        name: "id".to_string(),
+                        column: int_column(Default::Deny, (0..rows as i64).collect()),
+                    },
+                    FieldColumn {
+                        name: "optional".to_string(),
+                        column: optional,
+                    },
+                ],
+            }),
+        };
+        let bytes = BinaryFile::new(table.schema(), table.clone())
+            .to_bytes()
+            .unwrap();
+        assert_eq!(
+            BinaryFile::from_bytes(&bytes).unwrap().blocks[0].table,
+            table
+        );
+
+        // The stored count must match the rows of the block before any
+        // defaults are expanded
+        let mut encoded = Vec::new();
+        let small = Table::Array {
+            default: Default::Allow,
+            column: Box::new(int_column(Default::Allow, vec![0; 3])),
+        };
+        small
+            .write_to(&mut encoded, &CompressionConfig::default())
+            .unwrap();
+        // Table tag, table default, column length and column tag
+        let count = 1 + 1 + 4 + 1;
+        encoded[count..count + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        match Table::read_rows(
+            &mut encoded.as_slice(),
+            &CompressionConfig::default(),
+            &Projection::All,
+            Some(3),
+        ) {
+            Err(BinaryError::CorruptedData(message)) => assert!(message.contains("defaults")),
+            other => panic!("expected corrupted data, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_map_columns_roundtrip() {
+        use crate::data::{Field, Value};
+        use crate::logical::{FieldSchema, ValueSchema};
+
+        let map_schema = ValueSchema::Map {
+            default: Default::Allow,
+            key: Box::new(ValueSchema::Binary {
+                default: Default::Allow,
+                encoding: Encoding::Binary(BinaryEncoding::Utf8),
+            }),
+            value: Box::new(ValueSchema::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+            }),
+        };
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(ValueSchema::Struct {
+                default: Default::Allow,
+                fields: vec![FieldSchema {
+                    name: "tags".to_string(),
+                    schema: map_schema,
+                }],
+            }),
+        };
+        let rows = LogicalTable::Array(
+            (0..100)
+                .map(|i| {
+                    Value::Struct(vec![Field {
+                        name: "tags".to_string(),
+                        value: Value::Map(
+                            (0..i % 3)
+                                .map(|j| {
+                                    (Value::Binary(format!("k{}", j).into_bytes()), Value::Int(i))
+                                })
+                                .collect(),
+                        ),
+                    }])
+                })
+                .collect(),
+        );
+        let table = Table::from_logical(&schema, &rows).unwrap();
+
+        for version in [FormatVersion::V2, FormatVersion::V3] {
+            let bytes = BinaryFile::new(schema.clone(), table.clone())
+                .to_bytes_version(version)
+                .unwrap();
+            let file = BinaryFile::from_bytes(&bytes).unwrap();
+            assert_eq!(file.header.schema, schema);
+            assert_eq!(file.blocks[0].table.to_logical().unwrap(), rows);
+        }
+    }
+
+    #[test]
+    fn test_double_columns_xor_encoded() {
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(crate::logical::ValueSchema::Double {
+                default: Default::Allow,
+            }),
+        };
+        let mut values: Vec<f64> = (0..4000).map(|i| 20.0 + (i / 50) as f64 * 0.25).collect();
+        values.extend([f64::NAN, -0.0, f64::INFINITY, f64::MIN_POSITIVE]);
+        let table = Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Double {
+                default: Default::Allow,
+                values: values.clone(),
+            }),
+        };
+
+        let bytes = BinaryFile::new(schema, table).to_bytes().unwrap();
+        // 8 bytes per reading without XOR encoding
+        assert!(bytes.len() < 4000);
+
+        let read_back = BinaryFile::from_bytes(&bytes).unwrap();
+        let Table::Array { column, .. } = &read_back.blocks[0].table else {
+            panic!("expected an array table");
+        };
+        let Column::Double {
+            values: decoded, ..
+        } = column.as_ref()
+        else {
+            panic!("expected a double column");
+        };
+        let bits = |values: &[f64]| values.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(decoded), bits(&values));
+    }
+
+    #[test]
+    fn test_optimization_target_needs_no_reader_config() {
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(crate::logical::ValueSchema::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::TimeMilliseconds),
+            }),
+        };
+        let table = Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::TimeMilliseconds),
+                values: (0..5000)
+                    .map(|i| 1_704_067_200_000 + i * 1000 + i % 7)
+                    .collect(),
+            }),
+        };
+
+        for target in [
+            OptimizationTarget::MinimizeSpace,
+            OptimizationTarget::Balanced,
+            OptimizationTarget::MaximizeSpeed,
+        ] {
+            let compression = CompressionConfig {
+                optimization_target: target,
+                ..CompressionConfig::default()
+            };
+            let file = BinaryFile::new_with_compression(schema.clone(), table.clone(), compression);
+            let bytes = file.to_bytes().unwrap();
+
+            let read_back = BinaryFile::from_bytes(&bytes).unwrap();
+            assert_eq!(read_back.blocks[0].table, table);
+            // The target is a writer preference, not part of the header
+            assert_eq!(
+                read_back.header.compression.optimization_target,
+                OptimizationTarget::default()
+            );
+        }
+    }
+
+    #[test]
+    fn test_corrupted_row_counts_rejected() {
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(crate::logical::ValueSchema::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+            }),
+        };
+        // A struct whose fields disagree on their row count, which the writer
+        // does not check but every reader must
+        let table = Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Struct {
+                default: Default::Allow,
+                fields: vec![
+                    FieldColumn {
+                        name: "id".to_string(),
+                        column: Column::Int {
+                            default: Default::Allow,
+                            encoding: Encoding::Int(IntEncoding::Int),
+                            values: vec![1, 2, 3],
+                        },
+                    },
+                    FieldColumn {
+                        name: "score".to_string(),
+                        column: Column::Int {
+                            default: Default::Allow,
+                            encoding: Encoding::Int(IntEncoding::Int),
+                            values: vec![10, 20],
+                        },
+                    },
+                ],
+            }),
+        };
+        let bytes = BinaryFile::new(schema, table).to_bytes().unwrap();
+
+        match BinaryFile::from_bytes(&bytes) {
+            Err(BinaryError::CorruptedData(message)) => assert!(message.contains("score")),
+            other => panic!("expected corrupted data, got {:?}", other),
+        }
+        let mut indexed = IndexedReader::new(std::io::Cursor::new(&bytes)).unwrap();
+        assert!(matches!(
+            indexed.read_block(0),
+            Err(BinaryError::CorruptedData(_))
+        ));
+
+        // A block claiming more rows than its table holds, read with and
+        // without a predicate
+        let table = Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Struct {
+                default: Default::Allow,
+                fields: vec![FieldColumn {
+                    name: "id".to_string(),
+                    column: Column::Int {
+                        default: Default::Deny,
+                        encoding: Encoding::Int(IntEncoding::Int),
+                        values: vec![1, 2, 3],
+                    },
+                }],
+            }),
+        };
+        let compression = CompressionConfig::default();
+        let mut bytes = MAGIC_NUMBER_V2.to_vec();
+        Header::new(table.schema(), compression.clone())
+            .write_to(&mut bytes)
+            .unwrap();
+        write_u32(&mut bytes, 1).unwrap();
+        write_block(&mut bytes, 4, &table, &compression).unwrap();
+
+        let predicate = Predicate::parse("id >= 0").unwrap();
+        for reader in [
+            BinaryReader::new(bytes.as_slice()).unwrap(),
+            BinaryReader::new(bytes.as_slice())
+                .unwrap()
+                .with_predicate(predicate),
+        ] {
+            match reader.collect::<Result<Vec<_>>>() {
+                Err(BinaryError::CorruptedData(message)) => assert!(message.contains("4 rows")),
+                other => panic!("expected corrupted data, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_checksums_detect_corruption() {
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(crate::logical::ValueSchema::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+            }),
+        };
+        let table = Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+                values: (0..1000).map(|i| i * 37 % 1009).collect(),
+            }),
+        };
+        let bytes = BinaryFile::new(schema, table).to_bytes().unwrap();
+        let entry = IndexedReader::new(std::io::Cursor::new(&bytes))
+            .unwrap()
+            .index()[0];
+
+        let mut reader = IndexedReader::new(std::io::Cursor::new(&bytes)).unwrap();
+        assert!(reader.verify().is_ok());
+
+        // A flipped bit inside the schema JSON
+        let mut corrupted = bytes.clone();
+        corrupted[30] ^= 0x01;
+        match BinaryFile::from_bytes(&corrupted) {
+            Err(BinaryError::ChecksumMismatch { section, .. }) => assert_eq!(section, "header"),
+            other => panic!("expected a header checksum mismatch, got {:?}", other),
+        }
 
-fn write_string<W: Write>(writer: &mut W, s: &str) -> Result<()> {
-    let bytes = s.as_bytes();
-    write_u32(writer, bytes.len() as u32)?;
-    writer.write_all(bytes)?;
-    Ok(())
-}
+        // A flipped bit inside the block's table data
+        let mut corrupted = bytes.clone();
+        corrupted[(entry.offset + entry.length / 2) as usize] ^= 0x10;
+        for result in [
+            BinaryFile::from_bytes(&corrupted).map(|_| ()),
+            IndexedReader::new(std::io::Cursor::new(&corrupted))
+                .unwrap()
+                .verify(),
+        ] {
+            match result {
+                Err(BinaryError::ChecksumMismatch { section, .. }) => {
+                    assert_eq!(section, "block 0")
+                }
+                other => panic!("expected a block checksum mismatch, got {:?}", other),
+            }
+        }
+        let mut reader = IndexedReader::new(std::io::Cursor::new(&corrupted)).unwrap();
+        assert!(reader.read_block(0).is_err());
 
-fn read_string<R: Read>(reader: &mut R) -> Result<String> {
-    let len = read_u32(reader)? as usize;
-    let mut buf = vec![0u8; len];
-    reader.read_exact(&mut buf)?;
-    String::from_utf8(buf).map_err(|e| BinaryError::DeserializationError(e.to_string()))
-}
+        // A corrupted column tag is caught by the checksum before the column
+        // is decoded, whichever way the block is read
+        let block_start = entry.offset as usize;
+        let stats_len = read_u32(&mut &bytes[block_start + 4..]).unwrap() as usize;
+        // row_count, stats, table tag, table default and column length
+        let column_tag = block_start + 8 + stats_len + 2 + 4;
+        assert_eq!(bytes[column_tag], 1);
+        let mut corrupted = bytes.clone();
+        corrupted[column_tag] = 0xEE;
+        let predicate = Predicate::parse("value >= 0").unwrap();
+        for result in [
+            BinaryFile::from_bytes(&corrupted).map(|_| ()),
+            BinaryReader::new(corrupted.as_slice())
+                .unwrap()
+                .with_predicate(predicate)
+                .try_for_each(|block| block.map(|_| ())),
+            IndexedReader::new(std::io::Cursor::new(&corrupted))
+                .unwrap()
+                .read_block(0)
+                .map(|_| ()),
+            Block::read_from(
+                &mut &corrupted[block_start..],
+                &CompressionConfig::default(),
+            )
+            .map(|_| ()),
+        ] {
+            assert!(
+                matches!(result, Err(BinaryError::ChecksumMismatch { .. })),
+                "expected a block checksum mismatch, got {:?}",
+                result
+            );
+        }
+    }
 
-/// Write a sized byte array (future: will use Snappy compression)
-///
-/// Format:
-/// - uncompressed_size: u32 (little-endian)
-/// - compressed_size: u32 (little-endian)
-/// - data: compressed_size bytes
-///
-/// Currently no compression is applied (compressed_size == uncompressed_size)
-fn write_sized_byte_array<W: Write>(writer: &mut W, data: &[u8]) -> Result<()> {
-    write_u32(writer, data.len() as u32)?; // uncompressed size
-    write_u32(writer, data.len() as u32)?; // compressed size (same for now)
-    writer.write_all(data)?;
-    Ok(())
-}
+    #[test]
+    fn test_header_compressed() {
+        use crate::logical::{FieldSchema, ValueSchema};
 
-/// Read a sized byte array (future: will decompress with Snappy)
-fn read_sized_byte_array<R: Read>(reader: &mut R) -> Result<Vec<u8>> {
-    let _uncompressed_size = read_u32(reader)?;
-    let compressed_size = read_u32(reader)? as usize;
-    let mut buf = vec![0u8; compressed_size];
-    reader.read_exact(&mut buf)?;
-    // For now, no decompression
-    Ok(buf)
-}
+        // A deeply nested schema, whose JSON repeats the same shapes
+        let mut element = ValueSchema::Int {
+            default: Default::Allow,
+            encoding: Encoding::Int(IntEncoding::Int),
+        };
+        for depth in 0..8 {
+            element = ValueSchema::Struct {
+                default: Default::Allow,
+                fields: vec![
+                    FieldSchema {
+                        name: format!("level_{}", depth),
+                        schema: element,
+                    },
+                    FieldSchema {
+                        name: "label".to_string(),
+                        schema: ValueSchema::Binary {
+                            default: Default::Allow,
+                            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+                        },
+                    },
+                ],
+            };
+        }
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(element),
+        };
+        let logical = crate::data::Table::Array(Vec::new());
+        let table = Table::from_logical(&schema, &logical).unwrap();
+        let file = BinaryFile::new(schema.clone(), table.clone());
+        let bytes = file.to_bytes().unwrap();
 
-/// Write integer array with full compression pipeline
-fn write_int_array_compressed<W: Write>(writer: &mut W, values: &[i64]) -> Result<()> {
-    write_u32(writer, values.len() as u32)?;
-    let compressed = compress_int_array(values)?;
-    write_u32(writer, compressed.len() as u32)?;
-    writer.write_all(&compressed)?;
-    Ok(())
-}
+        let schema_json = serde_json::to_vec(&schema).unwrap();
+        let header_len = u32::from_le_bytes(bytes[16..20].try_into().unwrap()) as usize;
+        assert_eq!(&bytes[..16], MAGIC_NUMBER_V2);
+        assert!(header_len < schema_json.len() / 2);
+        // The reserved bytes close the header, just before the block count
+        let header_end = 24 + header_len;
+        assert_eq!(
+            &bytes[header_end - HEADER_RESERVED_SIZE_V2..header_end],
+            &[0u8; HEADER_RESERVED_SIZE_V2]
+        );
 
-/// Read integer array with full decompression pipeline
-fn read_int_array_compressed<R: Read>(reader: &mut R) -> Result<Vec<i64>> {
-    let len = read_u32(reader)? as usize;
-    let compressed_size = read_u32(reader)? as usize;
-    let mut compressed = vec![0u8; compressed_size];
-    reader.read_exact(&mut compressed)?;
-    decompress_int_array(&compressed, len)
-}
+        let read_back = BinaryFile::from_bytes(&bytes).unwrap();
+        assert_eq!(read_back.header.schema, schema);
+        assert_eq!(read_back.blocks[0].table, table);
+    }
 
-fn write_int_array_usize_compressed<W: Write>(writer: &mut W, values: &[usize]) -> Result<()> {
-    let i64_values: Vec<i64> = values.iter().map(|&v| v as i64).collect();
-    write_int_array_compressed(writer, &i64_values)
-}
+    #[test]
+    fn test_version_1_files_readable() {
+        use crate::data::{Field, Value};
+        use crate::logical::{FieldSchema, ValueSchema, VariantSchema};
 
-fn read_int_array_usize_compressed<R: Read>(reader: &mut R) -> Result<Vec<usize>> {
-    let i64_values = read_int_array_compressed(reader)?;
-    Ok(i64_values.iter().map(|&v| v as usize).collect())
-}
+        // Written by the last release producing version 1 files
+        let bytes: &[u8] = include_bytes!("../tests/fixtures/v1-baseline.zbra");
 
-fn write_u32_array_compressed<W: Write>(writer: &mut W, values: &[u32]) -> Result<()> {
-    let i64_values: Vec<i64> = values.iter().map(|&v| v as i64).collect();
-    write_int_array_compressed(writer, &i64_values)
-}
+        let int = |encoding| ValueSchema::Int {
+            default: Default::Allow,
+            encoding: Encoding::Int(encoding),
+        };
+        let utf8 = ValueSchema::Binary {
+            default: Default::Allow,
+            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+        };
+        let field = |name: &str, schema| FieldSchema {
+            name: name.to_string(),
+            schema,
+        };
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(ValueSchema::Struct {
+                default: Default::Allow,
+                fields: vec![
+                    field("id", int(IntEncoding::Int)),
+                    field("name", utf8.clone()),
+                    field(
+                        "score",
+                        ValueSchema::Double {
+                            default: Default::Allow,
+                        },
+                    ),
+                    field("at", int(IntEncoding::TimeMilliseconds)),
+                    field(
+                        "tags",
+                        ValueSchema::Array {
+                            default: Default::Allow,
+                            element: Box::new(utf8),
+                        },
+                    ),
+                    field(
+                        "level",
+                        ValueSchema::Enum {
+                            default: Default::Allow,
+                            variants: vec![
+                                VariantSchema {
+                                    name: "info".to_string(),
+                                    tag: 0,
+                                    schema: ValueSchema::Unit,
+                                },
+                                VariantSchema {
+                                    name: "error".to_string(),
+                                    tag: 1,
+                                    schema: int(IntEncoding::Int),
+                                },
+                            ],
+                        },
+                    ),
+                ],
+            }),
+        };
 
-fn read_u32_array_compressed<R: Read>(reader: &mut R) -> Result<Vec<u32>> {
-    let i64_values = read_int_array_compressed(reader)?;
-    Ok(i64_values.iter().map(|&v| v as u32).collect())
-}
+        let row = |id: i64, name: &str, score: f64, tags: &[&str], level: Option<i64>| {
+            let level = match level {
+                None => Value::Enum {
+                    tag: 0,
+                    value: Box::new(Value::Unit),
+                },
+                Some(code) => Value::Enum {
+                    tag: 1,
+                    value: Box::new(Value::Int(code)),
+                },
+            };
+            let tags = tags
+                .iter()
+                .map(|tag| Value::Binary(tag.as_bytes().to_vec()))
+                .collect();
+            let fields = [
+                ("id", Value::Int(id)),
+                ("name", Value::Binary(name.as_bytes().to_vec())),
+                ("score", Value::Double(score)),
+                ("at", Value::Int(1_699_999_999_000 + id * 1000)),
+                ("tags", Value::Array(tags)),
+                ("level", level),
+            ];
+            Value::Struct(
+                fields
+                    .into_iter()
+                    .map(|(name, value)| Field {
+                        name: name.to_string(),
+                        value,
+                    })
+                    .collect(),
+            )
+        };
+        let blocks = [
+            vec![
+                row(1, "alpha", 0.5, &["a", "b"], None),
+                row(2, "beta", -1.25, &[], Some(404)),
+                row(3, "gamma", 3.0, &["c"], Some(500)),
+            ],
+            vec![
+                row(4, "delta", 0.0, &["d", "e", "f"], None),
+                row(5, "", 1e10, &[], None),
+            ],
+        ];
 
-/// Write a sized byte array with compression
-fn write_sized_byte_array_compressed<W: Write>(
-    writer: &mut W,
-    data: &[u8],
-    algorithm: &crate::compression::CompressionAlgorithm,
-) -> Result<()> {
-    let compressed = crate::compression::compress_binary(data, algorithm)?;
-    write_u32(writer, data.len() as u32)?; // uncompressed size
-    write_u32(writer, compressed.len() as u32)?; // compressed size
-    writer.write_all(&compressed)?;
-    Ok(())
-}
+        let file = BinaryFile::from_bytes(bytes).unwrap();
+        assert_eq!(file.header.schema, schema);
+        let read_back: Vec<_> = file
+            .blocks
+            .iter()
+            .map(|block| block.table.to_logical().unwrap())
+            .collect();
+        assert_eq!(
+            read_back,
+            blocks
+                .iter()
+                .map(|rows| crate::data::Table::Array(rows.clone()))
+                .collect::<Vec<_>>()
+        );
 
-/// Read a sized byte array with decompression
-fn read_sized_byte_array_compressed<R: Read>(
-    reader: &mut R,
-    algorithm: &crate::compression::CompressionAlgorithm,
-) -> Result<Vec<u8>> {
-    let _uncompressed_size = read_u32(reader)?;
-    let compressed_size = read_u32(reader)? as usize;
-    let mut compressed = vec![0u8; compressed_size];
-    reader.read_exact(&mut compressed)?;
-    crate::compression::decompress_binary(&compressed, algorithm)
-}
+        // Without statistics every block is decoded and then filtered
+        let predicate = Predicate::parse("id >= 3").unwrap();
+        let mut reader = BinaryReader::new(bytes).unwrap().with_predicate(predicate);
+        let matching: Vec<_> = reader
+            .by_ref()
+            .map(|block| block.unwrap().row_count)
+            .collect();
+        assert_eq!(matching, vec![1, 2]);
+        assert_eq!((reader.blocks_read(), reader.blocks_skipped()), (2, 0));
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::data::{BinaryEncoding, Default, Encoding, IntEncoding};
-    use crate::logical::TableSchema;
-    use crate::striped::{Column, Table};
+        // Columns are not length-prefixed, so none can be skipped
+        let projection = Projection::new(&[&["id"]]).unwrap();
+        let mut reader = BinaryReader::new(bytes)
+            .unwrap()
+            .with_projection(projection);
+        assert!(matches!(
+            reader.next(),
+            Some(Err(BinaryError::InvalidProjection(_)))
+        ));
+
+        // There is no block index either, but re-encoding adds one
+        assert!(matches!(
+            IndexedReader::new(std::io::Cursor::new(bytes)),
+            Err(BinaryError::MissingBlockIndex)
+        ));
+        let upgraded = BinaryReader::new(bytes)
+            .unwrap()
+            .copy_to_version(Vec::new(), FormatVersion::V2, BTreeMap::new())
+            .unwrap();
+        let mut indexed = IndexedReader::new(std::io::Cursor::new(upgraded)).unwrap();
+        assert_eq!(indexed.version(), FormatVersion::V2);
+        assert_eq!(indexed.header().schema, schema);
+        assert_eq!(indexed.row_count(), 5);
+        assert_eq!(indexed.read_block(1).unwrap().table, file.blocks[1].table);
+    }
 
     #[test]
-    fn test_binary_roundtrip_simple() {
-        // Create a simple integer array
+    fn test_format_versions() {
+        assert_eq!(detect_version(MAGIC_NUMBER_V1).unwrap(), 1);
+        assert_eq!(detect_version(MAGIC_NUMBER_V2).unwrap(), 2);
+        assert_eq!(detect_version(b"||_ZBRA||00042||").unwrap(), 42);
+        for bad in [
+            b"||_ZBRA||0004x||",
+            b"||_ZEBR||00001||",
+            b"||_ZBRA||00001|/",
+        ] {
+            assert!(matches!(
+                detect_version(bad),
+                Err(BinaryError::InvalidMagicNumber)
+            ));
+        }
+
         let schema = TableSchema::Array {
             default: Default::Allow,
             element: Box::new(crate::logical::ValueSchema::Int {
@@ -715,65 +4536,264 @@ mod tests {
                 encoding: Encoding::Int(IntEncoding::Int),
             }),
         };
-
         let table = Table::Array {
             default: Default::Allow,
             column: Box::new(Column::Int {
                 default: Default::Allow,
                 encoding: Encoding::Int(IntEncoding::Int),
-                values: vec![1, 2, 3, 4, 5],
+                values: vec![3, 1, 4, 1, 5],
             }),
         };
 
-        // Create binary file
-        let binary_file = BinaryFile::new(schema.clone(), table.clone());
+        for version in [FormatVersion::V2, FormatVersion::V3] {
+            let file = BinaryFile::new(schema.clone(), table.clone());
+            let bytes = file.to_bytes_version(version).unwrap();
+            assert_eq!(
+                detect_version(bytes[..16].try_into().unwrap()).unwrap(),
+                version.number()
+            );
 
-        // Serialize to bytes
-        let bytes = binary_file.to_bytes().expect("Failed to serialize");
+            let mut indexed = IndexedReader::new(std::io::Cursor::new(&bytes)).unwrap();
+            assert_eq!(indexed.version(), version);
+            assert_eq!(indexed.read_block(0).unwrap().table, table);
 
-        // Deserialize from bytes
-        let deserialized = BinaryFile::from_bytes(&bytes).expect("Failed to deserialize");
+            let mut writer = BinaryWriter::with_version(
+                Vec::new(),
+                schema.clone(),
+                CompressionConfig::default(),
+                version,
+            )
+            .unwrap();
+            writer.write_table(&table).unwrap();
+            let streamed = writer.finish().unwrap();
+            let reader = BinaryReader::new(streamed.as_slice()).unwrap();
+            assert_eq!(reader.version(), version);
+            let blocks = reader.collect::<Result<Vec<_>>>().unwrap();
+            assert_eq!(blocks[0].table, table);
+        }
 
-        // Check that we got back what we put in
-        assert_eq!(deserialized.header.schema, schema);
-        assert_eq!(deserialized.blocks.len(), 1);
-        assert_eq!(deserialized.blocks[0].table, table);
+        // Version 1 readers cannot open anything written here
+        let file = BinaryFile::new(schema.clone(), table.clone());
+        assert!(matches!(
+            file.to_bytes_version(FormatVersion::V1),
+            Err(BinaryError::SerializationError(_))
+        ));
+        assert!(matches!(
+            BinaryWriter::with_version(
+                Vec::new(),
+                schema.clone(),
+                CompressionConfig::default(),
+                FormatVersion::V1,
+            ),
+            Err(BinaryError::SerializationError(_))
+        ));
+
+        // Files from a newer library are recognised but not decoded
+        let mut future = BinaryFile::new(schema, table).to_bytes().unwrap();
+        future[..16].copy_from_slice(b"||_ZBRA||00004||");
+        assert!(matches!(
+            BinaryFile::from_bytes(&future),
+            Err(BinaryError::UnsupportedVersion(4))
+        ));
     }
 
     #[test]
-    fn test_binary_roundtrip_struct() {
-        use crate::logical::{FieldSchema, ValueSchema};
+    fn test_unified_header() {
+        use crate::compression::{ColumnCompressionConfig, IntPipeline};
+        use crate::logical::{FieldSchema, ValueSchema, VariantSchema};
 
-        // Create a struct array
+        let int = ValueSchema::Int {
+            default: Default::Deny,
+            encoding: Encoding::Int(IntEncoding::TimeMicroseconds),
+        };
         let schema = TableSchema::Array {
             default: Default::Allow,
             element: Box::new(ValueSchema::Struct {
                 default: Default::Allow,
                 fields: vec![
                     FieldSchema {
-                        name: "id".to_string(),
-                        schema: ValueSchema::Int {
+                        name: "at".to_string(),
+                        schema: int.clone(),
+                    },
+                    FieldSchema {
+                        name: "event".to_string(),
+                        schema: ValueSchema::Enum {
                             default: Default::Allow,
-                            encoding: Encoding::Int(IntEncoding::Int),
+                            variants: vec![
+                                VariantSchema {
+                                    name: "none".to_string(),
+                                    tag: 0,
+                                    schema: ValueSchema::Unit,
+                                },
+                                VariantSchema {
+                                    name: "score".to_string(),
+                                    tag: 7,
+                                    schema: ValueSchema::Reversed {
+                                        inner: Box::new(ValueSchema::Double {
+                                            default: Default::Allow,
+                                        }),
+                                    },
+                                },
+                            ],
                         },
                     },
                     FieldSchema {
-                        name: "name".to_string(),
-                        schema: ValueSchema::Binary {
-                            default: Default::Allow,
-                            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+                        name: "attributes".to_string(),
+                        schema: ValueSchema::Nested {
+                            table: Box::new(TableSchema::Map {
+                                default: Default::Allow,
+                                key: Box::new(ValueSchema::Binary {
+                                    default: Default::Allow,
+                                    encoding: Encoding::Binary(BinaryEncoding::Utf8),
+                                }),
+                                value: Box::new(ValueSchema::Array {
+                                    default: Default::Allow,
+                                    element: Box::new(int),
+                                }),
+                            }),
                         },
                     },
                 ],
             }),
         };
+        let compression = CompressionConfig {
+            binary_data: CompressionAlgorithm::Brotli { level: 9 },
+            strings: CompressionAlgorithm::Zstd { level: -5 },
+            columns: vec![ColumnCompressionConfig {
+                path: vec!["event".to_string(), "score".to_string()],
+                algorithm: CompressionAlgorithm::Lz4,
+                int_pipeline: IntPipeline::Xor,
+            }],
+            optimization_target: OptimizationTarget::MinimizeSpace,
+        };
+        let mut header = Header::new(schema.clone(), compression);
+        header
+            .metadata
+            .insert("source".to_string(), "sensor-7".to_string());
+        header.metadata.insert("rows".to_string(), String::new());
+
+        let mut encoded = Vec::new();
+        header
+            .write_version_to(&mut encoded, FormatVersion::V3)
+            .unwrap();
+        let decoded =
+            Header::read_version_from(&mut encoded.as_slice(), FormatVersion::V3).unwrap();
+        assert_eq!(decoded.schema, schema);
+        assert_eq!(
+            format!("{:?}", decoded.compression),
+            format!("{:?}", header.compression)
+        );
+        assert_eq!(decoded.metadata, header.metadata);
+
+        // Older versions have nowhere to keep the metadata
+        assert!(matches!(
+            header.write_version_to(&mut Vec::new(), FormatVersion::V2),
+            Err(BinaryError::SerializationError(_))
+        ));
+
+        // Reserved bytes must stay zero, even under a valid checksum
+        let last = encoded.len() - 1;
+        encoded[last] = 1;
+        let crc = crc32fast::hash(&encoded[8..]);
+        encoded[4..8].copy_from_slice(&crc.to_le_bytes());
+        assert!(matches!(
+            Header::read_version_from(&mut encoded.as_slice(), FormatVersion::V3),
+            Err(BinaryError::CorruptedData(_))
+        ));
+
+        // Deeply nested schemas are rejected instead of overflowing the stack
+        let mut nested = vec![1, 0];
+        nested.extend(std::iter::repeat(8).take(100_000));
+        nested.push(0);
+        assert!(matches!(
+            TableSchema::read_from(&mut nested.as_slice()),
+            Err(BinaryError::CorruptedData(_))
+        ));
+        let mut shallow = vec![1, 0];
+        shallow.extend(std::iter::repeat(8).take(MAX_SCHEMA_DEPTH - 1));
+        shallow.push(0);
+        assert!(TableSchema::read_from(&mut shallow.as_slice()).is_ok());
+    }
+
+    #[test]
+    fn test_upgrade_copies_blocks() {
+        let schema = TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(crate::logical::ValueSchema::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+            }),
+        };
+        let block = |values: Vec<i64>| Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+                values,
+            }),
+        };
+        let tables = [block(vec![1, 2, 3]), block(vec![4, 5])];
+
+        let mut writer = BinaryWriter::with_version(
+            Vec::new(),
+            schema.clone(),
+            CompressionConfig::default(),
+            FormatVersion::V2,
+        )
+        .unwrap();
+        for table in &tables {
+            writer.write_table(table).unwrap();
+        }
+        let v2 = writer.finish().unwrap();
+
+        let mut original = IndexedReader::new(std::io::Cursor::new(&v2)).unwrap();
+        let metadata = BTreeMap::from([("origin".to_string(), "v2".to_string())]);
+        let v3 = original
+            .copy_to_version(Vec::new(), FormatVersion::V3, metadata.clone())
+            .unwrap();
+
+        let mut upgraded = IndexedReader::new(std::io::Cursor::new(&v3)).unwrap();
+        assert_eq!(upgraded.version(), FormatVersion::V3);
+        assert_eq!(upgraded.header().schema, schema);
+        assert_eq!(upgraded.header().metadata, metadata);
+        assert!(upgraded.verify().is_ok());
+        for (i, table) in tables.iter().enumerate() {
+            assert_eq!(&upgraded.read_block(i).unwrap().table, table);
+            assert_eq!(
+                upgraded.read_block_bytes(i).unwrap(),
+                original.read_block_bytes(i).unwrap()
+            );
+        }
+    }
 
+    #[test]
+    fn test_projected_copy_records_projected_schema() {
+        use crate::logical::{FieldSchema, ValueSchema};
+
+        let int = || ValueSchema::Int {
+            default: Default::Allow,
+            encoding: Encoding::Int(IntEncoding::Int),
+        };
+        let struct_schema = |names: &[&str]| TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(ValueSchema::Struct {
+                default: Default::Allow,
+                fields: names
+                    .iter()
+                    .map(|name| FieldSchema {
+                        name: name.to_string(),
+                        schema: int(),
+                    })
+                    .collect(),
+            }),
+        };
+        let schema = struct_schema(&["id", "score"]);
         let table = Table::Array {
             default: Default::Allow,
             column: Box::new(Column::Struct {
                 default: Default::Allow,
                 fields: vec![
-                    crate::striped::FieldColumn {
+                    FieldColumn {
                         name: "id".to_string(),
                         column: Column::Int {
                             default: Default::Allow,
@@ -781,29 +4801,127 @@ mod tests {
                             values: vec![1, 2, 3],
                         },
                     },
-                    crate::striped::FieldColumn {
-                        name: "name".to_string(),
-                        column: Column::Binary {
+                    FieldColumn {
+                        name: "score".to_string(),
+                        column: Column::Int {
                             default: Default::Allow,
-                            encoding: Encoding::Binary(BinaryEncoding::Utf8),
-                            lengths: vec![5, 3, 7],
-                            data: b"AliceBobCharlie".to_vec(),
+                            encoding: Encoding::Int(IntEncoding::Int),
+                            values: vec![10, 20, 30],
                         },
                     },
                 ],
             }),
         };
+        let mut writer =
+            BinaryWriter::new(Vec::new(), schema.clone(), CompressionConfig::default()).unwrap();
+        writer.write_table(&table).unwrap();
+        let bytes = writer.finish().unwrap();
 
-        // Create binary file
-        let binary_file = BinaryFile::new(schema.clone(), table.clone());
+        let copy = BinaryReader::new(std::io::Cursor::new(&bytes))
+            .unwrap()
+            .with_projection(Projection::new(&[&["score"]]).unwrap())
+            .copy_to_version(Vec::new(), FormatVersion::V2, BTreeMap::new())
+            .unwrap();
+        let mut projected = IndexedReader::new(std::io::Cursor::new(copy)).unwrap();
+        assert_eq!(projected.header().schema, struct_schema(&["score"]));
+        let block = projected.read_block(0).unwrap();
+        assert_eq!(block.table.schema(), struct_schema(&["score"]));
+        assert_eq!(block.row_count, 3);
 
-        // Serialize and deserialize
-        let bytes = binary_file.to_bytes().expect("Failed to serialize");
-        let deserialized = BinaryFile::from_bytes(&bytes).expect("Failed to deserialize");
+        // Unknown fields are reported before anything is written
+        let result = BinaryReader::new(std::io::Cursor::new(&bytes))
+            .unwrap()
+            .with_projection(Projection::new(&[&["missing"]]).unwrap())
+            .copy_to_version(Vec::new(), FormatVersion::V2, BTreeMap::new());
+        assert!(matches!(result, Err(BinaryError::InvalidProjection(_))));
+    }
 
-        // Verify roundtrip
-        assert_eq!(deserialized.header.schema, schema);
-        assert_eq!(deserialized.blocks[0].table, table);
+    #[test]
+    fn test_read_with_schema() {
+        use crate::data::{Field, Value};
+        use crate::error::SchemaError;
+        use crate::logical::{FieldSchema, ValueSchema};
+
+        let int = |default| ValueSchema::Int {
+            default,
+            encoding: Encoding::Int(IntEncoding::Int),
+        };
+        let text = |default| ValueSchema::Binary {
+            default,
+            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+        };
+        let table_of = |fields: Vec<(&str, ValueSchema)>| TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(ValueSchema::Struct {
+                default: Default::Allow,
+                fields: fields
+                    .into_iter()
+                    .map(|(name, schema)| FieldSchema {
+                        name: name.to_string(),
+                        schema,
+                    })
+                    .collect(),
+            }),
+        };
+        let row = |fields: Vec<(&str, Value)>| {
+            Value::Struct(
+                fields
+                    .into_iter()
+                    .map(|(name, value)| Field {
+                        name: name.to_string(),
+                        value,
+                    })
+                    .collect(),
+            )
+        };
+
+        let writer_schema = table_of(vec![
+            ("id", int(Default::Deny)),
+            ("name", text(Default::Allow)),
+        ]);
+        let mut writer =
+            BinaryWriter::new(Vec::new(), writer_schema, CompressionConfig::default()).unwrap();
+        for id in [1, 2] {
+            writer
+                .write_rows(&LogicalTable::Array(vec![row(vec![
+                    ("id", Value::Int(id)),
+                    ("name", Value::Binary(b"zebra".to_vec())),
+                ])]))
+                .unwrap();
+        }
+        let bytes = writer.finish().unwrap();
+
+        // Added fields are defaulted, removed fields are dropped
+        let reader_schema = table_of(vec![
+            ("email", text(Default::Allow)),
+            ("id", int(Default::Deny)),
+        ]);
+        let resolved = read_with_schema(&mut bytes.as_slice(), &reader_schema).unwrap();
+        let expected = LogicalTable::Array(
+            [1, 2]
+                .into_iter()
+                .map(|id| {
+                    row(vec![
+                        ("email", Value::Binary(Vec::new())),
+                        ("id", Value::Int(id)),
+                    ])
+                })
+                .collect(),
+        );
+        assert_eq!(resolved, expected);
+        assert!(resolved.validate_schema(&reader_schema).is_ok());
+
+        // A required field the writer never had cannot be filled in
+        let strict_schema = table_of(vec![
+            ("id", int(Default::Deny)),
+            ("email", text(Default::Deny)),
+        ]);
+        assert!(matches!(
+            read_with_schema(&mut bytes.as_slice(), &strict_schema),
+            Err(ConversionError::Schema(
+                SchemaError::IncompatibleSchema { .. }
+            ))
+        ));
     }
 
     #[test]
@@ -832,6 +4950,8 @@ mod tests {
         let no_compression_config = CompressionConfig {
             binary_data: CompressionAlgorithm::None,
             strings: CompressionAlgorithm::None,
+            columns: Vec::new(),
+            optimization_target: OptimizationTarget::MinimizeSpace,
         };
 
         let binary_file_no_compression =
@@ -851,6 +4971,8 @@ mod tests {
         let zstd_compression_config = CompressionConfig {
             binary_data: CompressionAlgorithm::Zstd { level: 3 },
             strings: CompressionAlgorithm::Zstd { level: 3 },
+            columns: Vec::new(),
+            optimization_target: OptimizationTarget::MinimizeSpace,
         };
 
         let binary_file_zstd = BinaryFile::new_with_compression(
@@ -871,4 +4993,73 @@ mod tests {
         println!("No compression: {} bytes", bytes_no_compression.len());
         println!("Zstd compression: {} bytes", bytes_zstd.len());
     }
+
+    #[test]
+    fn test_out_of_range_lengths_and_tags_rejected() {
+        let encode = |values: &[i64]| {
+            let mut bytes = Vec::new();
+            write_int_array_compressed(&mut bytes, values, OptimizationTarget::default()).unwrap();
+            bytes
+        };
+
+        let lengths = encode(&[3, 0, 5]);
+        assert_eq!(
+            read_int_array_usize_compressed(&mut lengths.as_slice()).unwrap(),
+            vec![3, 0, 5]
+        );
+        assert!(matches!(
+            read_int_array_usize_compressed(&mut encode(&[3, -1]).as_slice()),
+            Err(BinaryError::CorruptedData(_))
+        ));
+
+        let tags = encode(&[0, u32::MAX as i64]);
+        assert_eq!(
+            read_u32_array_compressed(&mut tags.as_slice()).unwrap(),
+            vec![0, u32::MAX]
+        );
+        for out_of_range in [-1, u32::MAX as i64 + 1] {
+            assert!(matches!(
+                read_u32_array_compressed(&mut encode(&[0, out_of_range]).as_slice()),
+                Err(BinaryError::CorruptedData(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_column_lengths_checked() {
+        let column = Column::Struct {
+            default: Default::Allow,
+            fields: vec![FieldColumn {
+                name: "id".to_string(),
+                column: Column::Int {
+                    default: Default::Allow,
+                    encoding: Encoding::Int(IntEncoding::Int),
+                    values: vec![1, 2, 3],
+                },
+            }],
+        };
+        let compression = CompressionConfig::default();
+        let mut bytes = Vec::new();
+        column.write_to(&mut bytes, &compression).unwrap();
+        let length = read_u32(&mut &bytes[..]).unwrap();
+        assert_eq!(length as usize, bytes.len() - 4);
+        assert_eq!(
+            Column::read_from(&mut &bytes[..], &compression).unwrap(),
+            column
+        );
+
+        // A prefix claiming more bytes than the column decodes from
+        let mut padded = bytes.clone();
+        padded[..4].copy_from_slice(&(length + 1).to_le_bytes());
+        padded.push(0);
+        match Column::read_from(&mut &padded[..], &compression) {
+            Err(BinaryError::CorruptedData(message)) => assert!(message.contains("trailing")),
+            other => panic!("expected trailing bytes, got {:?}", other),
+        }
+
+        // A prefix cutting the column short stops decoding at the prefix
+        let mut short = bytes.clone();
+        short[..4].copy_from_slice(&(length - 1).to_le_bytes());
+        assert!(Column::read_from(&mut &short[..], &compression).is_err());
+    }
 }
@@ -1,13 +1,48 @@
 // Compression algorithms for zbra binary format
 //
 // This module implements the core compression pipeline:
-// 1. Frame-of-reference encoding (integers)
+// 1. Frame-of-reference, delta or delta-of-delta encoding (integers)
 // 2. Zig-zag encoding (signed to unsigned)
 // 3. BP64 bit-packing (64-element chunks)
-// 4. Zstd compression (binary data)
+// 4. Zstd, LZ4, Snappy or Brotli compression (binary data)
+//
+// Int arrays with long runs of equal values are run-length encoded instead,
+// with the run values and lengths each going through steps 1-3. Double bit
+// patterns may instead be XOR encoded with Gorilla-style bit packing.
+//
+// Low-cardinality binary columns are dictionary encoded before step 4, with
+// their indices going through steps 1-3.
+//
+// The writer picks encodings per column and block by trying the candidates on
+// a sample of the values. Every choice is tagged in the block, so readers need
+// no configuration.
 
+use crate::binary::total_length;
 use crate::error::{BinaryError, Result};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::ops::RangeInclusive;
+
+/// Brotli window size as log2 bytes, the format's default
+const BROTLI_WINDOW: u32 = 22;
+
+/// Quality levels accepted by the Brotli encoder
+pub const BROTLI_LEVELS: RangeInclusive<u32> = 0..=11;
+
+/// Largest ratio between Lz4 output and input, reached by long runs
+const LZ4_MAX_EXPANSION: usize = 255;
+
+/// Binary columns are only considered for dictionary encoding when every
+/// distinct value appears at least this many times on average
+const DICTIONARY_MIN_REPEATS: usize = 2;
+
+/// Number of evenly spaced windows sampled when choosing an encoding
+const SAMPLE_WINDOWS: usize = 4;
+
+/// Values per sample window, large enough to show runs and regular steps
+const SAMPLE_WINDOW_SIZE: usize = 256;
 
 /// Compression algorithms supported by zbra
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -16,10 +51,12 @@ pub enum CompressionAlgorithm {
     None,
     /// Zstd compression with configurable level (1-22)
     Zstd { level: i32 },
-    // FUTURE: Additional compression algorithms
-    // Lz4,
-    // Snappy,
-    // Brotli { level: u32 },
+    /// LZ4 block compression, for hot latency-sensitive reads
+    Lz4,
+    /// Snappy raw compression, as used by the original Zebra format
+    Snappy,
+    /// Brotli compression with configurable level (0-11), for cold storage
+    Brotli { level: u32 },
 }
 
 /// Configuration for compression settings
@@ -29,6 +66,13 @@ pub struct CompressionConfig {
     pub binary_data: CompressionAlgorithm,
     /// Compression for string data
     pub strings: CompressionAlgorithm,
+    /// Per-column overrides, matched by the longest field path prefix
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<ColumnCompressionConfig>,
+    /// Size versus decode speed trade-off when the writer picks encodings.
+    /// Only used when writing, so it is not stored in the file.
+    #[serde(skip)]
+    pub optimization_target: OptimizationTarget,
 }
 
 impl Default for CompressionConfig {
@@ -36,6 +80,233 @@ impl Default for CompressionConfig {
         Self {
             binary_data: CompressionAlgorithm::Zstd { level: 3 },
             strings: CompressionAlgorithm::Zstd { level: 3 },
+            columns: Vec::new(),
+            optimization_target: OptimizationTarget::default(),
+        }
+    }
+}
+
+/// Trade-off between size and decode speed when choosing encodings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum OptimizationTarget {
+    /// Always keep the smallest encoding
+    #[default]
+    MinimizeSpace,
+    /// Keep the fastest encoding within 10% of the smallest
+    Balanced,
+    /// Keep the fastest encoding within twice the size of the smallest
+    MaximizeSpeed,
+}
+
+impl OptimizationTarget {
+    /// How much larger than the smallest candidate a faster one may be
+    fn size_slack(self) -> f64 {
+        match self {
+            OptimizationTarget::MinimizeSpace => 1.0,
+            OptimizationTarget::Balanced => 1.1,
+            OptimizationTarget::MaximizeSpeed => 2.0,
+        }
+    }
+
+    /// Pick among candidates given as (choice, encoded size, decode cost)
+    fn pick<T: Copy>(self, candidates: &[(T, usize, u8)]) -> Option<T> {
+        let smallest = candidates.iter().map(|&(_, size, _)| size).min()?;
+        let limit = smallest as f64 * self.size_slack();
+        candidates
+            .iter()
+            .filter(|&&(_, size, _)| size as f64 <= limit)
+            .min_by_key(|&&(_, size, cost)| (cost, size))
+            .map(|&(choice, _, _)| choice)
+    }
+}
+
+/// Compression override for the column at a field path and everything below it
+///
+/// Paths descend through struct fields by name, and pass through array
+/// elements, enum variants, nested tables, reversed columns and map columns.
+/// An empty path applies to every column in the file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnCompressionConfig {
+    pub path: Vec<String>,
+    /// Compression for the column's binary data, or for the encoded values of
+    /// int and double columns
+    pub algorithm: CompressionAlgorithm,
+    /// Integer pipeline for int and double values
+    #[serde(default)]
+    pub int_pipeline: IntPipeline,
+}
+
+/// Encoding applied to int and double values before byte compression
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum IntPipeline {
+    /// Whichever of frame-of-reference, run-length and raw encoding suits
+    /// each block, also trying delta and delta-of-delta encoding for
+    /// time-encoded columns and XOR encoding for doubles
+    #[default]
+    BitPacked,
+    /// Delta encoding, zig-zag and BP64 bit-packing
+    Delta,
+    /// Delta-of-delta encoding, zig-zag and BP64 bit-packing
+    DeltaOfDelta,
+    /// Run-length encoding, with bit-packed run values and lengths
+    RunLength,
+    /// XOR of consecutive values with Gorilla-style bit packing
+    Xor,
+    /// Little-endian 8-byte values, for data that does not pack
+    Plain,
+}
+
+/// Encoding applied to integers before zig-zag and BP64 bit-packing, tagged
+/// in the compressed int array header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntArrayEncoding {
+    /// Offsets from the median
+    FrameOfReference,
+    /// Differences between consecutive values, for monotonic series
+    Delta,
+    /// Differences between consecutive deltas, for regularly spaced series
+    DeltaOfDelta,
+    /// Runs of equal values, for enum tags and sorted columns
+    RunLength,
+    /// Little-endian 8-byte values without bit-packing, for data that does
+    /// not pack
+    Raw,
+    /// XOR of consecutive values with Gorilla-style bit packing, for double
+    /// bit patterns whose neighbours share sign, exponent and high mantissa
+    /// bits
+    Xor,
+}
+
+/// Encodings the writer tries for every int array
+pub const DEFAULT_INT_ENCODINGS: &[IntArrayEncoding] = &[
+    IntArrayEncoding::FrameOfReference,
+    IntArrayEncoding::RunLength,
+    IntArrayEncoding::Raw,
+];
+
+/// Encodings the writer tries for time-encoded int columns
+pub const TIME_INT_ENCODINGS: &[IntArrayEncoding] = &[
+    IntArrayEncoding::FrameOfReference,
+    IntArrayEncoding::Delta,
+    IntArrayEncoding::DeltaOfDelta,
+    IntArrayEncoding::RunLength,
+    IntArrayEncoding::Raw,
+];
+
+/// Encodings the writer tries for the bit patterns of double columns
+pub const DOUBLE_ENCODINGS: &[IntArrayEncoding] = &[
+    IntArrayEncoding::FrameOfReference,
+    IntArrayEncoding::Xor,
+    IntArrayEncoding::RunLength,
+    IntArrayEncoding::Raw,
+];
+
+/// A run of equal values in an int array
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntRun {
+    pub value: i64,
+    pub length: usize,
+}
+
+impl IntArrayEncoding {
+    fn tag(self) -> u8 {
+        match self {
+            IntArrayEncoding::FrameOfReference => 0,
+            IntArrayEncoding::Delta => 1,
+            IntArrayEncoding::DeltaOfDelta => 2,
+            IntArrayEncoding::RunLength => 3,
+            IntArrayEncoding::Raw => 4,
+            IntArrayEncoding::Xor => 5,
+        }
+    }
+
+    /// Relative decode cost, lowest first
+    fn decode_cost(self) -> u8 {
+        match self {
+            IntArrayEncoding::Raw => 0,
+            IntArrayEncoding::RunLength => 1,
+            IntArrayEncoding::FrameOfReference => 2,
+            IntArrayEncoding::Delta => 3,
+            IntArrayEncoding::DeltaOfDelta => 4,
+            IntArrayEncoding::Xor => 5,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Result<Self> {
+        match tag {
+            0 => Ok(IntArrayEncoding::FrameOfReference),
+            1 => Ok(IntArrayEncoding::Delta),
+            2 => Ok(IntArrayEncoding::DeltaOfDelta),
+            3 => Ok(IntArrayEncoding::RunLength),
+            4 => Ok(IntArrayEncoding::Raw),
+            5 => Ok(IntArrayEncoding::Xor),
+            _ => Err(BinaryError::DecompressionError(format!(
+                "Unknown int array encoding tag: {}",
+                tag
+            ))),
+        }
+    }
+}
+
+impl CompressionConfig {
+    /// Settings for the struct field `name`, with override paths made
+    /// relative to it
+    pub fn for_field(&self, name: &str) -> Cow<'_, CompressionConfig> {
+        if self.columns.is_empty() {
+            return Cow::Borrowed(self);
+        }
+
+        let exact = self
+            .columns
+            .iter()
+            .find(|column| column.path.len() == 1 && column.path[0] == name);
+        let columns = self
+            .columns
+            .iter()
+            .filter_map(|column| match column.path.split_first() {
+                // Overrides of an enclosing column keep applying unless the
+                // field has its own
+                None if exact.is_none() => Some(column.clone()),
+                None => None,
+                Some((first, rest)) if first == name => Some(ColumnCompressionConfig {
+                    path: rest.to_vec(),
+                    ..column.clone()
+                }),
+                Some(_) => None,
+            })
+            .collect();
+
+        Cow::Owned(CompressionConfig {
+            binary_data: self.binary_data.clone(),
+            strings: self.strings.clone(),
+            columns,
+            optimization_target: self.optimization_target,
+        })
+    }
+
+    /// Override applying to the current column, if any
+    pub fn column_override(&self) -> Option<&ColumnCompressionConfig> {
+        self.columns.iter().find(|column| column.path.is_empty())
+    }
+
+    /// Algorithm for the binary data of the current column
+    pub fn strings_algorithm(&self) -> &CompressionAlgorithm {
+        self.column_override()
+            .map_or(&self.strings, |column| &column.algorithm)
+    }
+
+    /// Algorithm for the data of the current binary table
+    pub fn binary_data_algorithm(&self) -> &CompressionAlgorithm {
+        self.column_override()
+            .map_or(&self.binary_data, |column| &column.algorithm)
+    }
+
+    /// Pipeline and algorithm for the values of the current int or double
+    /// column
+    pub fn int_codec(&self) -> (IntPipeline, &CompressionAlgorithm) {
+        match self.column_override() {
+            Some(column) => (column.int_pipeline, &column.algorithm),
+            None => (IntPipeline::BitPacked, &CompressionAlgorithm::None),
         }
     }
 }
@@ -81,6 +352,230 @@ pub fn frame_of_reference_decode(midpoint: i64, deltas: &[i64]) -> Vec<i64> {
         .collect()
 }
 
+/// Delta encoding: the first value becomes the reference and every value is
+/// replaced by its difference from the previous one
+///
+/// The first delta is always zero, keeping one residual per value.
+pub fn delta_encode(values: &[i64]) -> (i64, Vec<i64>) {
+    let reference = values.first().copied().unwrap_or(0);
+    (reference, differences(reference, values))
+}
+
+/// Delta decoding: running sum of the deltas from the reference
+pub fn delta_decode(reference: i64, deltas: &[i64]) -> Vec<i64> {
+    running_sum(reference, deltas)
+}
+
+/// Delta-of-delta encoding: the differences between consecutive deltas
+///
+/// Returns the first value, the first step between values and the residuals.
+/// Measuring the first delta against that step turns a regularly spaced
+/// series into all-zero residuals.
+pub fn delta_of_delta_encode(values: &[i64]) -> (i64, i64, Vec<i64>) {
+    let reference = values.first().copied().unwrap_or(0);
+    let step = match values {
+        [first, second, ..] => second.wrapping_sub(*first),
+        _ => 0,
+    };
+    let deltas = differences(reference.wrapping_sub(step), values);
+    (reference, step, differences(step, &deltas))
+}
+
+/// Delta-of-delta decoding
+pub fn delta_of_delta_decode(reference: i64, step: i64, residuals: &[i64]) -> Vec<i64> {
+    let deltas = running_sum(step, residuals);
+    running_sum(reference.wrapping_sub(step), &deltas)
+}
+
+fn differences(start: i64, values: &[i64]) -> Vec<i64> {
+    let mut previous = start;
+    values
+        .iter()
+        .map(|&value| {
+            let delta = value.wrapping_sub(previous);
+            previous = value;
+            delta
+        })
+        .collect()
+}
+
+fn running_sum(start: i64, deltas: &[i64]) -> Vec<i64> {
+    let mut current = start;
+    deltas
+        .iter()
+        .map(|&delta| {
+            current = current.wrapping_add(delta);
+            current
+        })
+        .collect()
+}
+
+/// Run-length encoding: collapse consecutive equal values into runs
+pub fn run_length_encode(values: &[i64]) -> Vec<IntRun> {
+    let mut runs: Vec<IntRun> = Vec::new();
+    for &value in values {
+        match runs.last_mut() {
+            Some(run) if run.value == value => run.length += 1,
+            _ => runs.push(IntRun { value, length: 1 }),
+        }
+    }
+    runs
+}
+
+/// Run-length decoding: expand runs back into values
+pub fn run_length_decode(runs: &[IntRun]) -> Vec<i64> {
+    runs.iter()
+        .flat_map(|run| std::iter::repeat(run.value).take(run.length))
+        .collect()
+}
+
+/// XOR encoding with Gorilla-style bit packing
+///
+/// The first value is stored in full. Every later value is XORed with the
+/// previous one: an unchanged value costs a single `0` bit, and otherwise
+/// only the meaningful bits of the XOR are stored, reusing the previous
+/// leading/trailing zero window when it still fits:
+/// - `10` + bits inside the previous window
+/// - `11` + 5-bit leading zero count + 6-bit meaningful bit count - 1 + bits
+pub fn xor_encode(values: &[i64]) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+    let Some((&first, rest)) = values.split_first() else {
+        return Vec::new();
+    };
+    writer.write(first as u64, 64);
+
+    let mut previous = first as u64;
+    let mut window: Option<(u32, u32)> = None;
+    for &value in rest {
+        let xor = previous ^ value as u64;
+        previous = value as u64;
+        if xor == 0 {
+            writer.write(0, 1);
+            continue;
+        }
+
+        let leading = xor.leading_zeros().min(31);
+        let trailing = xor.trailing_zeros();
+        match window {
+            Some((window_leading, window_trailing))
+                if leading >= window_leading && trailing >= window_trailing =>
+            {
+                writer.write(0b10, 2);
+                writer.write(
+                    xor >> window_trailing,
+                    64 - window_leading - window_trailing,
+                );
+            }
+            _ => {
+                let meaningful = 64 - leading - trailing;
+                writer.write(0b11, 2);
+                writer.write(leading as u64, 5);
+                writer.write((meaningful - 1) as u64, 6);
+                writer.write(xor >> trailing, meaningful);
+                window = Some((leading, trailing));
+            }
+        }
+    }
+    writer.bytes
+}
+
+/// Decode `count` values written by `xor_encode`
+pub fn xor_decode(data: &[u8], count: usize) -> Result<Vec<i64>> {
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    // The first value takes 64 bits and every later one at least one
+    let fits = data
+        .len()
+        .checked_mul(8)
+        .is_some_and(|bits| bits >= 64 && count - 1 <= bits - 64);
+    if !fits {
+        return Err(BinaryError::DecompressionError(format!(
+            "{} XOR encoded values do not fit in {} bytes",
+            count,
+            data.len()
+        )));
+    }
+    let mut values = Vec::with_capacity(count);
+    let mut reader = BitReader { data, position: 0 };
+    let mut previous = reader.read(64)?;
+    values.push(previous as i64);
+
+    let mut window = (0, 0);
+    while values.len() < count {
+        if reader.read(1)? == 1 {
+            if reader.read(1)? == 1 {
+                let leading = reader.read(5)? as u32;
+                let meaningful = reader.read(6)? as u32 + 1;
+                if leading + meaningful > 64 {
+                    return Err(BinaryError::DecompressionError(format!(
+                        "XOR window of {} leading and {} meaningful bits exceeds 64",
+                        leading, meaningful
+                    )));
+                }
+                window = (leading, 64 - leading - meaningful);
+            }
+            let (leading, trailing) = window;
+            previous ^= reader.read(64 - leading - trailing)? << trailing;
+        }
+        values.push(previous as i64);
+    }
+    Ok(values)
+}
+
+/// Writes bit fields most significant bit first
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    /// Bits used in the last byte, 8 when it is full
+    used: u32,
+}
+
+impl BitWriter {
+    /// Append the low `count` bits of `value`
+    fn write(&mut self, value: u64, count: u32) {
+        let mut remaining = count;
+        while remaining > 0 {
+            if self.bytes.is_empty() || self.used == 8 {
+                self.bytes.push(0);
+                self.used = 0;
+            }
+            let free = 8 - self.used;
+            let take = free.min(remaining);
+            let chunk = (value >> (remaining - take)) & ((1 << take) - 1);
+            *self.bytes.last_mut().unwrap() |= (chunk as u8) << (free - take);
+            self.used += take;
+            remaining -= take;
+        }
+    }
+}
+
+/// Reads bit fields written by `BitWriter`
+struct BitReader<'a> {
+    data: &'a [u8],
+    /// Position in bits
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, count: u32) -> Result<u64> {
+        let mut value = 0u64;
+        let mut remaining = count;
+        while remaining > 0 {
+            let byte = *self.data.get(self.position / 8).ok_or_else(|| {
+                BinaryError::DecompressionError("XOR bit stream truncated".to_string())
+            })?;
+            let offset = (self.position % 8) as u32;
+            let take = (8 - offset).min(remaining);
+            let chunk = (byte >> (8 - offset - take)) & ((1u16 << take) - 1) as u8;
+            value = (value << take) | chunk as u64;
+            self.position += take as usize;
+            remaining -= take;
+        }
+        Ok(value)
+    }
+}
+
 /// Zig-zag encoding converts signed integers to unsigned
 ///
 /// This brings small negative numbers closer to zero, improving compression.
@@ -189,6 +684,23 @@ pub fn bp64_unpack(packed: &[u8], count: usize) -> Result<Vec<u64>> {
     }
 
     let data = &packed[1..];
+    // Values of 32 bits or more are stored as 8 bytes each
+    let bits_per_value = if bit_width >= 32 {
+        64
+    } else {
+        bit_width as usize
+    };
+    let fits = count
+        .checked_mul(bits_per_value)
+        .is_some_and(|bits| bits <= data.len().saturating_mul(8));
+    if !fits {
+        return Err(BinaryError::DecompressionError(format!(
+            "{} values of {} bits do not fit in {} packed bytes",
+            count,
+            bit_width,
+            data.len()
+        )));
+    }
     let mut values = Vec::with_capacity(count);
 
     // For very large bit widths, read as little-endian 8-byte values
@@ -232,103 +744,634 @@ pub fn bp64_unpack(packed: &[u8], count: usize) -> Result<Vec<u64>> {
     Ok(values)
 }
 
+/// Levels accepted by the Zstd encoder, including the fast negative levels
+pub fn zstd_levels() -> RangeInclusive<i32> {
+    zstd::compression_level_range()
+}
+
 /// Compress binary data using the specified algorithm
 pub fn compress_binary(data: &[u8], algorithm: &CompressionAlgorithm) -> Result<Vec<u8>> {
     match algorithm {
         CompressionAlgorithm::None => Ok(data.to_vec()),
         CompressionAlgorithm::Zstd { level } => zstd::bulk::compress(data, *level)
-            .map_err(|e| BinaryError::CompressionError(format!("Zstd compression failed: {}", e))), // FUTURE: Add other compression algorithms
+            .map_err(|e| BinaryError::CompressionError(format!("Zstd compression failed: {}", e))),
+        CompressionAlgorithm::Lz4 => Ok(lz4_flex::compress_prepend_size(data)),
+        CompressionAlgorithm::Snappy => snap::raw::Encoder::new().compress_vec(data).map_err(|e| {
+            BinaryError::CompressionError(format!("Snappy compression failed: {}", e))
+        }),
+        CompressionAlgorithm::Brotli { level } => {
+            let mut compressed = Vec::new();
+            {
+                let mut writer =
+                    brotli::CompressorWriter::new(&mut compressed, 4096, *level, BROTLI_WINDOW);
+                writer.write_all(data).map_err(|e| {
+                    BinaryError::CompressionError(format!("Brotli compression failed: {}", e))
+                })?;
+            }
+            Ok(compressed)
+        }
     }
 }
 
 /// Decompress binary data using the specified algorithm
 pub fn decompress_binary(data: &[u8], algorithm: &CompressionAlgorithm) -> Result<Vec<u8>> {
+    decompress_binary_bounded(data, algorithm, usize::MAX)
+}
+
+/// Decompress binary data that is known to decompress to at most `max_len`
+/// bytes
+///
+/// Sizes recorded inside the compressed data are checked against `max_len`
+/// before the output is allocated, so corrupt input cannot ask for an
+/// arbitrarily large buffer.
+pub fn decompress_binary_bounded(
+    data: &[u8],
+    algorithm: &CompressionAlgorithm,
+    max_len: usize,
+) -> Result<Vec<u8>> {
+    let too_large = |size: u64| {
+        BinaryError::DecompressionError(format!(
+            "Decompressed size {} exceeds the expected {} bytes",
+            size, max_len
+        ))
+    };
     match algorithm {
-        CompressionAlgorithm::None => Ok(data.to_vec()),
+        CompressionAlgorithm::None => {
+            if data.len() > max_len {
+                return Err(too_large(data.len() as u64));
+            }
+            Ok(data.to_vec())
+        }
         CompressionAlgorithm::Zstd { .. } => {
+            if let Ok(Some(size)) = zstd::zstd_safe::get_frame_content_size(data) {
+                if size > max_len as u64 {
+                    return Err(too_large(size));
+                }
+            }
             // Try progressively larger buffer sizes until successful
-            let mut buffer_size = data.len() * 4;
+            let mut buffer_size = (data.len() * 4).min(max_len);
             loop {
                 match zstd::bulk::decompress(data, buffer_size) {
                     Ok(result) => return Ok(result),
                     Err(e) => {
-                        if buffer_size > data.len() * 100 {
+                        if buffer_size > data.len() * 100 || buffer_size >= max_len {
                             // Give up after trying 100x expansion
                             return Err(BinaryError::DecompressionError(format!(
                                 "Zstd decompression failed: {}",
                                 e
                             )));
                         }
-                        buffer_size *= 2;
+                        buffer_size = (buffer_size * 2).min(max_len);
                     }
                 }
             }
-        } // FUTURE: Add other compression algorithms
+        }
+        CompressionAlgorithm::Lz4 => {
+            let lz4_error =
+                |e| BinaryError::DecompressionError(format!("Lz4 decompression failed: {}", e));
+            // The size prefix is read from the file, so it is checked
+            // before it sizes the output buffer
+            let (size, compressed) = lz4_flex::block::uncompressed_size(data).map_err(lz4_error)?;
+            if size > max_len || size > compressed.len().saturating_mul(LZ4_MAX_EXPANSION) {
+                return Err(too_large(size as u64));
+            }
+            let mut decompressed = vec![0u8; size];
+            let written = lz4_flex::block::decompress_into(compressed, &mut decompressed)
+                .map_err(lz4_error)?;
+            if written != size {
+                return Err(BinaryError::DecompressionError(format!(
+                    "Lz4 data decompressed to {} bytes but records {}",
+                    written, size
+                )));
+            }
+            Ok(decompressed)
+        }
+        CompressionAlgorithm::Snappy => {
+            let snappy_error =
+                |e| BinaryError::DecompressionError(format!("Snappy decompression failed: {}", e));
+            let size = snap::raw::decompress_len(data).map_err(snappy_error)?;
+            if size > max_len {
+                return Err(too_large(size as u64));
+            }
+            snap::raw::Decoder::new()
+                .decompress_vec(data)
+                .map_err(snappy_error)
+        }
+        CompressionAlgorithm::Brotli { .. } => {
+            let mut decompressed = Vec::new();
+            brotli::Decompressor::new(data, 4096)
+                .take((max_len as u64).saturating_add(1))
+                .read_to_end(&mut decompressed)
+                .map_err(|e| {
+                    BinaryError::DecompressionError(format!("Brotli decompression failed: {}", e))
+                })?;
+            if decompressed.len() > max_len {
+                return Err(too_large(decompressed.len() as u64));
+            }
+            Ok(decompressed)
+        }
     }
 }
 
-/// Full integer compression pipeline
+/// Decompress binary data that records its uncompressed size alongside it
+///
+/// Fails unless the data decompresses to exactly `len` bytes.
+pub fn decompress_binary_exact(
+    data: &[u8],
+    algorithm: &CompressionAlgorithm,
+    len: usize,
+) -> Result<Vec<u8>> {
+    let decompressed = decompress_binary_bounded(data, algorithm, len)?;
+    if decompressed.len() != len {
+        return Err(BinaryError::DecompressionError(format!(
+            "Data decompressed to {} bytes but {} were recorded",
+            decompressed.len(),
+            len
+        )));
+    }
+    Ok(decompressed)
+}
+
+/// Upper bound on the encoded size of `count` integers in any pipeline,
+/// used to bound their decompression
+pub fn max_encoded_int_array_len(count: usize) -> usize {
+    count.saturating_mul(16).saturating_add(64)
+}
+
+/// Encode integers with the given pipeline
+///
+/// `candidates` are the encodings the default pipeline chooses between.
+pub fn encode_int_array(
+    values: &[i64],
+    pipeline: IntPipeline,
+    candidates: &[IntArrayEncoding],
+    target: OptimizationTarget,
+) -> Result<Vec<u8>> {
+    match pipeline {
+        IntPipeline::BitPacked => compress_int_array_adaptive(values, candidates, target),
+        IntPipeline::Delta => compress_int_array_with(values, IntArrayEncoding::Delta),
+        IntPipeline::DeltaOfDelta => {
+            compress_int_array_with(values, IntArrayEncoding::DeltaOfDelta)
+        }
+        IntPipeline::RunLength => compress_int_array_with(values, IntArrayEncoding::RunLength),
+        IntPipeline::Xor => compress_int_array_with(values, IntArrayEncoding::Xor),
+        IntPipeline::Plain => Ok(values.iter().flat_map(|v| v.to_le_bytes()).collect()),
+    }
+}
+
+/// Decode `count` integers encoded with the given pipeline
+pub fn decode_int_array(data: &[u8], count: usize, pipeline: IntPipeline) -> Result<Vec<i64>> {
+    match pipeline {
+        IntPipeline::BitPacked
+        | IntPipeline::Delta
+        | IntPipeline::DeltaOfDelta
+        | IntPipeline::RunLength
+        | IntPipeline::Xor => decompress_int_array(data, count),
+        IntPipeline::Plain => {
+            if count.checked_mul(8) != Some(data.len()) {
+                return Err(BinaryError::DecompressionError(format!(
+                    "Expected {} plain integers but found {} bytes",
+                    count,
+                    data.len()
+                )));
+            }
+            Ok(data
+                .chunks_exact(8)
+                .map(|chunk| i64::from_le_bytes(chunk.try_into().unwrap()))
+                .collect())
+        }
+    }
+}
+
+/// Full integer compression pipeline, using frame-of-reference encoding
 pub fn compress_int_array(values: &[i64]) -> Result<Vec<u8>> {
+    compress_int_array_with(values, IntArrayEncoding::FrameOfReference)
+}
+
+/// Full integer compression pipeline with the given encoding
+///
+/// Format: [encoding tag u8][reference i64][step i64, delta-of-delta
+/// only][packed length u32][BP64 packed zig-zag residuals]. Run-length
+/// encoded arrays are [encoding tag u8][run count u32][run values size
+/// u32][run values][run lengths], with the values and lengths compressed as
+/// frame-of-reference int arrays, raw arrays are [encoding tag u8][8-byte
+/// values] and XOR arrays are [encoding tag u8][Gorilla bit stream]. Empty
+/// arrays compress to no bytes.
+pub fn compress_int_array_with(values: &[i64], encoding: IntArrayEncoding) -> Result<Vec<u8>> {
     if values.is_empty() {
         return Ok(Vec::new());
     }
+    match encoding {
+        IntArrayEncoding::RunLength => return compress_int_runs(&run_length_encode(values)),
+        IntArrayEncoding::Raw => {
+            let mut result = Vec::with_capacity(1 + values.len() * 8);
+            result.push(encoding.tag());
+            result.extend(values.iter().flat_map(|v| v.to_le_bytes()));
+            return Ok(result);
+        }
+        IntArrayEncoding::Xor => {
+            let mut result = vec![encoding.tag()];
+            result.extend(xor_encode(values));
+            return Ok(result);
+        }
+        _ => {}
+    }
 
-    // Step 1: Frame-of-reference encoding
-    let (midpoint, deltas) = frame_of_reference_encode(values);
+    // Step 1: Frame-of-reference, delta or delta-of-delta encoding
+    let (reference, step, residuals) = match encoding {
+        IntArrayEncoding::FrameOfReference => {
+            let (midpoint, deltas) = frame_of_reference_encode(values);
+            (midpoint, None, deltas)
+        }
+        IntArrayEncoding::Delta => {
+            let (reference, deltas) = delta_encode(values);
+            (reference, None, deltas)
+        }
+        IntArrayEncoding::DeltaOfDelta => {
+            let (reference, step, residuals) = delta_of_delta_encode(values);
+            (reference, Some(step), residuals)
+        }
+        IntArrayEncoding::RunLength | IntArrayEncoding::Raw | IntArrayEncoding::Xor => {
+            unreachable!("run-length, raw and XOR arrays are compressed above")
+        }
+    };
 
     // Step 2: Zig-zag encoding
-    let unsigned_values = zig_zag_encode(&deltas);
+    let unsigned_values = zig_zag_encode(&residuals);
 
     // Step 3: BP64 bit-packing
     let packed = bp64_pack(&unsigned_values)?;
 
-    // Combine midpoint and packed data
+    // Combine encoding tag, reference, step and packed data
     let mut result = Vec::new();
-    result.extend_from_slice(&midpoint.to_le_bytes());
+    result.push(encoding.tag());
+    result.extend_from_slice(&reference.to_le_bytes());
+    if let Some(step) = step {
+        result.extend_from_slice(&step.to_le_bytes());
+    }
     result.extend_from_slice(&(packed.len() as u32).to_le_bytes());
     result.extend_from_slice(&packed);
 
     Ok(result)
 }
 
+fn compress_int_runs(runs: &[IntRun]) -> Result<Vec<u8>> {
+    let run_values: Vec<i64> = runs.iter().map(|run| run.value).collect();
+    let run_lengths: Vec<i64> = runs.iter().map(|run| run.length as i64).collect();
+    let packed_values = compress_int_array(&run_values)?;
+    let packed_lengths = compress_int_array(&run_lengths)?;
+
+    let mut result = Vec::new();
+    result.push(IntArrayEncoding::RunLength.tag());
+    result.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+    result.extend_from_slice(&(packed_values.len() as u32).to_le_bytes());
+    result.extend_from_slice(&packed_values);
+    result.extend_from_slice(&packed_lengths);
+    Ok(result)
+}
+
+/// Choose an encoding for `values` by compressing a sample with each
+/// candidate
+pub fn choose_int_encoding(
+    values: &[i64],
+    candidates: &[IntArrayEncoding],
+    target: OptimizationTarget,
+) -> Result<IntArrayEncoding> {
+    // Windows are compressed separately so the jumps between them do not
+    // count against delta encodings
+    let windows = sample_windows(values.len());
+    let mut sizes = Vec::with_capacity(candidates.len());
+    for &encoding in candidates {
+        let mut size = 0;
+        for window in &windows {
+            size += compress_int_array_with(&values[window.clone()], encoding)?.len();
+        }
+        sizes.push((encoding, size, encoding.decode_cost()));
+    }
+    Ok(target
+        .pick(&sizes)
+        .unwrap_or(IntArrayEncoding::FrameOfReference))
+}
+
+/// Compress with the candidate encoding chosen from a sample of the values
+pub fn compress_int_array_adaptive(
+    values: &[i64],
+    candidates: &[IntArrayEncoding],
+    target: OptimizationTarget,
+) -> Result<Vec<u8>> {
+    let encoding = choose_int_encoding(values, candidates, target)?;
+    compress_int_array_with(values, encoding)
+}
+
+/// Row ranges sampled when choosing an encoding: the whole column when it is
+/// small, otherwise evenly spaced windows
+fn sample_windows(len: usize) -> Vec<std::ops::Range<usize>> {
+    if len <= SAMPLE_WINDOWS * SAMPLE_WINDOW_SIZE {
+        return std::iter::once(0..len).collect();
+    }
+    let last_start = len - SAMPLE_WINDOW_SIZE;
+    (0..SAMPLE_WINDOWS)
+        .map(|window| {
+            let start = last_start * window / (SAMPLE_WINDOWS - 1);
+            start..start + SAMPLE_WINDOW_SIZE
+        })
+        .collect()
+}
+
 /// Full integer decompression pipeline
 pub fn decompress_int_array(data: &[u8], count: usize) -> Result<Vec<i64>> {
     if data.is_empty() {
         return Ok(Vec::new());
     }
 
-    if data.len() < 12 {
-        return Err(BinaryError::DecompressionError(
-            "Invalid compressed data length".to_string(),
-        ));
+    // Extract encoding
+    let encoding = IntArrayEncoding::from_tag(data[0])?;
+    match encoding {
+        IntArrayEncoding::RunLength => {
+            return Ok(run_length_decode(&decompress_int_runs(data, count)?));
+        }
+        IntArrayEncoding::Raw => {
+            if count.checked_mul(8) != Some(data.len() - 1) {
+                return Err(BinaryError::DecompressionError(format!(
+                    "Expected {} raw integers but found {} bytes",
+                    count,
+                    data.len() - 1
+                )));
+            }
+            return Ok(data[1..]
+                .chunks_exact(8)
+                .map(|chunk| i64::from_le_bytes(chunk.try_into().unwrap()))
+                .collect());
+        }
+        IntArrayEncoding::Xor => return xor_decode(&data[1..], count),
+        _ => {}
     }
+    decompress_packed(encoding, &data[1..], count)
+}
 
-    // Extract midpoint
-    let midpoint = i64::from_le_bytes(data[0..8].try_into().unwrap());
+/// Decompress an int array written before arrays carried an encoding tag, as
+/// in version 1 files
+///
+/// Format: [midpoint i64][packed length u32][BP64 packed zig-zag residuals],
+/// the frame-of-reference layout without its tag. Empty arrays compress to no
+/// bytes.
+pub fn decompress_int_array_untagged(data: &[u8], count: usize) -> Result<Vec<i64>> {
+    if data.is_empty() {
+        return Ok(Vec::new());
+    }
+    decompress_packed(IntArrayEncoding::FrameOfReference, data, count)
+}
 
-    // Extract packed data length
-    let packed_len = u32::from_le_bytes(data[8..12].try_into().unwrap()) as usize;
+/// Decompress the bit-packed residuals of a frame-of-reference, delta or
+/// delta-of-delta array, starting after its encoding tag
+fn decompress_packed(encoding: IntArrayEncoding, data: &[u8], count: usize) -> Result<Vec<i64>> {
+    // Extract reference and step
+    let mut offset = 0;
+    let mut next_word = |size: usize| -> Result<&[u8]> {
+        let word = data.get(offset..offset + size).ok_or_else(|| {
+            BinaryError::DecompressionError("Invalid compressed data length".to_string())
+        })?;
+        offset += size;
+        Ok(word)
+    };
+    let reference = i64::from_le_bytes(next_word(8)?.try_into().unwrap());
+    let step = match encoding {
+        IntArrayEncoding::DeltaOfDelta => i64::from_le_bytes(next_word(8)?.try_into().unwrap()),
+        _ => 0,
+    };
 
-    if data.len() < 12 + packed_len {
-        return Err(BinaryError::DecompressionError(
-            "Insufficient data for packed array".to_string(),
-        ));
-    }
+    // Extract packed data length
+    let packed_len = u32::from_le_bytes(next_word(4)?.try_into().unwrap()) as usize;
 
-    let packed = &data[12..12 + packed_len];
+    let packed = next_word(packed_len).map_err(|_| {
+        BinaryError::DecompressionError("Insufficient data for packed array".to_string())
+    })?;
 
     // Step 1: BP64 bit-unpacking
     let unsigned_values = bp64_unpack(packed, count)?;
 
     // Step 2: Zig-zag decoding
-    let deltas = zig_zag_decode(&unsigned_values);
+    let residuals = zig_zag_decode(&unsigned_values);
 
-    // Step 3: Frame-of-reference decoding
-    let values = frame_of_reference_decode(midpoint, &deltas);
+    // Step 3: Frame-of-reference, delta or delta-of-delta decoding
+    let values = match encoding {
+        IntArrayEncoding::FrameOfReference => frame_of_reference_decode(reference, &residuals),
+        IntArrayEncoding::Delta => delta_decode(reference, &residuals),
+        IntArrayEncoding::DeltaOfDelta => delta_of_delta_decode(reference, step, &residuals),
+        IntArrayEncoding::RunLength | IntArrayEncoding::Raw | IntArrayEncoding::Xor => {
+            unreachable!("run-length, raw and XOR arrays are decompressed above")
+        }
+    };
 
     Ok(values)
 }
 
+/// Decompress an int array of `count` values as runs of equal values
+///
+/// Run-length encoded arrays are returned without expanding them, so
+/// aggregates can work a run at a time.
+pub fn decompress_int_runs(data: &[u8], count: usize) -> Result<Vec<IntRun>> {
+    if data.first() != Some(&IntArrayEncoding::RunLength.tag()) {
+        return Ok(run_length_encode(&decompress_int_array(data, count)?));
+    }
+
+    let header = |range: std::ops::Range<usize>| {
+        data.get(range)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()) as usize)
+            .ok_or_else(|| {
+                BinaryError::DecompressionError("Invalid compressed data length".to_string())
+            })
+    };
+    let run_count = header(1..5)?;
+    if run_count > count {
+        return Err(BinaryError::DecompressionError(format!(
+            "{} runs cannot cover {} values",
+            run_count, count
+        )));
+    }
+    let values_len = header(5..9)?;
+    if data.len() < 9 + values_len {
+        return Err(BinaryError::DecompressionError(
+            "Insufficient data for run values".to_string(),
+        ));
+    }
+    let (packed_values, packed_lengths) = data[9..].split_at(values_len);
+
+    let run_values = decompress_int_array(packed_values, run_count)?;
+    let run_lengths = decompress_int_array(packed_lengths, run_count)?;
+    let mut runs = Vec::with_capacity(run_count);
+    let mut total = 0usize;
+    for (value, length) in run_values.into_iter().zip(run_lengths) {
+        let length = usize::try_from(length).map_err(|_| {
+            BinaryError::DecompressionError(format!("Invalid run length {}", length))
+        })?;
+        total = total
+            .checked_add(length)
+            .filter(|&total| total <= count)
+            .ok_or_else(|| {
+                BinaryError::DecompressionError(format!(
+                    "Runs cover more than the {} expected values",
+                    count
+                ))
+            })?;
+        runs.push(IntRun { value, length });
+    }
+    if total != count {
+        return Err(BinaryError::DecompressionError(format!(
+            "Runs cover {} values but {} were expected",
+            total, count
+        )));
+    }
+    Ok(runs)
+}
+
+/// Decode `count` integers encoded with the given pipeline as runs of equal
+/// values
+pub fn decode_int_runs(data: &[u8], count: usize, pipeline: IntPipeline) -> Result<Vec<IntRun>> {
+    match pipeline {
+        IntPipeline::Plain => Ok(run_length_encode(&decode_int_array(data, count, pipeline)?)),
+        _ => decompress_int_runs(data, count),
+    }
+}
+
+/// Dictionary encoding of a binary column
+///
+/// Every distinct value is stored once, in order of first appearance, and
+/// each row refers to its value by index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryDictionary {
+    pub lengths: Vec<usize>,
+    pub data: Vec<u8>,
+    pub indices: Vec<u32>,
+}
+
+/// Dictionary encode a binary column given as value lengths and
+/// concatenated data
+///
+/// Returns `None` when the column has too many distinct values for a
+/// dictionary to pay off, or when the lengths do not match the data.
+pub fn dictionary_encode(lengths: &[usize], data: &[u8]) -> Option<BinaryDictionary> {
+    build_dictionary(lengths, data, lengths.len() / DICTIONARY_MIN_REPEATS)
+}
+
+/// Dictionary encode a binary column when a sample shows it is worth it
+///
+/// Columns with too many distinct values are never dictionary encoded.
+/// Otherwise the plain and dictionary layouts of a sample of the rows are
+/// compressed with `algorithm` and compared.
+pub fn choose_dictionary(
+    lengths: &[usize],
+    data: &[u8],
+    algorithm: &CompressionAlgorithm,
+    target: OptimizationTarget,
+) -> Result<Option<BinaryDictionary>> {
+    let Some(dictionary) = dictionary_encode(lengths, data) else {
+        return Ok(None);
+    };
+    if dictionary.indices.is_empty() {
+        return Ok(None);
+    }
+
+    let mut offsets = Vec::with_capacity(lengths.len() + 1);
+    offsets.push(0);
+    for &length in lengths {
+        offsets.push(offsets[offsets.len() - 1] + length);
+    }
+    let mut sample_lengths = Vec::new();
+    let mut sample_data = Vec::new();
+    for window in sample_windows(lengths.len()) {
+        sample_lengths.extend_from_slice(&lengths[window.clone()]);
+        sample_data.extend_from_slice(&data[offsets[window.start]..offsets[window.end]]);
+    }
+    let Some(sample) = build_dictionary(&sample_lengths, &sample_data, usize::MAX) else {
+        return Ok(None);
+    };
+
+    let as_ints = |values: &[usize]| values.iter().map(|&v| v as i64).collect::<Vec<_>>();
+    let plain_size = compress_int_array(&as_ints(&sample_lengths))?.len()
+        + compress_binary(&sample_data, algorithm)?.len();
+    let indices: Vec<i64> = sample.indices.iter().map(|&i| i as i64).collect();
+    let dictionary_size = compress_int_array(&as_ints(&sample.lengths))?.len()
+        + compress_binary(&sample.data, algorithm)?.len()
+        + compress_int_array(&indices)?.len();
+
+    let use_dictionary = target
+        .pick(&[(false, plain_size, 0), (true, dictionary_size, 1)])
+        .unwrap_or(false);
+    Ok(use_dictionary.then_some(dictionary))
+}
+
+fn build_dictionary(
+    lengths: &[usize],
+    data: &[u8],
+    max_entries: usize,
+) -> Option<BinaryDictionary> {
+    let mut entries: HashMap<&[u8], u32> = HashMap::new();
+    let mut dictionary = BinaryDictionary {
+        lengths: Vec::new(),
+        data: Vec::new(),
+        indices: Vec::with_capacity(lengths.len()),
+    };
+
+    let mut offset = 0usize;
+    for &length in lengths {
+        let value = data.get(offset..offset.checked_add(length)?)?;
+        offset += length;
+        let index = match entries.get(value) {
+            Some(&index) => index,
+            None => {
+                if entries.len() >= max_entries {
+                    return None;
+                }
+                let index = entries.len() as u32;
+                entries.insert(value, index);
+                dictionary.lengths.push(length);
+                dictionary.data.extend_from_slice(value);
+                index
+            }
+        };
+        dictionary.indices.push(index);
+    }
+
+    if offset != data.len() {
+        return None;
+    }
+    Some(dictionary)
+}
+
+/// Expand a dictionary back into value lengths and concatenated data
+pub fn dictionary_decode(dictionary: &BinaryDictionary) -> Result<(Vec<usize>, Vec<u8>)> {
+    let total = total_length(&dictionary.lengths)?;
+    if total != dictionary.data.len() {
+        return Err(BinaryError::DecompressionError(format!(
+            "Dictionary lengths cover {} bytes but data has {}",
+            total,
+            dictionary.data.len()
+        )));
+    }
+    let mut offsets = Vec::with_capacity(dictionary.lengths.len());
+    let mut offset = 0usize;
+    for &length in &dictionary.lengths {
+        offsets.push(offset..offset + length);
+        offset += length;
+    }
+
+    let mut lengths = Vec::with_capacity(dictionary.indices.len());
+    let mut data = Vec::new();
+    for &index in &dictionary.indices {
+        let range = offsets.get(index as usize).ok_or_else(|| {
+            BinaryError::DecompressionError(format!(
+                "Dictionary index {} out of range for {} entries",
+                index,
+                offsets.len()
+            ))
+        })?;
+        lengths.push(range.len());
+        data.extend_from_slice(&dictionary.data[range.clone()]);
+    }
+    Ok((lengths, data))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -364,6 +1407,250 @@ mod tests {
         let compressed = compress_int_array(&values).unwrap();
         let decompressed = decompress_int_array(&compressed, values.len()).unwrap();
         assert_eq!(values, decompressed);
+
+        // Version 1 files store the same layout without the encoding tag
+        assert_eq!(compressed[0], IntArrayEncoding::FrameOfReference.tag());
+        let untagged = decompress_int_array_untagged(&compressed[1..], values.len()).unwrap();
+        assert_eq!(values, untagged);
+        assert!(decompress_int_array_untagged(&compressed[1..12], values.len()).is_err());
+        assert!(decompress_int_array_untagged(&[], 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_delta_of_delta_shrinks_regular_timestamps() {
+        // One reading per second, starting at 2024-01-01
+        let timestamps: Vec<i64> = (0..1024).map(|i| 1_704_067_200_000 + i * 1000).collect();
+
+        let frame_of_reference = compress_int_array(&timestamps).unwrap();
+        let delta = compress_int_array_with(&timestamps, IntArrayEncoding::Delta).unwrap();
+        let delta_of_delta =
+            compress_int_array_with(&timestamps, IntArrayEncoding::DeltaOfDelta).unwrap();
+        assert!(delta.len() < frame_of_reference.len());
+        assert!(delta_of_delta.len() < delta.len());
+
+        let auto = encode_int_array(
+            &timestamps,
+            IntPipeline::BitPacked,
+            TIME_INT_ENCODINGS,
+            OptimizationTarget::MinimizeSpace,
+        )
+        .unwrap();
+        assert_eq!(auto, delta_of_delta);
+        assert_eq!(
+            decode_int_array(&auto, timestamps.len(), IntPipeline::BitPacked).unwrap(),
+            timestamps
+        );
+    }
+
+    #[test]
+    fn test_run_length_picked_for_long_runs() {
+        let tags: Vec<i64> = [0, 2, 1].iter().flat_map(|&t| vec![t; 500]).collect();
+
+        let frame_of_reference = compress_int_array(&tags).unwrap();
+        let smallest = compress_int_array_adaptive(
+            &tags,
+            DEFAULT_INT_ENCODINGS,
+            OptimizationTarget::MinimizeSpace,
+        )
+        .unwrap();
+        assert_eq!(smallest[0], IntArrayEncoding::RunLength.tag());
+        assert!(smallest.len() * 5 < frame_of_reference.len());
+
+        assert_eq!(
+            decompress_int_runs(&smallest, tags.len()).unwrap(),
+            vec![
+                IntRun {
+                    value: 0,
+                    length: 500
+                },
+                IntRun {
+                    value: 2,
+                    length: 500
+                },
+                IntRun {
+                    value: 1,
+                    length: 500
+                },
+            ]
+        );
+        assert_eq!(decompress_int_array(&smallest, tags.len()).unwrap(), tags);
+        assert!(decompress_int_array(&smallest, tags.len() + 1).is_err());
+
+        // Negative run lengths and more runs than values are corrupt
+        let encode_runs = |lengths: &[i64]| {
+            let values = compress_int_array(&vec![7; lengths.len()]).unwrap();
+            let mut data = vec![IntArrayEncoding::RunLength.tag()];
+            data.extend_from_slice(&(lengths.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(values.len() as u32).to_le_bytes());
+            data.extend_from_slice(&values);
+            data.extend_from_slice(&compress_int_array(lengths).unwrap());
+            data
+        };
+        assert!(decompress_int_runs(&encode_runs(&[-1, 3]), 2).is_err());
+        assert!(decompress_int_runs(&encode_runs(&[i64::MAX, i64::MAX]), 2).is_err());
+        assert!(decompress_int_runs(&encode_runs(&[1, 1, 0]), 2).is_err());
+        assert_eq!(
+            decompress_int_runs(&encode_runs(&[1, 1]), 2).unwrap().len(),
+            2
+        );
+
+        // Without runs, frame-of-reference stays smaller
+        let distinct: Vec<i64> = (0..1500).collect();
+        let smallest = compress_int_array_adaptive(
+            &distinct,
+            DEFAULT_INT_ENCODINGS,
+            OptimizationTarget::MinimizeSpace,
+        )
+        .unwrap();
+        assert_eq!(smallest[0], IntArrayEncoding::FrameOfReference.tag());
+    }
+
+    #[test]
+    fn test_adaptive_encoding_follows_target() {
+        let sizes = [
+            (IntArrayEncoding::DeltaOfDelta, 100, 4),
+            (IntArrayEncoding::FrameOfReference, 105, 2),
+            (IntArrayEncoding::RunLength, 180, 1),
+            (IntArrayEncoding::Raw, 800, 0),
+        ];
+        assert_eq!(
+            OptimizationTarget::MinimizeSpace.pick(&sizes),
+            Some(IntArrayEncoding::DeltaOfDelta)
+        );
+        assert_eq!(
+            OptimizationTarget::Balanced.pick(&sizes),
+            Some(IntArrayEncoding::FrameOfReference)
+        );
+        assert_eq!(
+            OptimizationTarget::MaximizeSpeed.pick(&sizes),
+            Some(IntArrayEncoding::RunLength)
+        );
+
+        // Jittered timestamps pack best as delta-of-delta, sampled or not
+        let timestamps: Vec<i64> = (0..100_000)
+            .map(|i| 1_704_067_200_000 + i * 1000 + i % 3)
+            .collect();
+        assert_eq!(
+            choose_int_encoding(
+                &timestamps,
+                TIME_INT_ENCODINGS,
+                OptimizationTarget::MinimizeSpace
+            )
+            .unwrap(),
+            IntArrayEncoding::DeltaOfDelta
+        );
+
+        // Values spanning the whole range do not pack, so raw wins outright
+        let wide: Vec<i64> = (0..1000)
+            .map(|i| (i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) as i64)
+            .collect();
+        for target in [
+            OptimizationTarget::MinimizeSpace,
+            OptimizationTarget::Balanced,
+            OptimizationTarget::MaximizeSpeed,
+        ] {
+            let compressed =
+                compress_int_array_adaptive(&wide, DEFAULT_INT_ENCODINGS, target).unwrap();
+            assert_eq!(compressed[0], IntArrayEncoding::Raw.tag());
+            assert_eq!(decompress_int_array(&compressed, wide.len()).unwrap(), wide);
+        }
+    }
+
+    #[test]
+    fn test_choose_dictionary() {
+        let statuses = ["active", "suspended", "pending"];
+        let values: Vec<&str> = (0..3000).map(|i| statuses[i % 3]).collect();
+        let lengths: Vec<usize> = values.iter().map(|v| v.len()).collect();
+        let data = values.concat().into_bytes();
+
+        let dictionary = choose_dictionary(
+            &lengths,
+            &data,
+            &CompressionAlgorithm::None,
+            OptimizationTarget::MinimizeSpace,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(dictionary.lengths.len(), 3);
+
+        // Zstd squeezes the repeating pattern about as well as a dictionary,
+        // so a speed target keeps the plain layout
+        let plain = choose_dictionary(
+            &lengths,
+            &data,
+            &CompressionAlgorithm::Zstd { level: 3 },
+            OptimizationTarget::MaximizeSpeed,
+        )
+        .unwrap();
+        assert_eq!(plain, None);
+    }
+
+    #[test]
+    fn test_xor_shrinks_sensor_readings() {
+        // Temperature readings to one decimal place drifting around 21.5
+        let readings: Vec<f64> = (0..1024)
+            .map(|i| (215.0 + ((i * 7) % 11) as f64 - 5.0) / 10.0)
+            .collect();
+        let bits: Vec<i64> = readings.iter().map(|v| v.to_bits() as i64).collect();
+
+        // Frame-of-reference cannot pack these at all, XOR drops the shared
+        // sign, exponent and high mantissa bits
+        let frame_of_reference = compress_int_array(&bits).unwrap();
+        let xor = compress_int_array_with(&bits, IntArrayEncoding::Xor).unwrap();
+        assert!(frame_of_reference.len() > bits.len() * 8);
+        assert!(xor.len() * 6 < frame_of_reference.len() * 5);
+
+        let chosen =
+            compress_int_array_adaptive(&bits, DOUBLE_ENCODINGS, OptimizationTarget::MinimizeSpace)
+                .unwrap();
+        assert_eq!(chosen[0], IntArrayEncoding::Xor.tag());
+        assert_eq!(decompress_int_array(&chosen, bits.len()).unwrap(), bits);
+
+        // A truncated stream is an error rather than a panic
+        assert!(xor_decode(&xor[1..xor.len() / 2], bits.len()).is_err());
+
+        // Readings that hold steady cost about a bit each
+        let steady: Vec<i64> = (0..1024)
+            .map(|i| (20.0 + (i / 64) as f64 * 0.5).to_bits() as i64)
+            .collect();
+        let xor = compress_int_array_with(&steady, IntArrayEncoding::Xor).unwrap();
+        assert!(xor.len() < 200);
+    }
+
+    #[test]
+    fn test_dictionary_encoding() {
+        let lengths = vec![2, 2, 2, 2, 2, 2];
+        let data = b"usuknzususnz".to_vec();
+        let dictionary = dictionary_encode(&lengths, &data).unwrap();
+        assert_eq!(
+            dictionary,
+            BinaryDictionary {
+                lengths: vec![2, 2, 2],
+                data: b"usuknz".to_vec(),
+                indices: vec![0, 1, 2, 0, 0, 2],
+            }
+        );
+        assert_eq!(dictionary_decode(&dictionary).unwrap(), (lengths, data));
+
+        // Mostly distinct values fall back to plain storage
+        assert_eq!(dictionary_encode(&[1, 1, 1], b"abc"), None);
+        // Lengths that disagree with the data are never dictionary encoded
+        assert_eq!(dictionary_encode(&[1, 1, 1, 1], b"aaaaa"), None);
+
+        let corrupt = BinaryDictionary {
+            lengths: vec![1],
+            data: b"a".to_vec(),
+            indices: vec![0, 1],
+        };
+        assert!(dictionary_decode(&corrupt).is_err());
+
+        // Lengths that overflow are rejected rather than wrapping
+        let overflowing = BinaryDictionary {
+            lengths: vec![usize::MAX, 2],
+            data: b"a".to_vec(),
+            indices: vec![0],
+        };
+        assert!(dictionary_decode(&overflowing).is_err());
     }
 
     #[test]
@@ -375,6 +1662,133 @@ mod tests {
         assert_eq!(data.to_vec(), decompressed);
     }
 
+    #[test]
+    fn test_brotli_compression_roundtrip() {
+        let data = b"Hello, world! This is a test string for compression.";
+        let algorithm = CompressionAlgorithm::Brotli { level: 11 };
+        let compressed = compress_binary(data, &algorithm).unwrap();
+        let decompressed = decompress_binary(&compressed, &algorithm).unwrap();
+        assert_eq!(data.to_vec(), decompressed);
+    }
+
+    #[test]
+    fn test_lz4_snappy_compression_roundtrip() {
+        let data = b"Hello, world! This is a test string for compression.".repeat(10);
+        for algorithm in [CompressionAlgorithm::Lz4, CompressionAlgorithm::Snappy] {
+            let compressed = compress_binary(&data, &algorithm).unwrap();
+            assert!(compressed.len() < data.len());
+            let decompressed = decompress_binary(&compressed, &algorithm).unwrap();
+            assert_eq!(data, decompressed);
+        }
+        assert!(decompress_binary(b"not compressed", &CompressionAlgorithm::Snappy).is_err());
+    }
+
+    #[test]
+    fn test_decompression_bounded_by_expected_size() {
+        let data = vec![7u8; 1000];
+        for algorithm in [
+            CompressionAlgorithm::Zstd { level: 3 },
+            CompressionAlgorithm::Lz4,
+            CompressionAlgorithm::Snappy,
+            CompressionAlgorithm::Brotli { level: 5 },
+        ] {
+            let compressed = compress_binary(&data, &algorithm).unwrap();
+            assert_eq!(
+                decompress_binary_exact(&compressed, &algorithm, 1000).unwrap(),
+                data
+            );
+            assert!(decompress_binary_exact(&compressed, &algorithm, 999).is_err());
+            assert!(decompress_binary_exact(&compressed, &algorithm, 1001).is_err());
+        }
+
+        // A corrupt Lz4 size prefix is rejected before it sizes the buffer
+        let mut corrupted = compress_binary(&data, &CompressionAlgorithm::Lz4).unwrap();
+        corrupted[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(decompress_binary(&corrupted, &CompressionAlgorithm::Lz4).is_err());
+    }
+
+    #[test]
+    fn test_column_compression_resolution() {
+        let column = |path: &[&str], level: i32| ColumnCompressionConfig {
+            path: path.iter().map(|s| s.to_string()).collect(),
+            algorithm: CompressionAlgorithm::Zstd { level },
+            int_pipeline: IntPipeline::BitPacked,
+        };
+        let config = CompressionConfig {
+            columns: vec![
+                column(&["payload"], 19),
+                column(&["payload", "hot"], 1),
+                ColumnCompressionConfig {
+                    path: vec!["id".to_string()],
+                    algorithm: CompressionAlgorithm::None,
+                    int_pipeline: IntPipeline::Plain,
+                },
+            ],
+            ..CompressionConfig::default()
+        };
+
+        assert_eq!(config.strings_algorithm(), &config.strings);
+        let payload = config.for_field("payload");
+        assert_eq!(
+            payload.strings_algorithm(),
+            &CompressionAlgorithm::Zstd { level: 19 }
+        );
+        assert_eq!(
+            payload.for_field("cold").strings_algorithm(),
+            &CompressionAlgorithm::Zstd { level: 19 }
+        );
+        assert_eq!(
+            payload.for_field("hot").strings_algorithm(),
+            &CompressionAlgorithm::Zstd { level: 1 }
+        );
+        assert_eq!(
+            config.for_field("id").int_codec(),
+            (IntPipeline::Plain, &CompressionAlgorithm::None)
+        );
+        assert_eq!(
+            config.for_field("other").int_codec(),
+            (IntPipeline::BitPacked, &CompressionAlgorithm::None)
+        );
+
+        // Headers written before per-column overrides still parse
+        let legacy = r#"{"binary_data":{"Zstd":{"level":3}},"strings":"None"}"#;
+        let parsed: CompressionConfig = serde_json::from_str(legacy).unwrap();
+        assert!(parsed.columns.is_empty());
+    }
+
+    #[test]
+    fn test_int_counts_bounded_by_payload() {
+        // Counts read from a file must fit the bytes that hold them, without
+        // overflowing on the way
+        let plain: Vec<u8> = [1i64, 2, 3].iter().flat_map(|v| v.to_le_bytes()).collect();
+        assert_eq!(
+            decode_int_array(&plain, 3, IntPipeline::Plain).unwrap(),
+            vec![1, 2, 3]
+        );
+        for count in [4, usize::MAX / 4] {
+            assert!(decode_int_array(&plain, count, IntPipeline::Plain).is_err());
+        }
+
+        let values: Vec<i64> = (0..100).map(|i| i * 1000).collect();
+        let delta = compress_int_array_with(&values, IntArrayEncoding::Delta).unwrap();
+        assert_eq!(decompress_int_array(&delta, 100).unwrap(), values);
+        for count in [1000, usize::MAX / 4] {
+            assert!(decompress_int_array(&delta, count).is_err());
+        }
+
+        let raw = compress_int_array_with(&values, IntArrayEncoding::Raw).unwrap();
+        assert_eq!(decompress_int_array(&raw, 100).unwrap(), values);
+        for count in [101, usize::MAX / 4] {
+            assert!(decompress_int_array(&raw, count).is_err());
+        }
+
+        let xor = compress_int_array_with(&values, IntArrayEncoding::Xor).unwrap();
+        assert_eq!(decompress_int_array(&xor, 100).unwrap(), values);
+        for count in [100_000, usize::MAX] {
+            assert!(decompress_int_array(&xor, count).is_err());
+        }
+    }
+
     proptest! {
         #[test]
         fn test_frame_of_reference_property(values in prop::collection::vec(any::<i64>(), 0..100)) {
@@ -403,5 +1817,89 @@ mod tests {
             let decompressed = decompress_int_array(&compressed, values.len()).unwrap();
             prop_assert_eq!(values, decompressed);
         }
+
+        #[test]
+        fn test_zstd_compression_property(data in prop::collection::vec(any::<u8>(), 0..1000), level in 1i32..10) {
+            let algorithm = CompressionAlgorithm::Zstd { level };
+            let compressed = compress_binary(&data, &algorithm).unwrap();
+            prop_assert_eq!(decompress_binary(&compressed, &algorithm).unwrap(), data);
+        }
+
+        #[test]
+        fn test_brotli_compression_property(data in prop::collection::vec(any::<u8>(), 0..1000), level in 0u32..12) {
+            let algorithm = CompressionAlgorithm::Brotli { level };
+            let compressed = compress_binary(&data, &algorithm).unwrap();
+            prop_assert_eq!(decompress_binary(&compressed, &algorithm).unwrap(), data);
+        }
+
+        #[test]
+        fn test_brotli_compresses_repetitive_property(chunk in prop::collection::vec(any::<u8>(), 1..32), repeats in 32usize..64) {
+            let data = chunk.repeat(repeats);
+            let algorithm = CompressionAlgorithm::Brotli { level: 11 };
+            let compressed = compress_binary(&data, &algorithm).unwrap();
+            prop_assert!(compressed.len() < data.len());
+            prop_assert_eq!(decompress_binary(&compressed, &algorithm).unwrap(), data);
+        }
+
+        #[test]
+        fn test_delta_property(values in prop::collection::vec(any::<i64>(), 0..100)) {
+            let (reference, deltas) = delta_encode(&values);
+            prop_assert_eq!(delta_decode(reference, &deltas), values.clone());
+            let (reference, step, residuals) = delta_of_delta_encode(&values);
+            prop_assert_eq!(delta_of_delta_decode(reference, step, &residuals), values);
+        }
+
+        #[test]
+        fn test_delta_compression_property(values in prop::collection::vec(any::<i64>(), 0..200)) {
+            for encoding in [IntArrayEncoding::FrameOfReference, IntArrayEncoding::Delta, IntArrayEncoding::DeltaOfDelta, IntArrayEncoding::RunLength, IntArrayEncoding::Raw, IntArrayEncoding::Xor] {
+                let compressed = compress_int_array_with(&values, encoding).unwrap();
+                prop_assert_eq!(decompress_int_array(&compressed, values.len()).unwrap(), values.clone());
+            }
+        }
+
+        #[test]
+        fn test_int_runs_property(values in prop::collection::vec(0i64..3, 0..300)) {
+            let runs = run_length_encode(&values);
+            prop_assert!(runs.windows(2).all(|pair| pair[0].value != pair[1].value));
+            prop_assert_eq!(run_length_decode(&runs), values.clone());
+
+            let compressed = compress_int_array_adaptive(&values, DEFAULT_INT_ENCODINGS, OptimizationTarget::MinimizeSpace).unwrap();
+            prop_assert_eq!(decompress_int_runs(&compressed, values.len()).unwrap(), runs);
+            prop_assert_eq!(decompress_int_array(&compressed, values.len()).unwrap(), values);
+        }
+
+        #[test]
+        fn test_xor_property(values in prop::collection::vec(any::<i64>(), 0..200), repeats in 1usize..4) {
+            // Repeat values so unchanged and reused-window cases come up
+            let values: Vec<i64> = values.iter().flat_map(|&v| std::iter::repeat(v).take(repeats)).collect();
+            prop_assert_eq!(xor_decode(&xor_encode(&values), values.len()).unwrap(), values);
+        }
+
+        #[test]
+        fn test_xor_doubles_property(values in prop::collection::vec(any::<f64>(), 0..200)) {
+            let bits: Vec<i64> = values.iter().map(|v| v.to_bits() as i64).collect();
+            let compressed = compress_int_array_with(&bits, IntArrayEncoding::Xor).unwrap();
+            prop_assert_eq!(decompress_int_array(&compressed, bits.len()).unwrap(), bits);
+        }
+
+        #[test]
+        fn test_dictionary_property(values in prop::collection::vec(prop::sample::select(vec!["GET", "POST", "", "DELETE"]), 0..200)) {
+            let lengths: Vec<usize> = values.iter().map(|v| v.len()).collect();
+            let data: Vec<u8> = values.concat().into_bytes();
+            if let Some(dictionary) = dictionary_encode(&lengths, &data) {
+                prop_assert!(dictionary.lengths.len() <= 4);
+                prop_assert_eq!(dictionary_decode(&dictionary).unwrap(), (lengths, data));
+            } else {
+                prop_assert!(values.len() < 8);
+            }
+        }
+
+        #[test]
+        fn test_int_pipeline_property(values in prop::collection::vec(any::<i64>(), 0..100)) {
+            for pipeline in [IntPipeline::BitPacked, IntPipeline::Delta, IntPipeline::DeltaOfDelta, IntPipeline::RunLength, IntPipeline::Xor, IntPipeline::Plain] {
+                let encoded = encode_int_array(&values, pipeline, TIME_INT_ENCODINGS, OptimizationTarget::Balanced).unwrap();
+                prop_assert_eq!(decode_int_array(&encoded, values.len(), pipeline).unwrap(), values.clone());
+            }
+        }
     }
 }
@@ -16,6 +16,7 @@ pub enum Value {
     Enum { tag: u32, value: Box<Value> },
     Nested(Box<Table>),
     Reversed(Box<Value>),
+    Map(Vec<(Value, Value)>),
 }
 
 /// Named field in a struct
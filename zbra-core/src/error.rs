@@ -37,6 +37,7 @@ pub enum StripedError {
     InvalidColumnType(String),
     CompressionError(String),
     VectorOperationFailed(String),
+    InvariantViolation { path: String, reason: String },
 }
 
 /// Binary format encoding/decoding errors
@@ -44,7 +45,13 @@ pub enum StripedError {
 pub enum BinaryError {
     InvalidHeader,
     InvalidMagicNumber,
+    MissingBlockIndex,
     CorruptedData(String),
+    ChecksumMismatch {
+        section: String,
+        expected: u32,
+        actual: u32,
+    },
     UnsupportedVersion(u32),
     DecompressionFailure(String),
     SerializationFailure(String),
@@ -56,6 +63,11 @@ pub enum BinaryError {
     InvalidEncodingTag(u8),
     InvalidIntEncodingTag(u8),
     InvalidBinaryEncodingTag(u8),
+    InvalidStatsTag(u8),
+    InvalidSchemaTag(u8),
+    InvalidCompressionTag(u8),
+    InvalidProjection(String),
+    InvalidPredicate(String),
     CompressionError(String),
     DecompressionError(String),
     IoError(std::io::Error),
@@ -153,6 +165,9 @@ impl fmt::Display for StripedError {
             StripedError::VectorOperationFailed(msg) => {
                 write!(f, "Vector operation failed: {}", msg)
             }
+            StripedError::InvariantViolation { path, reason } => {
+                write!(f, "Invariant violated at {}: {}", path, reason)
+            }
         }
     }
 }
@@ -166,9 +181,23 @@ impl fmt::Display for BinaryError {
             BinaryError::InvalidMagicNumber => {
                 write!(f, "Invalid magic number in binary file")
             }
+            BinaryError::MissingBlockIndex => {
+                write!(f, "Binary file has no block index")
+            }
             BinaryError::CorruptedData(msg) => {
                 write!(f, "Corrupted data: {}", msg)
             }
+            BinaryError::ChecksumMismatch {
+                section,
+                expected,
+                actual,
+            } => {
+                write!(
+                    f,
+                    "Checksum mismatch in {}: stored {:08x}, computed {:08x}",
+                    section, expected, actual
+                )
+            }
             BinaryError::UnsupportedVersion(version) => {
                 write!(f, "Unsupported format version: {}", version)
             }
@@ -202,6 +231,21 @@ impl fmt::Display for BinaryError {
             BinaryError::InvalidBinaryEncodingTag(tag) => {
                 write!(f, "Invalid binary encoding tag: {}", tag)
             }
+            BinaryError::InvalidStatsTag(tag) => {
+                write!(f, "Invalid stats tag: {}", tag)
+            }
+            BinaryError::InvalidSchemaTag(tag) => {
+                write!(f, "Invalid schema tag: {}", tag)
+            }
+            BinaryError::InvalidCompressionTag(tag) => {
+                write!(f, "Invalid compression tag: {}", tag)
+            }
+            BinaryError::InvalidProjection(msg) => {
+                write!(f, "Invalid projection: {}", msg)
+            }
+            BinaryError::InvalidPredicate(msg) => {
+                write!(f, "Invalid predicate: {}", msg)
+            }
             BinaryError::IoError(err) => {
                 write!(f, "I/O error: {}", err)
             }
@@ -3,4 +3,7 @@ pub mod compression;
 pub mod data;
 pub mod error;
 pub mod logical;
+pub mod mmap;
+pub mod predicate;
+pub mod stats;
 pub mod striped;
@@ -3,6 +3,8 @@
 use crate::data::{BinaryEncoding, Default, Encoding, Field, IntEncoding, Table, Value};
 use crate::error::{LogicalError, SchemaError};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
 
 /// Schema definition for tables
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -55,6 +57,11 @@ pub enum ValueSchema {
     Reversed {
         inner: Box<ValueSchema>,
     },
+    Map {
+        default: Default,
+        key: Box<ValueSchema>,
+        value: Box<ValueSchema>,
+    },
 }
 
 /// Schema for struct fields
@@ -72,6 +79,28 @@ pub struct VariantSchema {
     pub schema: ValueSchema,
 }
 
+/// Positions of a struct schema's fields keyed by name, so struct values can
+/// be matched to their schema whatever order their fields arrive in
+pub(crate) struct FieldIndex<'a> {
+    positions: HashMap<&'a str, usize>,
+}
+
+impl<'a> FieldIndex<'a> {
+    pub(crate) fn new(fields: &'a [FieldSchema]) -> Self {
+        let positions = fields
+            .iter()
+            .enumerate()
+            .map(|(position, field)| (field.name.as_str(), position))
+            .collect();
+        FieldIndex { positions }
+    }
+
+    /// Position of the named field in the schema
+    pub(crate) fn position(&self, name: &str) -> Option<usize> {
+        self.positions.get(name).copied()
+    }
+}
+
 /// Logical operations on tables
 impl Table {
     /// Validate table against schema
@@ -155,42 +184,35 @@ impl Value {
             (Value::Unit, ValueSchema::Unit) => Ok(()),
             (Value::Int(n), ValueSchema::Int { encoding, .. }) => {
                 // Basic validation for integer encoding
-                match encoding {
-                    Encoding::Int(IntEncoding::Date) => {
-                        // Date validation optimized for compression efficiency.
-                        //
-                        // Upper limit: 4102444800000 (January 1, 2100 00:00:00 UTC)
-                        // This 42-bit timestamp ensures optimal compression performance:
-                        // - Frame-of-reference encoding creates deltas by subtracting median
-                        // - For typical datasets, deltas stay within 32-bit range
-                        // - BP64 bit-packing is efficient for ≤32-bit values
-                        // - Beyond this limit, compression falls back to 8-byte storage
-                        //
-                        // Lower limit: 0 (January 1, 1970 00:00:00 UTC - Unix epoch)
-                        if *n < 0 || *n > 4102444800000 {
-                            return Err(SchemaError::UnsupportedType(format!(
-                                "Date value {} is outside valid range (0 to 4102444800000)",
-                                n
-                            )));
-                        }
+                if let Encoding::Int(IntEncoding::Date) = encoding {
+                    // Date validation optimized for compression efficiency.
+                    //
+                    // Upper limit: 4102444800000 (January 1, 2100 00:00:00 UTC)
+                    // This 42-bit timestamp ensures optimal compression performance:
+                    // - Frame-of-reference encoding creates deltas by subtracting median
+                    // - For typical datasets, deltas stay within 32-bit range
+                    // - BP64 bit-packing is efficient for ≤32-bit values
+                    // - Beyond this limit, compression falls back to 8-byte storage
+                    //
+                    // Lower limit: 0 (January 1, 1970 00:00:00 UTC - Unix epoch)
+                    if *n < 0 || *n > 4102444800000 {
+                        return Err(SchemaError::UnsupportedType(format!(
+                            "Date value {} is outside valid range (0 to 4102444800000)",
+                            n
+                        )));
                     }
-                    _ => {} // Other encodings pass through
                 }
                 Ok(())
             }
             (Value::Double(_), ValueSchema::Double { .. }) => Ok(()),
             (Value::Binary(data), ValueSchema::Binary { encoding, .. }) => {
-                // Validate binary encoding
-                match encoding {
-                    Encoding::Binary(BinaryEncoding::Utf8) => {
-                        // Validate UTF-8 encoding
-                        if let Err(_) = std::str::from_utf8(data) {
-                            return Err(SchemaError::UnsupportedType(
-                                "Invalid UTF-8 encoding in binary data".to_string(),
-                            ));
-                        }
+                // Validate UTF-8 encoding, binary encoding passes through
+                if let Encoding::Binary(BinaryEncoding::Utf8) = encoding {
+                    if std::str::from_utf8(data).is_err() {
+                        return Err(SchemaError::UnsupportedType(
+                            "Invalid UTF-8 encoding in binary data".to_string(),
+                        ));
                     }
-                    _ => {} // Binary encoding passes through
                 }
                 Ok(())
             }
@@ -207,17 +229,21 @@ impl Value {
                     ..
                 },
             ) => {
-                if fields.len() != field_schemas.len() {
-                    return Err(SchemaError::TypeMismatch {
-                        expected: format!("struct with {} fields", field_schemas.len()),
-                        actual: format!("struct with {} fields", fields.len()),
-                    });
+                // Fields may arrive in any order, e.g. sorted by serde_json
+                let index = FieldIndex::new(field_schemas);
+                let mut matched: Vec<Option<&Field>> = vec![None; field_schemas.len()];
+                for field in fields {
+                    if let Some(position) = index.position(&field.name) {
+                        matched[position].get_or_insert(field);
+                    }
                 }
-                for (field, field_schema) in fields.iter().zip(field_schemas.iter()) {
-                    if field.name != field_schema.name {
-                        return Err(SchemaError::MissingField(field_schema.name.clone()));
+                for (field, field_schema) in matched.into_iter().zip(field_schemas) {
+                    match field {
+                        Some(field) => field.value.validate_schema(&field_schema.schema)?,
+                        // Optional fields may be left out and take their default
+                        None if field_schema.schema.default_policy() == Default::Allow => {}
+                        None => return Err(SchemaError::MissingField(field_schema.name.clone())),
                     }
-                    field.value.validate_schema(&field_schema.schema)?;
                 }
                 Ok(())
             }
@@ -237,6 +263,13 @@ impl Value {
             (Value::Reversed(value), ValueSchema::Reversed { inner }) => {
                 value.validate_schema(inner)
             }
+            (Value::Map(pairs), ValueSchema::Map { key, value, .. }) => {
+                for (k, v) in pairs {
+                    k.validate_schema(key)?;
+                    v.validate_schema(value)?;
+                }
+                Ok(())
+            }
             _ => Err(SchemaError::TypeMismatch {
                 expected: format!("{:?}", schema),
                 actual: format!("{:?}", self),
@@ -285,21 +318,23 @@ impl Value {
                 merged.extend(b.clone());
                 Ok(Value::Array(merged))
             }
-            // Structs merge field by field
+            // Structs merge field by field, matched by name
             (Value::Struct(a), Value::Struct(b)) => {
                 if a.len() != b.len() {
                     return Err(LogicalError::StructureMismatch(
                         "Cannot merge structs with different field counts".to_string(),
                     ));
                 }
+                let b_fields: HashMap<&str, &Field> =
+                    b.iter().map(|field| (field.name.as_str(), field)).collect();
                 let mut merged_fields = Vec::new();
-                for (field_a, field_b) in a.iter().zip(b.iter()) {
-                    if field_a.name != field_b.name {
-                        return Err(LogicalError::StructureMismatch(format!(
-                            "Field name mismatch: {} vs {}",
-                            field_a.name, field_b.name
-                        )));
-                    }
+                for field_a in a {
+                    let field_b = b_fields.get(field_a.name.as_str()).ok_or_else(|| {
+                        LogicalError::StructureMismatch(format!(
+                            "Field {} missing from struct being merged",
+                            field_a.name
+                        ))
+                    })?;
                     let merged_value = field_a.value.merge(&field_b.value)?;
                     merged_fields.push(Field {
                         name: field_a.name.clone(),
@@ -344,6 +379,13 @@ impl Value {
                 let merged_inner = a.merge(b)?;
                 Ok(Value::Reversed(Box::new(merged_inner)))
             }
+            // Maps merge like map tables, by key
+            (Value::Map(a), Value::Map(b)) => {
+                match Table::Map(a.clone()).merge(&Table::Map(b.clone()))? {
+                    Table::Map(pairs) => Ok(Value::Map(pairs)),
+                    _ => unreachable!("merging map tables yields a map table"),
+                }
+            }
             _ => Err(LogicalError::StructureMismatch(format!(
                 "Cannot merge values of different types: {:?} and {:?}",
                 self, other
@@ -387,6 +429,7 @@ impl Value {
             ValueSchema::Reversed { inner } => {
                 Value::Reversed(Box::new(Value::default_for_schema(inner)))
             }
+            ValueSchema::Map { .. } => Value::Map(Vec::new()),
         }
     }
 }
@@ -438,11 +481,143 @@ impl ValueSchema {
                 Ok(())
             }
             ValueSchema::Array { element, .. } => element.validate(),
+            ValueSchema::Map { key, value, .. } => {
+                key.validate()?;
+                value.validate()
+            }
             ValueSchema::Nested { table } => table.validate(),
             ValueSchema::Reversed { inner } => inner.validate(),
             _ => Ok(()),
         }
     }
+
+    /// Check that data written under this schema can be read as `reader`
+    pub fn check_resolvable(&self, reader: &ValueSchema) -> Result<(), SchemaError> {
+        match (self, reader) {
+            (ValueSchema::Unit, ValueSchema::Unit)
+            | (ValueSchema::Double { .. }, ValueSchema::Double { .. }) => Ok(()),
+            (
+                ValueSchema::Int { encoding, .. },
+                ValueSchema::Int {
+                    encoding: reader_encoding,
+                    ..
+                },
+            )
+            | (
+                ValueSchema::Binary { encoding, .. },
+                ValueSchema::Binary {
+                    encoding: reader_encoding,
+                    ..
+                },
+            ) => check_encoding_resolvable(encoding, reader_encoding),
+            (
+                ValueSchema::Array { element, .. },
+                ValueSchema::Array {
+                    element: reader_element,
+                    ..
+                },
+            ) => element.check_resolvable(reader_element),
+            (
+                ValueSchema::Struct { fields, .. },
+                ValueSchema::Struct {
+                    fields: reader_fields,
+                    ..
+                },
+            ) => {
+                for reader_field in reader_fields {
+                    match fields.iter().find(|f| f.name == reader_field.name) {
+                        Some(field) => field.schema.check_resolvable(&reader_field.schema)?,
+                        None if reader_field.schema.default_policy() == Default::Allow => {}
+                        None => {
+                            return Err(SchemaError::IncompatibleSchema {
+                                source: format!("struct without field '{}'", reader_field.name),
+                                target: format!(
+                                    "struct requiring field '{}' (Default::Deny)",
+                                    reader_field.name
+                                ),
+                            })
+                        }
+                    }
+                }
+                Ok(())
+            }
+            (
+                ValueSchema::Enum { variants, .. },
+                ValueSchema::Enum {
+                    variants: reader_variants,
+                    ..
+                },
+            ) => {
+                for variant in variants {
+                    match reader_variants.iter().find(|v| v.tag == variant.tag) {
+                        Some(reader_variant) => {
+                            variant.schema.check_resolvable(&reader_variant.schema)?
+                        }
+                        None => {
+                            return Err(SchemaError::IncompatibleSchema {
+                                source: format!(
+                                    "enum variant '{}' (tag {})",
+                                    variant.name, variant.tag
+                                ),
+                                target: format!("enum without tag {}", variant.tag),
+                            })
+                        }
+                    }
+                }
+                Ok(())
+            }
+            (
+                ValueSchema::Nested { table },
+                ValueSchema::Nested {
+                    table: reader_table,
+                },
+            ) => table.check_resolvable(reader_table),
+            (
+                ValueSchema::Reversed { inner },
+                ValueSchema::Reversed {
+                    inner: reader_inner,
+                },
+            ) => inner.check_resolvable(reader_inner),
+            (
+                ValueSchema::Map { key, value, .. },
+                ValueSchema::Map {
+                    key: reader_key,
+                    value: reader_value,
+                    ..
+                },
+            ) => {
+                key.check_resolvable(reader_key)?;
+                value.check_resolvable(reader_value)
+            }
+            _ => Err(SchemaError::IncompatibleSchema {
+                source: format!("{:?}", self),
+                target: format!("{:?}", reader),
+            }),
+        }
+    }
+
+    /// List every change between this schema and `new`
+    pub fn diff(&self, new: &ValueSchema) -> Vec<SchemaChange> {
+        let mut changes = Vec::new();
+        diff_values("", self, new, &mut changes);
+        changes
+    }
+
+    /// The default policy governing missing values of this schema
+    pub fn default_policy(&self) -> Default {
+        match self {
+            ValueSchema::Unit => Default::Allow,
+            ValueSchema::Int { default, .. }
+            | ValueSchema::Double { default }
+            | ValueSchema::Binary { default, .. }
+            | ValueSchema::Array { default, .. }
+            | ValueSchema::Struct { default, .. }
+            | ValueSchema::Enum { default, .. }
+            | ValueSchema::Map { default, .. } => default.clone(),
+            ValueSchema::Nested { table } => table.default_policy(),
+            ValueSchema::Reversed { inner } => inner.default_policy(),
+        }
+    }
 }
 
 impl TableSchema {
@@ -457,6 +632,685 @@ impl TableSchema {
             }
         }
     }
+
+    /// Check that data written under this schema can be read as `reader`
+    pub fn check_resolvable(&self, reader: &TableSchema) -> Result<(), SchemaError> {
+        match (self, reader) {
+            (
+                TableSchema::Binary { encoding, .. },
+                TableSchema::Binary {
+                    encoding: reader_encoding,
+                    ..
+                },
+            ) => check_encoding_resolvable(encoding, reader_encoding),
+            (
+                TableSchema::Array { element, .. },
+                TableSchema::Array {
+                    element: reader_element,
+                    ..
+                },
+            ) => element.check_resolvable(reader_element),
+            (
+                TableSchema::Map { key, value, .. },
+                TableSchema::Map {
+                    key: reader_key,
+                    value: reader_value,
+                    ..
+                },
+            ) => {
+                key.check_resolvable(reader_key)?;
+                value.check_resolvable(reader_value)
+            }
+            _ => Err(SchemaError::IncompatibleSchema {
+                source: format!("{:?}", self),
+                target: format!("{:?}", reader),
+            }),
+        }
+    }
+
+    /// List every change between this schema and `new`
+    pub fn diff(&self, new: &TableSchema) -> Vec<SchemaChange> {
+        let mut changes = Vec::new();
+        diff_tables("", self, new, &mut changes);
+        changes
+    }
+
+    /// The overall compatibility of moving from this schema to `new`
+    pub fn compatibility(&self, new: &TableSchema) -> Compatibility {
+        self.diff(new)
+            .iter()
+            .fold(Compatibility::Full, |overall, change| {
+                overall.and(change.compatibility)
+            })
+    }
+
+    /// The default policy governing missing values of this schema
+    pub fn default_policy(&self) -> Default {
+        match self {
+            TableSchema::Binary { default, .. }
+            | TableSchema::Array { default, .. }
+            | TableSchema::Map { default, .. } => default.clone(),
+        }
+    }
+}
+
+/// How a schema change affects data written on one side of it and read on
+/// the other
+///
+/// Backward-compatible changes let readers on the new schema read data
+/// written under the old one. Forward-compatible changes let readers still on
+/// the old schema read data written under the new one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compatibility {
+    /// Both backward- and forward-compatible
+    Full,
+    Backward,
+    Forward,
+    Breaking,
+}
+
+impl Compatibility {
+    /// New readers can read old data
+    pub fn is_backward(self) -> bool {
+        matches!(self, Compatibility::Full | Compatibility::Backward)
+    }
+
+    /// Old readers can read new data
+    pub fn is_forward(self) -> bool {
+        matches!(self, Compatibility::Full | Compatibility::Forward)
+    }
+
+    /// The compatibility of making both changes
+    pub fn and(self, other: Compatibility) -> Compatibility {
+        match (
+            self.is_backward() && other.is_backward(),
+            self.is_forward() && other.is_forward(),
+        ) {
+            (true, true) => Compatibility::Full,
+            (true, false) => Compatibility::Backward,
+            (false, true) => Compatibility::Forward,
+            (false, false) => Compatibility::Breaking,
+        }
+    }
+}
+
+impl fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compatibility::Full => write!(f, "fully compatible"),
+            Compatibility::Backward => write!(f, "backward-compatible"),
+            Compatibility::Forward => write!(f, "forward-compatible"),
+            Compatibility::Breaking => write!(f, "breaking"),
+        }
+    }
+}
+
+/// A single difference between an old and a new schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaChange {
+    /// Dotted path of field, element and variant names, empty for the root
+    pub path: String,
+    pub old: String,
+    pub new: String,
+    pub compatibility: Compatibility,
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {} ({})",
+            display_path(&self.path),
+            self.old,
+            self.new,
+            self.compatibility
+        )
+    }
+}
+
+fn display_path(path: &str) -> &str {
+    if path.is_empty() {
+        "<table>"
+    } else {
+        path
+    }
+}
+
+fn child_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn push_change(
+    changes: &mut Vec<SchemaChange>,
+    path: &str,
+    old: String,
+    new: String,
+    compatibility: Compatibility,
+) {
+    changes.push(SchemaChange {
+        path: path.to_string(),
+        old,
+        new,
+        compatibility,
+    });
+}
+
+fn diff_tables(path: &str, old: &TableSchema, new: &TableSchema, changes: &mut Vec<SchemaChange>) {
+    if std::mem::discriminant(old) != std::mem::discriminant(new) {
+        let kind = |schema: &TableSchema| match schema {
+            TableSchema::Binary { .. } => "binary table",
+            TableSchema::Array { .. } => "array table",
+            TableSchema::Map { .. } => "map table",
+        };
+        let (old_kind, new_kind) = (kind(old).to_string(), kind(new).to_string());
+        return push_change(changes, path, old_kind, new_kind, Compatibility::Breaking);
+    }
+    diff_defaults(path, &old.default_policy(), &new.default_policy(), changes);
+    match (old, new) {
+        (
+            TableSchema::Binary { encoding, .. },
+            TableSchema::Binary {
+                encoding: new_encoding,
+                ..
+            },
+        ) => diff_encodings(path, encoding, new_encoding, changes),
+        (
+            TableSchema::Array { element, .. },
+            TableSchema::Array {
+                element: new_element,
+                ..
+            },
+        ) => diff_values(&child_path(path, "element"), element, new_element, changes),
+        (
+            TableSchema::Map { key, value, .. },
+            TableSchema::Map {
+                key: new_key,
+                value: new_value,
+                ..
+            },
+        ) => {
+            diff_values(&child_path(path, "key"), key, new_key, changes);
+            diff_values(&child_path(path, "value"), value, new_value, changes);
+        }
+        _ => unreachable!("table kinds compared above"),
+    }
+}
+
+fn diff_values(path: &str, old: &ValueSchema, new: &ValueSchema, changes: &mut Vec<SchemaChange>) {
+    if std::mem::discriminant(old) != std::mem::discriminant(new) {
+        let kind = |schema: &ValueSchema| match schema {
+            ValueSchema::Unit => "unit",
+            ValueSchema::Int { .. } => "int",
+            ValueSchema::Double { .. } => "double",
+            ValueSchema::Binary { .. } => "binary",
+            ValueSchema::Array { .. } => "array",
+            ValueSchema::Struct { .. } => "struct",
+            ValueSchema::Enum { .. } => "enum",
+            ValueSchema::Nested { .. } => "nested table",
+            ValueSchema::Reversed { .. } => "reversed",
+            ValueSchema::Map { .. } => "map",
+        };
+        let (old_kind, new_kind) = (kind(old).to_string(), kind(new).to_string());
+        return push_change(changes, path, old_kind, new_kind, Compatibility::Breaking);
+    }
+    match (old, new) {
+        (ValueSchema::Unit, ValueSchema::Unit) => {}
+        (
+            ValueSchema::Int { default, encoding },
+            ValueSchema::Int {
+                default: new_default,
+                encoding: new_encoding,
+            },
+        )
+        | (
+            ValueSchema::Binary { default, encoding },
+            ValueSchema::Binary {
+                default: new_default,
+                encoding: new_encoding,
+            },
+        ) => {
+            diff_defaults(path, default, new_default, changes);
+            diff_encodings(path, encoding, new_encoding, changes);
+        }
+        (
+            ValueSchema::Double { default },
+            ValueSchema::Double {
+                default: new_default,
+            },
+        ) => diff_defaults(path, default, new_default, changes),
+        (
+            ValueSchema::Array { default, element },
+            ValueSchema::Array {
+                default: new_default,
+                element: new_element,
+            },
+        ) => {
+            diff_defaults(path, default, new_default, changes);
+            diff_values(&child_path(path, "element"), element, new_element, changes);
+        }
+        (
+            ValueSchema::Struct { default, fields },
+            ValueSchema::Struct {
+                default: new_default,
+                fields: new_fields,
+            },
+        ) => {
+            diff_defaults(path, default, new_default, changes);
+            diff_fields(path, fields, new_fields, changes);
+        }
+        (
+            ValueSchema::Enum { default, variants },
+            ValueSchema::Enum {
+                default: new_default,
+                variants: new_variants,
+            },
+        ) => {
+            diff_defaults(path, default, new_default, changes);
+            diff_variants(path, variants, new_variants, changes);
+        }
+        (ValueSchema::Nested { table }, ValueSchema::Nested { table: new_table }) => {
+            diff_tables(path, table, new_table, changes)
+        }
+        (ValueSchema::Reversed { inner }, ValueSchema::Reversed { inner: new_inner }) => {
+            diff_values(path, inner, new_inner, changes)
+        }
+        (
+            ValueSchema::Map {
+                default,
+                key,
+                value,
+            },
+            ValueSchema::Map {
+                default: new_default,
+                key: new_key,
+                value: new_value,
+            },
+        ) => {
+            diff_defaults(path, default, new_default, changes);
+            diff_values(&child_path(path, "key"), key, new_key, changes);
+            diff_values(&child_path(path, "value"), value, new_value, changes);
+        }
+        _ => unreachable!("value kinds compared above"),
+    }
+}
+
+/// Fields are matched by name, so a rename shows up as a removal and an
+/// addition. Readers drop fields they do not know and fill in fields the data
+/// lacks, which only works when the missing field allows defaults.
+fn diff_fields(
+    path: &str,
+    fields: &[FieldSchema],
+    new_fields: &[FieldSchema],
+    changes: &mut Vec<SchemaChange>,
+) {
+    for field in fields {
+        let field_path = child_path(path, &field.name);
+        match new_fields.iter().find(|f| f.name == field.name) {
+            Some(new_field) => diff_values(&field_path, &field.schema, &new_field.schema, changes),
+            None => {
+                let default = field.schema.default_policy();
+                let compatibility = match default {
+                    Default::Allow => Compatibility::Full,
+                    Default::Deny => Compatibility::Backward,
+                };
+                let old = format!("field with Default::{:?}", default);
+                push_change(
+                    changes,
+                    &field_path,
+                    old,
+                    "removed".to_string(),
+                    compatibility,
+                );
+            }
+        }
+    }
+    for new_field in new_fields {
+        if fields.iter().any(|f| f.name == new_field.name) {
+            continue;
+        }
+        let default = new_field.schema.default_policy();
+        let compatibility = match default {
+            Default::Allow => Compatibility::Full,
+            Default::Deny => Compatibility::Forward,
+        };
+        let new = format!("field with Default::{:?}", default);
+        let field_path = child_path(path, &new_field.name);
+        push_change(
+            changes,
+            &field_path,
+            "absent".to_string(),
+            new,
+            compatibility,
+        );
+    }
+}
+
+/// Variants are matched by tag: a reader cannot decode a tag it does not
+/// know, and a tag that now names a different variant changes meaning.
+fn diff_variants(
+    path: &str,
+    variants: &[VariantSchema],
+    new_variants: &[VariantSchema],
+    changes: &mut Vec<SchemaChange>,
+) {
+    for variant in variants {
+        let variant_path = child_path(path, &variant.name);
+        match new_variants.iter().find(|v| v.tag == variant.tag) {
+            Some(new_variant) if new_variant.name != variant.name => push_change(
+                changes,
+                &variant_path,
+                format!("variant '{}' (tag {})", variant.name, variant.tag),
+                format!("variant '{}' (tag {})", new_variant.name, new_variant.tag),
+                Compatibility::Breaking,
+            ),
+            Some(new_variant) => {
+                diff_values(&variant_path, &variant.schema, &new_variant.schema, changes)
+            }
+            None => push_change(
+                changes,
+                &variant_path,
+                format!("variant with tag {}", variant.tag),
+                "removed".to_string(),
+                Compatibility::Forward,
+            ),
+        }
+    }
+    for new_variant in new_variants {
+        if variants.iter().any(|v| v.tag == new_variant.tag) {
+            continue;
+        }
+        push_change(
+            changes,
+            &child_path(path, &new_variant.name),
+            "absent".to_string(),
+            format!("variant with tag {}", new_variant.tag),
+            Compatibility::Backward,
+        );
+    }
+}
+
+/// Tightening a default policy rejects old data that relied on it, while
+/// loosening it lets new data carry defaults old readers reject.
+fn diff_defaults(
+    path: &str,
+    default: &Default,
+    new_default: &Default,
+    changes: &mut Vec<SchemaChange>,
+) {
+    let compatibility = match (default, new_default) {
+        (Default::Allow, Default::Deny) => Compatibility::Forward,
+        (Default::Deny, Default::Allow) => Compatibility::Backward,
+        _ => return,
+    };
+    let (old, new) = (
+        format!("Default::{:?}", default),
+        format!("Default::{:?}", new_default),
+    );
+    push_change(changes, path, old, new, compatibility);
+}
+
+/// Any bytes are valid binary but not valid UTF-8, so only the step from
+/// UTF-8 to binary is safe to read old data with. Integer encodings change
+/// what the values mean.
+fn diff_encodings(
+    path: &str,
+    encoding: &Encoding,
+    new_encoding: &Encoding,
+    changes: &mut Vec<SchemaChange>,
+) {
+    let compatibility = match (encoding, new_encoding) {
+        _ if encoding == new_encoding => return,
+        (Encoding::Binary(BinaryEncoding::Utf8), Encoding::Binary(BinaryEncoding::Binary)) => {
+            Compatibility::Backward
+        }
+        (Encoding::Binary(BinaryEncoding::Binary), Encoding::Binary(BinaryEncoding::Utf8)) => {
+            Compatibility::Forward
+        }
+        _ => Compatibility::Breaking,
+    };
+    let (old, new) = (
+        format!("{:?} encoding", encoding),
+        format!("{:?} encoding", new_encoding),
+    );
+    push_change(changes, path, old, new, compatibility);
+}
+
+/// Check that values stored with `encoding` can be read with `reader`
+///
+/// As for `diff_encodings`, only UTF-8 widens to binary: resolution copies
+/// values as they are, so a narrower reader encoding would let through
+/// values it does not allow.
+fn check_encoding_resolvable(encoding: &Encoding, reader: &Encoding) -> Result<(), SchemaError> {
+    match (encoding, reader) {
+        _ if encoding == reader => Ok(()),
+        (Encoding::Binary(BinaryEncoding::Utf8), Encoding::Binary(BinaryEncoding::Binary)) => {
+            Ok(())
+        }
+        _ => Err(SchemaError::IncompatibleSchema {
+            source: format!("{:?} encoding", encoding),
+            target: format!("{:?} encoding", reader),
+        }),
+    }
+}
+
+/// Schema resolution: reading data written under an older schema
+impl Table {
+    /// Resolve a table written under `writer` into the shape of `reader`
+    ///
+    /// Struct fields are matched by name and enum variants by tag. Fields the
+    /// writer never had are filled with `Value::default_for_schema` when the
+    /// reader allows defaults, and fields the reader dropped are discarded.
+    pub fn resolve(
+        &self,
+        writer: &TableSchema,
+        reader: &TableSchema,
+    ) -> Result<Table, SchemaError> {
+        writer.check_resolvable(reader)?;
+        self.resolve_unchecked(writer, reader)
+    }
+
+    fn resolve_unchecked(
+        &self,
+        writer: &TableSchema,
+        reader: &TableSchema,
+    ) -> Result<Table, SchemaError> {
+        match (self, writer, reader) {
+            (Table::Binary(data), TableSchema::Binary { .. }, TableSchema::Binary { .. }) => {
+                Ok(Table::Binary(data.clone()))
+            }
+            (
+                Table::Array(values),
+                TableSchema::Array {
+                    element: writer_element,
+                    ..
+                },
+                TableSchema::Array {
+                    element: reader_element,
+                    ..
+                },
+            ) => values
+                .iter()
+                .map(|value| value.resolve_unchecked(writer_element, reader_element))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Table::Array),
+            (
+                Table::Map(pairs),
+                TableSchema::Map {
+                    key: writer_key,
+                    value: writer_value,
+                    ..
+                },
+                TableSchema::Map {
+                    key: reader_key,
+                    value: reader_value,
+                    ..
+                },
+            ) => pairs
+                .iter()
+                .map(|(k, v)| {
+                    Ok((
+                        k.resolve_unchecked(writer_key, reader_key)?,
+                        v.resolve_unchecked(writer_value, reader_value)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(Table::Map),
+            _ => Err(SchemaError::TypeMismatch {
+                expected: format!("{:?}", writer),
+                actual: format!("{:?}", self),
+            }),
+        }
+    }
+}
+
+impl Value {
+    /// Resolve a value written under `writer` into the shape of `reader`
+    pub fn resolve(
+        &self,
+        writer: &ValueSchema,
+        reader: &ValueSchema,
+    ) -> Result<Value, SchemaError> {
+        writer.check_resolvable(reader)?;
+        self.resolve_unchecked(writer, reader)
+    }
+
+    fn resolve_unchecked(
+        &self,
+        writer: &ValueSchema,
+        reader: &ValueSchema,
+    ) -> Result<Value, SchemaError> {
+        match (self, writer, reader) {
+            (
+                Value::Array(values),
+                ValueSchema::Array {
+                    element: writer_element,
+                    ..
+                },
+                ValueSchema::Array {
+                    element: reader_element,
+                    ..
+                },
+            ) => values
+                .iter()
+                .map(|value| value.resolve_unchecked(writer_element, reader_element))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::Array),
+            (
+                Value::Struct(fields),
+                ValueSchema::Struct {
+                    fields: writer_fields,
+                    ..
+                },
+                ValueSchema::Struct {
+                    fields: reader_fields,
+                    ..
+                },
+            ) => {
+                let mut resolved = Vec::with_capacity(reader_fields.len());
+                for reader_field in reader_fields {
+                    let written = writer_fields
+                        .iter()
+                        .find(|f| f.name == reader_field.name)
+                        .zip(fields.iter().find(|f| f.name == reader_field.name));
+                    let value = match written {
+                        Some((writer_field, field)) => field
+                            .value
+                            .resolve_unchecked(&writer_field.schema, &reader_field.schema)?,
+                        None => Value::default_for_schema(&reader_field.schema),
+                    };
+                    resolved.push(Field {
+                        name: reader_field.name.clone(),
+                        value,
+                    });
+                }
+                Ok(Value::Struct(resolved))
+            }
+            (
+                Value::Enum { tag, value },
+                ValueSchema::Enum {
+                    variants: writer_variants,
+                    ..
+                },
+                ValueSchema::Enum {
+                    variants: reader_variants,
+                    ..
+                },
+            ) => {
+                let writer_variant = writer_variants.iter().find(|v| v.tag == *tag);
+                let reader_variant = reader_variants.iter().find(|v| v.tag == *tag);
+                match (writer_variant, reader_variant) {
+                    (Some(writer_variant), Some(reader_variant)) => {
+                        Ok(Value::Enum {
+                            tag: *tag,
+                            value: Box::new(value.resolve_unchecked(
+                                &writer_variant.schema,
+                                &reader_variant.schema,
+                            )?),
+                        })
+                    }
+                    _ => Err(SchemaError::UnsupportedType(format!("enum tag {}", tag))),
+                }
+            }
+            (
+                Value::Nested(table),
+                ValueSchema::Nested {
+                    table: writer_table,
+                },
+                ValueSchema::Nested {
+                    table: reader_table,
+                },
+            ) => Ok(Value::Nested(Box::new(
+                table.resolve_unchecked(writer_table, reader_table)?,
+            ))),
+            (
+                Value::Reversed(value),
+                ValueSchema::Reversed {
+                    inner: writer_inner,
+                },
+                ValueSchema::Reversed {
+                    inner: reader_inner,
+                },
+            ) => Ok(Value::Reversed(Box::new(
+                value.resolve_unchecked(writer_inner, reader_inner)?,
+            ))),
+            (
+                Value::Map(pairs),
+                ValueSchema::Map {
+                    key: writer_key,
+                    value: writer_value,
+                    ..
+                },
+                ValueSchema::Map {
+                    key: reader_key,
+                    value: reader_value,
+                    ..
+                },
+            ) => pairs
+                .iter()
+                .map(|(k, v)| {
+                    Ok((
+                        k.resolve_unchecked(writer_key, reader_key)?,
+                        v.resolve_unchecked(writer_value, reader_value)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::Map),
+            (Value::Unit, ValueSchema::Unit, ValueSchema::Unit)
+            | (Value::Int(_), ValueSchema::Int { .. }, ValueSchema::Int { .. })
+            | (Value::Double(_), ValueSchema::Double { .. }, ValueSchema::Double { .. })
+            | (Value::Binary(_), ValueSchema::Binary { .. }, ValueSchema::Binary { .. }) => {
+                Ok(self.clone())
+            }
+            _ => Err(SchemaError::TypeMismatch {
+                expected: format!("{:?}", writer),
+                actual: format!("{:?}", self),
+            }),
+        }
+    }
 }
 
 /// Type-safe extractors for values
@@ -510,6 +1364,16 @@ impl Value {
             }),
         }
     }
+
+    pub fn take_map(&self) -> Result<&Vec<(Value, Value)>, LogicalError> {
+        match self {
+            Value::Map(m) => Ok(m),
+            _ => Err(LogicalError::InvalidValue {
+                field: "value".to_string(),
+                reason: format!("Expected map, got {:?}", self),
+            }),
+        }
+    }
 }
 
 /// Type-safe extractors for tables
@@ -590,4 +1454,209 @@ mod tests {
             _ => panic!("Expected array"),
         }
     }
+
+    #[test]
+    fn test_struct_fields_matched_by_name() {
+        let int = |default| ValueSchema::Int {
+            default,
+            encoding: Encoding::Int(IntEncoding::Int),
+        };
+        let schema = ValueSchema::Struct {
+            default: Default::Allow,
+            fields: [("id", Default::Deny), ("age", Default::Allow)]
+                .into_iter()
+                .map(|(name, default)| FieldSchema {
+                    name: name.to_string(),
+                    schema: int(default),
+                })
+                .collect(),
+        };
+        let row = |fields: &[(&str, i64)]| {
+            Value::Struct(
+                fields
+                    .iter()
+                    .map(|(name, n)| Field {
+                        name: name.to_string(),
+                        value: Value::Int(*n),
+                    })
+                    .collect(),
+            )
+        };
+
+        // Sorted keys, as serde_json produces them
+        let sorted = row(&[("age", 30), ("id", 1)]);
+        assert!(sorted.validate_schema(&schema).is_ok());
+        assert!(matches!(
+            row(&[("age", 30), ("name", 1)]).validate_schema(&schema),
+            Err(SchemaError::MissingField(name)) if name == "id"
+        ));
+
+        // Optional fields may be left out, and unknown fields are ignored as
+        // when striping
+        assert!(row(&[("id", 1)]).validate_schema(&schema).is_ok());
+        assert!(row(&[("id", 1), ("name", 2)])
+            .validate_schema(&schema)
+            .is_ok());
+
+        let merged = row(&[("id", 1), ("age", 30)]).merge(&sorted).unwrap();
+        assert_eq!(merged, row(&[("id", 1), ("age", 30)]));
+        assert!(sorted.merge(&row(&[("id", 1), ("name", 30)])).is_err());
+    }
+
+    #[test]
+    fn test_resolve_enum_by_tag() {
+        let variant = |name: &str, tag| VariantSchema {
+            name: name.to_string(),
+            tag,
+            schema: ValueSchema::Unit,
+        };
+        let enum_of = |variants| ValueSchema::Enum {
+            default: Default::Allow,
+            variants,
+        };
+        let writer = enum_of(vec![variant("low", 0), variant("high", 1)]);
+        let value = Value::Enum {
+            tag: 1,
+            value: Box::new(Value::Unit),
+        };
+
+        // Variants are matched by tag, so renames and new variants resolve
+        let extended = enum_of(vec![
+            variant("top", 1),
+            variant("low", 0),
+            variant("medium", 2),
+        ]);
+        assert_eq!(value.resolve(&writer, &extended).unwrap(), value);
+        assert!(writer.check_resolvable(&extended).is_ok());
+
+        // Dropping a variant the writer could have used is rejected
+        let narrowed = enum_of(vec![variant("high", 1)]);
+        assert!(matches!(
+            value.resolve(&writer, &narrowed),
+            Err(SchemaError::IncompatibleSchema { .. })
+        ));
+    }
+
+    #[test]
+    fn test_resolve_checks_encodings() {
+        let field = |encoding| TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(ValueSchema::Struct {
+                default: Default::Allow,
+                fields: vec![FieldSchema {
+                    name: "id".to_string(),
+                    schema: ValueSchema::Binary {
+                        default: Default::Allow,
+                        encoding: Encoding::Binary(encoding),
+                    },
+                }],
+            }),
+        };
+        let text = field(BinaryEncoding::Utf8);
+        let bytes = field(BinaryEncoding::Binary);
+
+        // UTF-8 values can be read as binary, but not the other way round
+        assert!(text.check_resolvable(&bytes).is_ok());
+        assert!(bytes.check_resolvable(&text).is_err());
+        let table = Table::Array(vec![Value::Struct(vec![Field {
+            name: "id".to_string(),
+            value: Value::Binary(vec![0xff]),
+        }])]);
+        assert!(table.resolve(&bytes, &text).is_err());
+
+        // Int encodings must match exactly
+        let int = |encoding| ValueSchema::Int {
+            default: Default::Allow,
+            encoding: Encoding::Int(encoding),
+        };
+        assert!(int(IntEncoding::Int)
+            .check_resolvable(&int(IntEncoding::Date))
+            .is_err());
+    }
+
+    #[test]
+    fn test_schema_diff() {
+        let text = |default| ValueSchema::Binary {
+            default,
+            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+        };
+        let field = |name: &str, schema| FieldSchema {
+            name: name.to_string(),
+            schema,
+        };
+        let table_of = |fields| TableSchema::Array {
+            default: Default::Allow,
+            element: Box::new(ValueSchema::Struct {
+                default: Default::Allow,
+                fields,
+            }),
+        };
+        let old = table_of(vec![
+            field("id", text(Default::Deny)),
+            field("note", text(Default::Allow)),
+        ]);
+        assert!(old.diff(&old).is_empty());
+        assert_eq!(old.compatibility(&old), Compatibility::Full);
+
+        // Optional fields come and go freely
+        let optional = table_of(vec![
+            field("id", text(Default::Deny)),
+            field("email", text(Default::Allow)),
+        ]);
+        assert_eq!(old.diff(&optional).len(), 2);
+        assert_eq!(old.compatibility(&optional), Compatibility::Full);
+
+        // New readers cannot invent a required field, old readers drop it
+        let required = table_of(vec![
+            field("id", text(Default::Deny)),
+            field("note", text(Default::Allow)),
+            field("email", text(Default::Deny)),
+        ]);
+        assert_eq!(
+            old.diff(&required),
+            vec![SchemaChange {
+                path: "element.email".to_string(),
+                old: "absent".to_string(),
+                new: "field with Default::Deny".to_string(),
+                compatibility: Compatibility::Forward,
+            }]
+        );
+        assert!(old.check_resolvable(&required).is_err());
+        assert!(required.check_resolvable(&old).is_ok());
+
+        // Removing a required field strands old readers
+        let removed = table_of(vec![field("note", text(Default::Allow))]);
+        assert_eq!(old.compatibility(&removed), Compatibility::Backward);
+
+        // Widening UTF-8 to binary only works one way
+        let widened = table_of(vec![
+            field(
+                "id",
+                ValueSchema::Binary {
+                    default: Default::Deny,
+                    encoding: Encoding::Binary(BinaryEncoding::Binary),
+                },
+            ),
+            field("note", text(Default::Allow)),
+        ]);
+        assert_eq!(old.compatibility(&widened), Compatibility::Backward);
+        assert_eq!(widened.compatibility(&old), Compatibility::Forward);
+
+        // Changing a field's type, or combining one-way changes, breaks both
+        let retyped = table_of(vec![
+            field(
+                "id",
+                ValueSchema::Int {
+                    default: Default::Deny,
+                    encoding: Encoding::Int(IntEncoding::Int),
+                },
+            ),
+            field("note", text(Default::Allow)),
+        ]);
+        assert_eq!(old.compatibility(&retyped), Compatibility::Breaking);
+        assert_eq!(
+            Compatibility::Backward.and(Compatibility::Forward),
+            Compatibility::Breaking
+        );
+    }
 }
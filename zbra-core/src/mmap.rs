@@ -0,0 +1,719 @@
+// Memory-mapped reader - zero-copy access to .zbra files
+
+use crate::binary::{
+    read_block_table, read_dictionary, read_int_array_usize_compressed, read_int_runs, read_string,
+    read_u32, read_u8, split_block_checksum, total_length, Block, BlockIndexEntry, FormatVersion,
+    Header, IndexedReader, Projection,
+};
+use crate::compression::{
+    decompress_binary_exact, dictionary_decode, CompressionAlgorithm, CompressionConfig, IntRun,
+};
+use crate::data::{Default, Encoding};
+use crate::error::{BinaryError, Result};
+use crate::logical::ValueSchema;
+use crate::stats::BlockStats;
+use crate::striped::Table;
+use memmap2::Mmap;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::Cursor;
+use std::ops::Range;
+use std::path::Path;
+
+/// Reader over a memory-mapped binary file
+///
+/// Blocks are located through the block index footer and handed out as
+/// slices of the mapping. Uncompressed binary data is borrowed from the
+/// mapping rather than copied.
+#[derive(Debug)]
+pub struct MappedReader {
+    mmap: Mmap,
+    header: Header,
+    version: FormatVersion,
+    header_range: Range<usize>,
+    index: Vec<BlockIndexEntry>,
+}
+
+/// Block borrowed from a memory-mapped file
+#[derive(Debug, Clone, Copy)]
+pub struct MappedBlock<'a> {
+    pub row_count: u32,
+    stats: &'a [u8],
+    table: &'a [u8],
+    compression: &'a CompressionConfig,
+}
+
+/// Binary column read from a mapped block
+///
+/// `data` borrows from the mapping when the column is stored uncompressed
+/// and without a dictionary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryColumnRef<'a> {
+    pub default: Default,
+    pub encoding: Encoding,
+    pub lengths: Vec<usize>,
+    pub data: Cow<'a, [u8]>,
+}
+
+/// Binary table read from a mapped block
+///
+/// `data` borrows from the mapping when the table is stored uncompressed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryTableRef<'a> {
+    pub default: Default,
+    pub encoding: Encoding,
+    pub data: Cow<'a, [u8]>,
+}
+
+impl MappedReader {
+    /// Map a file and parse its header and block index
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the mapping is read-only; as with any mmap, the file must not
+        // be truncated or modified by another process while it is mapped
+        let mmap = unsafe { Mmap::map(&file)? };
+        Self::from_mmap(mmap)
+    }
+
+    /// Parse the header and block index of an existing mapping
+    pub fn from_mmap(mmap: Mmap) -> Result<Self> {
+        let indexed = IndexedReader::new(Cursor::new(&mmap[..]))?;
+        let header = indexed.header().clone();
+        let version = indexed.version();
+        let index = indexed.index().to_vec();
+
+        // The encoded header sits between the magic number and the block count
+        let mut cursor = Cursor::new(&mmap[..]);
+        cursor.set_position(16);
+        Header::read_version_from(&mut cursor, version)?;
+        let header_range = 16..cursor.position() as usize;
+
+        let len = mmap.len() as u64;
+        if index.iter().any(|entry| {
+            entry
+                .offset
+                .checked_add(entry.length)
+                .map_or(true, |end| end > len)
+        }) {
+            return Err(BinaryError::CorruptedData(
+                "Block index points past the end of the file".to_string(),
+            ));
+        }
+
+        Ok(MappedReader {
+            mmap,
+            header,
+            version,
+            header_range,
+            index,
+        })
+    }
+
+    /// The file header
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Format version detected from the magic number
+    pub fn version(&self) -> FormatVersion {
+        self.version
+    }
+
+    /// The encoded header, borrowed from the mapping
+    pub fn header_bytes(&self) -> &[u8] {
+        &self.mmap[self.header_range.clone()]
+    }
+
+    /// The whole mapped file
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.mmap
+    }
+
+    /// Block index entries, one per block in file order
+    pub fn index(&self) -> &[BlockIndexEntry] {
+        &self.index
+    }
+
+    /// Number of blocks in the file
+    pub fn block_count(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Total number of rows across all blocks
+    pub fn row_count(&self) -> u64 {
+        self.index.iter().map(|entry| entry.row_count as u64).sum()
+    }
+
+    /// Borrow a single block from the mapping without decoding it, after
+    /// checking its checksum
+    pub fn block(&self, block: usize) -> Result<MappedBlock<'_>> {
+        let entry = self.index.get(block).ok_or_else(|| {
+            BinaryError::CorruptedData(format!(
+                "Block {} is out of range for a file with {} blocks",
+                block,
+                self.index.len()
+            ))
+        })?;
+        let bytes = &self.mmap[entry.offset as usize..(entry.offset + entry.length) as usize];
+        let bytes = split_block_checksum(block, bytes)?;
+
+        let mut cursor = Cursor::new(bytes);
+        let row_count = read_u32(&mut cursor)?;
+        if row_count != entry.row_count {
+            return Err(BinaryError::CorruptedData(format!(
+                "Block {} has {} rows but the index records {}",
+                block, row_count, entry.row_count
+            )));
+        }
+        let stats = next_sized(&mut cursor)?;
+        let table = &bytes[cursor.position() as usize..];
+
+        Ok(MappedBlock {
+            row_count,
+            stats,
+            table,
+            compression: &self.header.compression,
+        })
+    }
+
+    /// Decode a single block from the mapping
+    pub fn read_block(&self, block: usize) -> Result<Block> {
+        let mapped = self.block(block)?;
+        Ok(Block {
+            row_count: mapped.row_count,
+            table: mapped.read_table(&Projection::All)?,
+        })
+    }
+}
+
+impl<'a> MappedBlock<'a> {
+    /// The encoded block statistics
+    pub fn stats_bytes(&self) -> &'a [u8] {
+        self.stats
+    }
+
+    /// Decode the block statistics
+    pub fn stats(&self) -> Result<BlockStats> {
+        BlockStats::read_from(&mut Cursor::new(self.stats))
+    }
+
+    /// The encoded table
+    pub fn table_bytes(&self) -> &'a [u8] {
+        self.table
+    }
+
+    /// Decode the projected columns of the table
+    pub fn read_table(&self, projection: &Projection) -> Result<Table> {
+        read_block_table(
+            &mut Cursor::new(self.table),
+            self.row_count,
+            self.compression,
+            projection,
+        )
+    }
+
+    /// Read the data of a binary table
+    pub fn binary_table(&self) -> Result<BinaryTableRef<'a>> {
+        let mut cursor = Cursor::new(self.table);
+        let tag = read_u8(&mut cursor)?;
+        if tag != 0 {
+            return Err(BinaryError::InvalidProjection(
+                "Block does not hold a binary table".to_string(),
+            ));
+        }
+        let default = Default::read_from(&mut cursor)?;
+        let encoding = Encoding::read_from(&mut cursor)?;
+        let data = read_byte_array(&mut cursor, self.compression.binary_data_algorithm())?;
+        Ok(BinaryTableRef {
+            default,
+            encoding,
+            data,
+        })
+    }
+
+    /// Read a row-level binary column by struct field path
+    ///
+    /// Paths name columns the same way as `BlockStats`, starting with `key`
+    /// or `value` for map tables. Only the columns along the path are read.
+    pub fn binary_column(&self, path: &[&str]) -> Result<BinaryColumnRef<'a>> {
+        let (column, compression) = self.column_at(path)?;
+        let mut cursor = Cursor::new(column);
+        let tag = read_u8(&mut cursor)?;
+        if tag == 10 {
+            // Every value is the empty default
+            return match self.read_defaults(&mut cursor)? {
+                (count, ValueSchema::Binary { default, encoding }) => Ok(BinaryColumnRef {
+                    default,
+                    encoding,
+                    lengths: vec![0; count],
+                    data: Cow::Borrowed(&[]),
+                }),
+                _ => Err(BinaryError::InvalidProjection(format!(
+                    "{} is not a binary column",
+                    path.join(".")
+                ))),
+            };
+        }
+        if tag != 3 && tag != 9 {
+            return Err(BinaryError::InvalidProjection(format!(
+                "{} is not a binary column",
+                path.join(".")
+            )));
+        }
+        let default = Default::read_from(&mut cursor)?;
+        let encoding = Encoding::read_from(&mut cursor)?;
+        let (lengths, data) = if tag == 9 {
+            let dictionary = read_dictionary(&mut cursor, compression.strings_algorithm())?;
+            if dictionary.indices.len() != self.row_count as usize {
+                return Err(BinaryError::CorruptedData(format!(
+                    "Dictionary column of {} values in a block of {} rows",
+                    dictionary.indices.len(),
+                    self.row_count
+                )));
+            }
+            let (lengths, data) = dictionary_decode(&dictionary)?;
+            (lengths, Cow::Owned(data))
+        } else {
+            let lengths = read_int_array_usize_compressed(&mut cursor)?;
+            if lengths.len() != self.row_count as usize {
+                return Err(BinaryError::CorruptedData(format!(
+                    "Binary column of {} values in a block of {} rows",
+                    lengths.len(),
+                    self.row_count
+                )));
+            }
+            let data = read_byte_array(&mut cursor, compression.strings_algorithm())?;
+            let total = total_length(&lengths)?;
+            if total != data.len() {
+                return Err(BinaryError::CorruptedData(format!(
+                    "Binary column lengths cover {} bytes but data has {}",
+                    total,
+                    data.len()
+                )));
+            }
+            (lengths, data)
+        };
+        Ok(BinaryColumnRef {
+            default,
+            encoding,
+            lengths,
+            data,
+        })
+    }
+
+    /// Read a row-level int column by struct field path as runs of equal
+    /// values
+    ///
+    /// Run-length encoded columns are handed back without expanding them, so
+    /// aggregates over sorted or low-cardinality columns can work a run at a
+    /// time. Paths are the same as for `binary_column`.
+    pub fn int_runs(&self, path: &[&str]) -> Result<Vec<IntRun>> {
+        let (column, compression) = self.column_at(path)?;
+        let mut cursor = Cursor::new(column);
+        let tag = read_u8(&mut cursor)?;
+        if tag == 10 {
+            // Every value is the zero default, a single run if any
+            return match self.read_defaults(&mut cursor)? {
+                (0, ValueSchema::Int { .. }) => Ok(Vec::new()),
+                (count, ValueSchema::Int { .. }) => Ok(vec![IntRun {
+                    value: 0,
+                    length: count,
+                }]),
+                _ => Err(BinaryError::InvalidProjection(format!(
+                    "{} is not an int column",
+                    path.join(".")
+                ))),
+            };
+        }
+        if tag != 1 {
+            return Err(BinaryError::InvalidProjection(format!(
+                "{} is not an int column",
+                path.join(".")
+            )));
+        }
+        Default::read_from(&mut cursor)?;
+        Encoding::read_from(&mut cursor)?;
+        read_int_runs(&mut cursor, &compression)
+    }
+
+    /// Read the value count and schema of a column storing only defaults,
+    /// after its tag
+    ///
+    /// Row-level columns hold one value per row, so any other count is
+    /// rejected before the caller allocates for it.
+    fn read_defaults(&self, cursor: &mut Cursor<&'a [u8]>) -> Result<(usize, ValueSchema)> {
+        let count = read_u32(cursor)?;
+        if count != self.row_count {
+            return Err(BinaryError::CorruptedData(format!(
+                "Column of {} defaults in a block of {} rows",
+                count, self.row_count
+            )));
+        }
+        Ok((count as usize, ValueSchema::read_from(cursor)?))
+    }
+
+    /// Find the encoded column at a field path, with the compression that
+    /// applies to it
+    fn column_at(&self, path: &[&str]) -> Result<(&'a [u8], Cow<'a, CompressionConfig>)> {
+        let mut cursor = Cursor::new(self.table);
+        let tag = read_u8(&mut cursor)?;
+        Default::read_from(&mut cursor)?;
+        let (column, fields) = match tag {
+            1 => (field_column(next_sized(&mut cursor)?, path, path)?, path),
+            2 => {
+                let key = next_sized(&mut cursor)?;
+                let value = next_sized(&mut cursor)?;
+                match path.first() {
+                    Some(&"key") => (field_column(key, &path[1..], path)?, &path[1..]),
+                    Some(&"value") => (field_column(value, &path[1..], path)?, &path[1..]),
+                    _ => return Err(unknown_field(path)),
+                }
+            }
+            0 => return Err(unknown_field(path)),
+            _ => return Err(BinaryError::InvalidTableTag(tag)),
+        };
+        let compression = fields
+            .iter()
+            .fold(Cow::Borrowed(self.compression), |compression, name| {
+                Cow::Owned(compression.for_field(name).into_owned())
+            });
+        Ok((column, compression))
+    }
+}
+
+impl BinaryColumnRef<'_> {
+    /// Whether the data is borrowed from the mapping
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.data, Cow::Borrowed(_))
+    }
+
+    /// The value of every row
+    pub fn values(&self) -> impl Iterator<Item = &[u8]> {
+        let data: &[u8] = &self.data;
+        self.lengths.iter().scan(0, move |offset, &length| {
+            let value = data.get(*offset..*offset + length)?;
+            *offset += length;
+            Some(value)
+        })
+    }
+}
+
+/// Find the encoded column at a struct field path, passing through reversed
+/// columns
+fn field_column<'a>(column: &'a [u8], rest: &[&str], path: &[&str]) -> Result<&'a [u8]> {
+    let mut cursor = Cursor::new(column);
+    let tag = read_u8(&mut cursor)?;
+    if tag == 8 {
+        return field_column(next_sized(&mut cursor)?, rest, path);
+    }
+    let Some((name, rest)) = rest.split_first() else {
+        return Ok(column);
+    };
+    if tag != 5 {
+        return Err(unknown_field(path));
+    }
+
+    Default::read_from(&mut cursor)?;
+    let field_count = read_u32(&mut cursor)?;
+    for _ in 0..field_count {
+        let field_name = read_string(&mut cursor)?;
+        let field = next_sized(&mut cursor)?;
+        if field_name == *name {
+            return field_column(field, rest, path);
+        }
+    }
+    Err(unknown_field(path))
+}
+
+fn unknown_field(path: &[&str]) -> BinaryError {
+    BinaryError::InvalidProjection(format!("Unknown field: {}", path.join(".")))
+}
+
+/// Take the next length-prefixed section as a slice of the underlying bytes
+fn next_sized<'a>(cursor: &mut Cursor<&'a [u8]>) -> Result<&'a [u8]> {
+    let length = read_u32(cursor)? as usize;
+    take_slice(cursor, length)
+}
+
+fn take_slice<'a>(cursor: &mut Cursor<&'a [u8]>, length: usize) -> Result<&'a [u8]> {
+    let bytes: &'a [u8] = cursor.get_ref();
+    let start = cursor.position() as usize;
+    let slice = start
+        .checked_add(length)
+        .and_then(|end| bytes.get(start..end))
+        .ok_or_else(|| {
+            BinaryError::CorruptedData(format!(
+                "Section of {} bytes truncated after {} bytes",
+                length,
+                bytes.len().saturating_sub(start)
+            ))
+        })?;
+    cursor.set_position((start + length) as u64);
+    Ok(slice)
+}
+
+/// Read a sized byte array, borrowing it when stored uncompressed
+fn read_byte_array<'a>(
+    cursor: &mut Cursor<&'a [u8]>,
+    algorithm: &CompressionAlgorithm,
+) -> Result<Cow<'a, [u8]>> {
+    let uncompressed_size = read_u32(cursor)? as usize;
+    let compressed_size = read_u32(cursor)? as usize;
+    let data = take_slice(cursor, compressed_size)?;
+    match algorithm {
+        CompressionAlgorithm::None => Ok(Cow::Borrowed(data)),
+        _ => Ok(Cow::Owned(decompress_binary_exact(
+            data,
+            algorithm,
+            uncompressed_size,
+        )?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::BinaryFile;
+    use crate::data::{BinaryEncoding, IntEncoding};
+    use crate::logical::{FieldSchema, TableSchema, ValueSchema};
+    use crate::striped::{Column, FieldColumn};
+
+    fn write_temp(name: &str, bytes: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("zbra-mmap-{}-{}", std::process::id(), name));
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_mapped_reader_borrows_uncompressed_columns() {
+        let schema = TableSchema::Array {
+            default: crate::data::Default::Allow,
+            element: Box::new(ValueSchema::Struct {
+                default: crate::data::Default::Allow,
+                fields: vec![
+                    FieldSchema {
+                        name: "id".to_string(),
+                        schema: ValueSchema::Int {
+                            default: crate::data::Default::Allow,
+                            encoding: Encoding::Int(IntEncoding::Int),
+                        },
+                    },
+                    FieldSchema {
+                        name: "name".to_string(),
+                        schema: ValueSchema::Binary {
+                            default: crate::data::Default::Allow,
+                            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+                        },
+                    },
+                ],
+            }),
+        };
+        let table = Table::Array {
+            default: crate::data::Default::Allow,
+            column: Box::new(Column::Struct {
+                default: crate::data::Default::Allow,
+                fields: vec![
+                    FieldColumn {
+                        name: "id".to_string(),
+                        column: Column::Int {
+                            default: crate::data::Default::Allow,
+                            encoding: Encoding::Int(IntEncoding::Int),
+                            values: vec![1, 2, 3],
+                        },
+                    },
+                    FieldColumn {
+                        name: "name".to_string(),
+                        column: Column::Binary {
+                            default: crate::data::Default::Allow,
+                            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+                            lengths: vec![5, 3, 7],
+                            data: b"AliceBobCharlie".to_vec(),
+                        },
+                    },
+                ],
+            }),
+        };
+
+        let uncompressed = CompressionConfig {
+            binary_data: CompressionAlgorithm::None,
+            strings: CompressionAlgorithm::None,
+            columns: Vec::new(),
+            optimization_target: crate::compression::OptimizationTarget::MinimizeSpace,
+        };
+        let file = BinaryFile::new_with_compression(schema.clone(), table.clone(), uncompressed);
+        let path = write_temp("borrowed", &file.to_bytes().unwrap());
+        let reader = MappedReader::open(&path).unwrap();
+
+        assert_eq!(reader.header().schema, schema);
+        assert_eq!(reader.block_count(), 1);
+        assert_eq!(reader.row_count(), 3);
+        assert_eq!(reader.read_block(0).unwrap().table, table);
+
+        let block = reader.block(0).unwrap();
+        assert_eq!(block.stats().unwrap(), BlockStats::compute(&table));
+        let names = block.binary_column(&["name"]).unwrap();
+        assert!(names.is_borrowed());
+        assert_eq!(
+            names.values().collect::<Vec<_>>(),
+            vec![&b"Alice"[..], b"Bob", b"Charlie"]
+        );
+        let mapped = reader.as_bytes().as_ptr_range();
+        assert!(mapped.contains(&names.data.as_ptr()));
+        assert!(mapped.contains(&reader.header_bytes().as_ptr()));
+
+        assert!(block.binary_column(&["id"]).is_err());
+        assert!(block.binary_column(&["missing"]).is_err());
+        assert!(reader.block(1).is_err());
+
+        // Compressed columns are decompressed into owned buffers
+        let compressed = BinaryFile::new(schema, table);
+        let compressed_path = write_temp("compressed", &compressed.to_bytes().unwrap());
+        let reader = MappedReader::open(&compressed_path).unwrap();
+        let names = reader.block(0).unwrap().binary_column(&["name"]).unwrap();
+        assert!(!names.is_borrowed());
+        assert_eq!(&names.data[..], b"AliceBobCharlie");
+
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(compressed_path).unwrap();
+    }
+
+    #[test]
+    fn test_mapped_binary_columns_checked() {
+        // Binary columns whose lengths disagree with the block or with their
+        // data, which the writer does not check but the mapped reader must
+        let table = |lengths: Vec<usize>, data: &[u8]| Table::Array {
+            default: crate::data::Default::Allow,
+            column: Box::new(Column::Struct {
+                default: crate::data::Default::Allow,
+                fields: vec![
+                    FieldColumn {
+                        name: "id".to_string(),
+                        column: Column::Int {
+                            default: crate::data::Default::Allow,
+                            encoding: Encoding::Int(IntEncoding::Int),
+                            values: vec![1, 2, 3],
+                        },
+                    },
+                    FieldColumn {
+                        name: "name".to_string(),
+                        column: Column::Binary {
+                            default: crate::data::Default::Allow,
+                            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+                            lengths,
+                            data: data.to_vec(),
+                        },
+                    },
+                ],
+            }),
+        };
+
+        for (name, table, message) in [
+            ("short", table(vec![5, 3], b"AliceBob"), "2 values"),
+            (
+                "padded",
+                table(vec![5, 3, 7], b"AliceBobCharlie!"),
+                "15 bytes",
+            ),
+        ] {
+            let bytes = BinaryFile::new(table.schema(), table).to_bytes().unwrap();
+            let path = write_temp(name, &bytes);
+            let reader = MappedReader::open(&path).unwrap();
+            match reader.block(0).unwrap().binary_column(&["name"]) {
+                Err(BinaryError::CorruptedData(error)) => assert!(error.contains(message)),
+                other => panic!("expected corrupted data, got {:?}", other.map(|_| ())),
+            }
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_mapped_int_runs() {
+        let int_schema = ValueSchema::Int {
+            default: crate::data::Default::Allow,
+            encoding: Encoding::Int(IntEncoding::Int),
+        };
+        let schema = TableSchema::Array {
+            default: crate::data::Default::Allow,
+            element: Box::new(int_schema),
+        };
+        let values: Vec<i64> = [3, 1, 3].iter().flat_map(|&v| vec![v; 400]).collect();
+        let table = Table::Array {
+            default: crate::data::Default::Allow,
+            column: Box::new(Column::Int {
+                default: crate::data::Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+                values,
+            }),
+        };
+
+        let path = write_temp("runs", &BinaryFile::new(schema, table).to_bytes().unwrap());
+        let reader = MappedReader::open(&path).unwrap();
+        let block = reader.block(0).unwrap();
+        let runs = block.int_runs(&[]).unwrap();
+        assert_eq!(
+            runs.iter()
+                .map(|run| (run.value, run.length))
+                .collect::<Vec<_>>(),
+            vec![(3, 400), (1, 400), (3, 400)]
+        );
+        let sum: i64 = runs.iter().map(|run| run.value * run.length as i64).sum();
+        assert_eq!(sum, 2800);
+        assert!(block.int_runs(&["missing"]).is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_mapped_all_default_columns() {
+        let rows = 500;
+        let table = Table::Array {
+            default: crate::data::Default::Allow,
+            column: Box::new(Column::Struct {
+                default: crate::data::Default::Allow,
+                fields: vec![
+                    FieldColumn {
+                        name: "count".to_string(),
+                        column: Column::Int {
+                            default: crate::data::Default::Allow,
+                            encoding: Encoding::Int(IntEncoding::Int),
+                            values: vec![0; rows],
+                        },
+                    },
+                    FieldColumn {
+                        name: "label".to_string(),
+                        column: Column::Binary {
+                            default: crate::data::Default::Allow,
+                            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+                            lengths: vec![0; rows],
+                            data: Vec::new(),
+                        },
+                    },
+                ],
+            }),
+        };
+
+        let bytes = BinaryFile::new(table.schema(), table).to_bytes().unwrap();
+        let path = write_temp("defaults", &bytes);
+        let reader = MappedReader::open(&path).unwrap();
+        let block = reader.block(0).unwrap();
+
+        let runs = block.int_runs(&["count"]).unwrap();
+        assert_eq!(
+            runs.iter()
+                .map(|run| (run.value, run.length))
+                .collect::<Vec<_>>(),
+            vec![(0, rows)]
+        );
+
+        let label = block.binary_column(&["label"]).unwrap();
+        assert_eq!(label.encoding, Encoding::Binary(BinaryEncoding::Utf8));
+        assert_eq!(label.values().count(), rows);
+        assert!(label.values().all(<[u8]>::is_empty));
+
+        // The stored schema still decides the column type
+        assert!(block.int_runs(&["label"]).is_err());
+        assert!(block.binary_column(&["count"]).is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
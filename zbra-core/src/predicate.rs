@@ -0,0 +1,594 @@
+// Predicates - row filters over field paths, checked against block statistics
+
+use crate::error::{BinaryError, Result};
+use crate::stats::{BlockStats, ValueStats};
+use crate::striped::{Column, Table};
+
+/// Row filter over field paths
+///
+/// Parsed from expressions such as `timestamp >= 1700000000000 AND level == tag 3`.
+/// Paths name row-level columns the same way as `BlockStats`: struct field
+/// names joined with `.`, starting with `key` or `value` for map tables.
+/// `AND` binds tighter than `OR`, and parentheses group.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate {
+    Compare {
+        path: Vec<String>,
+        op: CompareOp,
+        literal: Literal,
+    },
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+}
+
+/// Comparison operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Value a column is compared against
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Int(i64),
+    Double(f64),
+    /// Enum tag, written `tag N`
+    Tag(u32),
+    /// Binary value, written as a double-quoted string
+    Binary(Vec<u8>),
+}
+
+impl Predicate {
+    /// Parse a predicate expression
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+        };
+        let predicate = parser.parse_or()?;
+        if parser.position != tokens.len() {
+            return Err(BinaryError::InvalidPredicate(format!(
+                "Unexpected {:?} in `{}`",
+                tokens[parser.position], input
+            )));
+        }
+        Ok(predicate)
+    }
+
+    /// Whether any row of a block with these statistics could match
+    ///
+    /// Columns without statistics are assumed to match, so `false` means the
+    /// block can be skipped without decoding it.
+    pub fn may_match(&self, stats: &BlockStats) -> bool {
+        match self {
+            Predicate::And(left, right) => left.may_match(stats) && right.may_match(stats),
+            Predicate::Or(left, right) => left.may_match(stats) || right.may_match(stats),
+            Predicate::Compare { path, op, literal } => {
+                let path: Vec<&str> = path.iter().map(String::as_str).collect();
+                match stats.get(&path) {
+                    Some(values) => stats_may_match(values, *op, literal),
+                    None => true,
+                }
+            }
+        }
+    }
+
+    /// Evaluate the predicate for every row of a striped table
+    pub fn matches(&self, table: &Table) -> Result<Vec<bool>> {
+        match self {
+            Predicate::And(left, right) => {
+                let left = left.matches(table)?;
+                let right = right.matches(table)?;
+                Ok(left.iter().zip(right).map(|(&l, r)| l && r).collect())
+            }
+            Predicate::Or(left, right) => {
+                let left = left.matches(table)?;
+                let right = right.matches(table)?;
+                Ok(left.iter().zip(right).map(|(&l, r)| l || r).collect())
+            }
+            Predicate::Compare { path, op, literal } => {
+                let column = table_column(table, path)?;
+                compare_column(column, *op, literal).ok_or_else(|| {
+                    BinaryError::InvalidPredicate(format!(
+                        "Cannot compare {} with {:?} using {:?}",
+                        path.join("."),
+                        literal,
+                        op
+                    ))
+                })
+            }
+        }
+    }
+}
+
+fn stats_may_match(stats: &ValueStats, op: CompareOp, literal: &Literal) -> bool {
+    match (stats, literal) {
+        (ValueStats::Int { min, max, .. }, Literal::Int(x)) => range_may_match(min, max, op, x),
+        (ValueStats::Int { min, max, .. }, Literal::Double(x)) => {
+            range_may_match(&(*min as f64), &(*max as f64), op, x)
+        }
+        (ValueStats::Double { min, max, .. }, Literal::Double(x)) => {
+            range_may_match(min, max, op, x)
+        }
+        (ValueStats::Double { min, max, .. }, Literal::Int(x)) => {
+            range_may_match(min, max, op, &(*x as f64))
+        }
+        (
+            ValueStats::Binary {
+                min_length,
+                max_length,
+                ..
+            },
+            Literal::Binary(x),
+        ) if op == CompareOp::Eq => (*min_length..=*max_length).contains(&(x.len() as u64)),
+        (ValueStats::Enum { tag_counts }, Literal::Tag(x)) => match op {
+            CompareOp::Eq => tag_counts.iter().any(|(tag, _)| tag == x),
+            CompareOp::Ne => tag_counts.iter().any(|(tag, _)| tag != x),
+            _ => true,
+        },
+        _ => true,
+    }
+}
+
+fn range_may_match<T: PartialOrd>(min: &T, max: &T, op: CompareOp, x: &T) -> bool {
+    match op {
+        CompareOp::Eq => min <= x && x <= max,
+        CompareOp::Ne => !(min == x && max == x),
+        CompareOp::Lt => min < x,
+        CompareOp::Le => min <= x,
+        CompareOp::Gt => max > x,
+        CompareOp::Ge => max >= x,
+    }
+}
+
+fn compare<T: PartialOrd>(value: &T, op: CompareOp, x: &T) -> bool {
+    match op {
+        CompareOp::Eq => value == x,
+        CompareOp::Ne => value != x,
+        CompareOp::Lt => value < x,
+        CompareOp::Le => value <= x,
+        CompareOp::Gt => value > x,
+        CompareOp::Ge => value >= x,
+    }
+}
+
+/// Row-level column of a table named by a field path
+fn table_column<'a>(table: &'a Table, path: &[String]) -> Result<&'a Column> {
+    match table {
+        Table::Array { column, .. } => field_column(column, path, path),
+        Table::Map {
+            key_column,
+            value_column,
+            ..
+        } => match path.first().map(String::as_str) {
+            Some("key") => field_column(key_column, &path[1..], path),
+            Some("value") => field_column(value_column, &path[1..], path),
+            _ => Err(unknown_path(path)),
+        },
+        Table::Binary { .. } => Err(unknown_path(path)),
+    }
+}
+
+fn field_column<'a>(column: &'a Column, rest: &[String], path: &[String]) -> Result<&'a Column> {
+    match column {
+        Column::Reversed { inner } => field_column(inner, rest, path),
+        _ if rest.is_empty() => Ok(column),
+        Column::Struct { fields, .. } => {
+            let field = fields
+                .iter()
+                .find(|field| field.name == rest[0])
+                .ok_or_else(|| unknown_path(path))?;
+            field_column(&field.column, &rest[1..], path)
+        }
+        _ => Err(unknown_path(path)),
+    }
+}
+
+fn unknown_path(path: &[String]) -> BinaryError {
+    BinaryError::InvalidPredicate(format!("No row-level column at `{}`", path.join(".")))
+}
+
+/// Compare every row of a column, `None` if the literal does not apply
+fn compare_column(column: &Column, op: CompareOp, literal: &Literal) -> Option<Vec<bool>> {
+    match (column, literal) {
+        (Column::Int { values, .. }, Literal::Int(x)) => {
+            Some(values.iter().map(|v| compare(v, op, x)).collect())
+        }
+        (Column::Int { values, .. }, Literal::Double(x)) => Some(
+            values
+                .iter()
+                .map(|&v| compare(&(v as f64), op, x))
+                .collect(),
+        ),
+        (Column::Double { values, .. }, Literal::Double(x)) => {
+            Some(values.iter().map(|v| compare(v, op, x)).collect())
+        }
+        (Column::Double { values, .. }, Literal::Int(x)) => Some(
+            values
+                .iter()
+                .map(|v| compare(v, op, &(*x as f64)))
+                .collect(),
+        ),
+        (Column::Binary { lengths, data, .. }, Literal::Binary(x))
+            if matches!(op, CompareOp::Eq | CompareOp::Ne) =>
+        {
+            let mut offset = 0;
+            Some(
+                lengths
+                    .iter()
+                    .map(|&length| {
+                        let value = &data[offset..offset + length];
+                        offset += length;
+                        compare(&value, op, &x.as_slice())
+                    })
+                    .collect(),
+            )
+        }
+        (Column::Enum { tags, .. }, Literal::Tag(x))
+            if matches!(op, CompareOp::Eq | CompareOp::Ne) =>
+        {
+            Some(tags.iter().map(|tag| compare(tag, op, x)).collect())
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Path(Vec<String>),
+    Op(CompareOp),
+    Literal(Literal),
+    Tag,
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let invalid = |msg: String| BinaryError::InvalidPredicate(msg);
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' || c == ')' {
+            chars.next();
+            tokens.push(if c == '(' { Token::Open } else { Token::Close });
+        } else if "=!<>".contains(c) {
+            chars.next();
+            let eq = chars.next_if(|&(_, next)| next == '=').is_some();
+            let op = match (c, eq) {
+                ('=', true) => CompareOp::Eq,
+                ('!', true) => CompareOp::Ne,
+                ('<', false) => CompareOp::Lt,
+                ('<', true) => CompareOp::Le,
+                ('>', false) => CompareOp::Gt,
+                ('>', true) => CompareOp::Ge,
+                _ => return Err(invalid(format!("Unknown operator at offset {}", start))),
+            };
+            tokens.push(Token::Op(op));
+        } else if c == '"' {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '"')) => break,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, escaped)) => value.push(escaped),
+                        None => break,
+                    },
+                    Some((_, other)) => value.push(other),
+                    None => {
+                        return Err(invalid(format!("Unterminated string at offset {}", start)))
+                    }
+                }
+            }
+            tokens.push(Token::Literal(Literal::Binary(value.into_bytes())));
+        } else if c.is_ascii_digit() || c == '-' {
+            chars.next();
+            let mut end = start + c.len_utf8();
+            while let Some(&(i, next)) = chars.peek() {
+                let exponent_sign =
+                    (next == '-' || next == '+') && input[..i].ends_with(['e', 'E']);
+                if !(next.is_ascii_alphanumeric() || next == '.' || exponent_sign) {
+                    break;
+                }
+                chars.next();
+                end = i + next.len_utf8();
+            }
+            let text = &input[start..end];
+            let literal = match text.parse::<i64>() {
+                Ok(value) => Literal::Int(value),
+                Err(_) => Literal::Double(
+                    text.parse::<f64>()
+                        .map_err(|_| invalid(format!("Invalid number `{}`", text)))?,
+                ),
+            };
+            tokens.push(Token::Literal(literal));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some((i, next)) =
+                chars.next_if(|&(_, next)| next.is_alphanumeric() || next == '_' || next == '.')
+            {
+                end = i + next.len_utf8();
+            }
+            let word = &input[start..end];
+            tokens.push(match word {
+                "AND" | "and" => Token::And,
+                "OR" | "or" => Token::Or,
+                "tag" => Token::Tag,
+                _ => {
+                    let path: Vec<String> = word.split('.').map(str::to_string).collect();
+                    if path.iter().any(String::is_empty) {
+                        return Err(invalid(format!("Invalid field path `{}`", word)));
+                    }
+                    Token::Path(path)
+                }
+            });
+        } else {
+            return Err(invalid(format!(
+                "Unexpected character `{}` at offset {}",
+                c, start
+            )));
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.tokens.get(self.position) == Some(token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Predicate> {
+        let mut predicate = self.parse_and()?;
+        while self.eat(&Token::Or) {
+            predicate = Predicate::Or(Box::new(predicate), Box::new(self.parse_and()?));
+        }
+        Ok(predicate)
+    }
+
+    fn parse_and(&mut self) -> Result<Predicate> {
+        let mut predicate = self.parse_compare()?;
+        while self.eat(&Token::And) {
+            predicate = Predicate::And(Box::new(predicate), Box::new(self.parse_compare()?));
+        }
+        Ok(predicate)
+    }
+
+    fn parse_compare(&mut self) -> Result<Predicate> {
+        if self.eat(&Token::Open) {
+            let predicate = self.parse_or()?;
+            return if self.eat(&Token::Close) {
+                Ok(predicate)
+            } else {
+                Err(BinaryError::InvalidPredicate("Expected `)`".to_string()))
+            };
+        }
+
+        let path = match self.next() {
+            Some(Token::Path(path)) => path.clone(),
+            other => {
+                return Err(BinaryError::InvalidPredicate(format!(
+                    "Expected a field path, found {:?}",
+                    other
+                )))
+            }
+        };
+        let op = match self.next() {
+            Some(Token::Op(op)) => *op,
+            other => {
+                return Err(BinaryError::InvalidPredicate(format!(
+                    "Expected a comparison after `{}`, found {:?}",
+                    path.join("."),
+                    other
+                )))
+            }
+        };
+        let literal = match self.next() {
+            Some(Token::Literal(literal)) => literal.clone(),
+            Some(Token::Tag) => match self.next() {
+                Some(Token::Literal(Literal::Int(tag))) => {
+                    Literal::Tag(u32::try_from(*tag).map_err(|_| {
+                        BinaryError::InvalidPredicate(format!("Invalid enum tag {}", tag))
+                    })?)
+                }
+                other => {
+                    return Err(BinaryError::InvalidPredicate(format!(
+                        "Expected an enum tag, found {:?}",
+                        other
+                    )))
+                }
+            },
+            other => {
+                return Err(BinaryError::InvalidPredicate(format!(
+                    "Expected a value after `{}`, found {:?}",
+                    path.join("."),
+                    other
+                )))
+            }
+        };
+
+        if matches!(literal, Literal::Tag(_) | Literal::Binary(_))
+            && !matches!(op, CompareOp::Eq | CompareOp::Ne)
+        {
+            return Err(BinaryError::InvalidPredicate(format!(
+                "Only == and != apply to {:?}",
+                literal
+            )));
+        }
+
+        Ok(Predicate::Compare { path, op, literal })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{BinaryEncoding, Default, Encoding, IntEncoding};
+    use crate::striped::{FieldColumn, VariantColumn};
+
+    fn compare(path: &str, op: CompareOp, literal: Literal) -> Predicate {
+        Predicate::Compare {
+            path: path.split('.').map(str::to_string).collect(),
+            op,
+            literal,
+        }
+    }
+
+    fn events(timestamps: Vec<i64>, levels: Vec<u32>, hosts: &[&str]) -> Table {
+        let variant = |name: &str, tag: u32| VariantColumn {
+            name: name.to_string(),
+            tag,
+            column: Column::Unit {
+                count: levels.iter().filter(|&&t| t == tag).count(),
+            },
+        };
+        Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Struct {
+                default: Default::Allow,
+                fields: vec![
+                    FieldColumn {
+                        name: "timestamp".to_string(),
+                        column: Column::Int {
+                            default: Default::Allow,
+                            encoding: Encoding::Int(IntEncoding::TimeMilliseconds),
+                            values: timestamps,
+                        },
+                    },
+                    FieldColumn {
+                        name: "level".to_string(),
+                        column: Column::Enum {
+                            default: Default::Allow,
+                            tags: levels.clone(),
+                            variants: vec![variant("info", 0), variant("error", 3)],
+                        },
+                    },
+                    FieldColumn {
+                        name: "host".to_string(),
+                        column: Column::Binary {
+                            default: Default::Allow,
+                            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+                            lengths: hosts.iter().map(|h| h.len()).collect(),
+                            data: hosts.concat().into_bytes(),
+                        },
+                    },
+                ],
+            }),
+        }
+    }
+
+    #[test]
+    fn test_parse_predicate() {
+        assert_eq!(
+            Predicate::parse("timestamp >= 1000 AND level == tag 3").unwrap(),
+            Predicate::And(
+                Box::new(compare("timestamp", CompareOp::Ge, Literal::Int(1000))),
+                Box::new(compare("level", CompareOp::Eq, Literal::Tag(3))),
+            )
+        );
+        assert_eq!(
+            Predicate::parse("a.b < -1.5 or (c != \"x\" and d > 2)").unwrap(),
+            Predicate::Or(
+                Box::new(compare("a.b", CompareOp::Lt, Literal::Double(-1.5))),
+                Box::new(Predicate::And(
+                    Box::new(compare("c", CompareOp::Ne, Literal::Binary(b"x".to_vec()))),
+                    Box::new(compare("d", CompareOp::Gt, Literal::Int(2))),
+                )),
+            )
+        );
+
+        for invalid in [
+            "",
+            "a >",
+            "a = 1",
+            "a == tag x",
+            "(a == 1",
+            "a < tag 1",
+            "a == 1 b",
+        ] {
+            assert!(
+                matches!(
+                    Predicate::parse(invalid),
+                    Err(BinaryError::InvalidPredicate(_))
+                ),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_predicate_may_match_stats() {
+        let stats = BlockStats::compute(&events(
+            vec![100, 200, 300],
+            vec![0, 0, 3],
+            &["a", "bb", "c"],
+        ));
+
+        let may_match = |input: &str| Predicate::parse(input).unwrap().may_match(&stats);
+        assert!(may_match("timestamp >= 300"));
+        assert!(!may_match("timestamp > 300"));
+        assert!(!may_match("timestamp < 100"));
+        assert!(may_match("timestamp == 150"));
+        assert!(!may_match("timestamp == 301"));
+        assert!(may_match("level == tag 3"));
+        assert!(!may_match("level == tag 1"));
+        assert!(!may_match("host == \"ccc\""));
+        assert!(!may_match("timestamp > 300 AND level == tag 3"));
+        assert!(may_match("timestamp > 300 OR level == tag 3"));
+        // Columns without statistics cannot rule a block out
+        assert!(may_match("missing == 1"));
+    }
+
+    #[test]
+    fn test_predicate_matches_rows() {
+        let table = events(vec![100, 200, 300], vec![0, 3, 3], &["a", "bb", "c"]);
+
+        let matches = |input: &str| Predicate::parse(input).unwrap().matches(&table).unwrap();
+        assert_eq!(matches("timestamp >= 200"), vec![false, true, true]);
+        assert_eq!(
+            matches("level == tag 3 AND host != \"c\""),
+            vec![false, true, false]
+        );
+        assert_eq!(
+            matches("timestamp < 150 OR host == \"c\""),
+            vec![true, false, true]
+        );
+
+        assert!(Predicate::parse("missing == 1")
+            .unwrap()
+            .matches(&table)
+            .is_err());
+        assert!(Predicate::parse("level == 3")
+            .unwrap()
+            .matches(&table)
+            .is_err());
+    }
+}
@@ -0,0 +1,277 @@
+// Block statistics - per-column summaries stored alongside each binary block
+
+use crate::striped::{Column, Table};
+
+/// Statistics for the row-level columns of a single block
+///
+/// Columns are identified by their struct field path from the table's row
+/// column (an empty path for a non-struct row column). Map tables prefix the
+/// key and value columns with `key` and `value`. Only columns with one value
+/// per row are summarised: array elements, nested tables and enum variant
+/// payloads are not.
+///
+/// Every block of format version 2 and up stores its statistics. Version 1
+/// blocks have none, so predicates over them are checked row by row.
+#[derive(Debug, Clone, PartialEq, std::default::Default)]
+pub struct BlockStats {
+    pub columns: Vec<ColumnStats>,
+}
+
+/// Statistics for a single column within a block
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+    pub path: Vec<String>,
+    pub values: ValueStats,
+}
+
+/// Summary of the values held by a column
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueStats {
+    Int {
+        min: i64,
+        max: i64,
+        default_count: u64,
+    },
+    /// Doubles containing NaN have no ordering, so min/max are only recorded
+    /// for NaN-free columns
+    Double {
+        min: f64,
+        max: f64,
+        default_count: u64,
+    },
+    Binary {
+        min_length: u64,
+        max_length: u64,
+        default_count: u64,
+    },
+    /// Row count per tag, in ascending tag order
+    Enum { tag_counts: Vec<(u32, u64)> },
+}
+
+impl BlockStats {
+    /// Compute statistics for every row-level column of a striped table
+    pub fn compute(table: &Table) -> Self {
+        let mut columns = Vec::new();
+        match table {
+            Table::Binary { .. } => {}
+            Table::Array { column, .. } => collect(column, &mut Vec::new(), &mut columns),
+            Table::Map {
+                key_column,
+                value_column,
+                ..
+            } => {
+                collect(key_column, &mut vec!["key".to_string()], &mut columns);
+                collect(value_column, &mut vec!["value".to_string()], &mut columns);
+            }
+        }
+        BlockStats { columns }
+    }
+
+    /// Look up the statistics for a field path
+    pub fn get(&self, path: &[&str]) -> Option<&ValueStats> {
+        self.columns
+            .iter()
+            .find(|column| {
+                column
+                    .path
+                    .iter()
+                    .map(String::as_str)
+                    .eq(path.iter().copied())
+            })
+            .map(|column| &column.values)
+    }
+}
+
+impl ValueStats {
+    /// Statistics for an int column, `None` when the column is empty
+    pub fn for_ints(values: &[i64]) -> Option<Self> {
+        let min = *values.iter().min()?;
+        let max = *values.iter().max()?;
+        let default_count = values.iter().filter(|&&v| v == 0).count() as u64;
+        Some(ValueStats::Int {
+            min,
+            max,
+            default_count,
+        })
+    }
+
+    /// Statistics for a double column, `None` when the column is empty or
+    /// contains NaN
+    pub fn for_doubles(values: &[f64]) -> Option<Self> {
+        if values.is_empty() || values.iter().any(|v| v.is_nan()) {
+            return None;
+        }
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let default_count = values.iter().filter(|&&v| v == 0.0).count() as u64;
+        Some(ValueStats::Double {
+            min,
+            max,
+            default_count,
+        })
+    }
+
+    /// Statistics for a binary column from its value lengths, `None` when the
+    /// column is empty
+    pub fn for_binary_lengths(lengths: &[usize]) -> Option<Self> {
+        let min_length = *lengths.iter().min()? as u64;
+        let max_length = *lengths.iter().max()? as u64;
+        let default_count = lengths.iter().filter(|&&l| l == 0).count() as u64;
+        Some(ValueStats::Binary {
+            min_length,
+            max_length,
+            default_count,
+        })
+    }
+
+    /// Statistics for an enum column from its tags, `None` when the column is
+    /// empty
+    pub fn for_enum_tags(tags: &[u32]) -> Option<Self> {
+        if tags.is_empty() {
+            return None;
+        }
+        let mut tag_counts: Vec<(u32, u64)> = Vec::new();
+        for &tag in tags {
+            match tag_counts.binary_search_by_key(&tag, |&(t, _)| t) {
+                Ok(index) => tag_counts[index].1 += 1,
+                Err(index) => tag_counts.insert(index, (tag, 1)),
+            }
+        }
+        Some(ValueStats::Enum { tag_counts })
+    }
+}
+
+fn collect(column: &Column, path: &mut Vec<String>, out: &mut Vec<ColumnStats>) {
+    let values = match column {
+        Column::Int { values, .. } => ValueStats::for_ints(values),
+        Column::Double { values, .. } => ValueStats::for_doubles(values),
+        Column::Binary { lengths, .. } => ValueStats::for_binary_lengths(lengths),
+        Column::Enum { tags, .. } => ValueStats::for_enum_tags(tags),
+        Column::Struct { fields, .. } => {
+            for field in fields {
+                path.push(field.name.clone());
+                collect(&field.column, path, out);
+                path.pop();
+            }
+            None
+        }
+        Column::Reversed { inner } => {
+            collect(inner, path, out);
+            None
+        }
+        // Arrays, maps and nested tables hold a variable number of values per row
+        Column::Unit { .. } | Column::Array { .. } | Column::Map { .. } | Column::Nested { .. } => {
+            None
+        }
+    };
+
+    if let Some(values) = values {
+        out.push(ColumnStats {
+            path: path.clone(),
+            values,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{BinaryEncoding, Default, Encoding, IntEncoding};
+    use crate::striped::{FieldColumn, VariantColumn};
+
+    #[test]
+    fn test_block_stats_compute() {
+        let table = Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Struct {
+                default: Default::Allow,
+                fields: vec![
+                    FieldColumn {
+                        name: "timestamp".to_string(),
+                        column: Column::Int {
+                            default: Default::Allow,
+                            encoding: Encoding::Int(IntEncoding::TimeMilliseconds),
+                            values: vec![30, 10, 20, 0],
+                        },
+                    },
+                    FieldColumn {
+                        name: "value".to_string(),
+                        column: Column::Double {
+                            default: Default::Allow,
+                            values: vec![1.5, -2.0, 0.0, 0.0],
+                        },
+                    },
+                    FieldColumn {
+                        name: "host".to_string(),
+                        column: Column::Binary {
+                            default: Default::Allow,
+                            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+                            lengths: vec![1, 0, 3, 2],
+                            data: b"abcdef".to_vec(),
+                        },
+                    },
+                    FieldColumn {
+                        name: "level".to_string(),
+                        column: Column::Enum {
+                            default: Default::Allow,
+                            tags: vec![3, 0, 3, 3],
+                            variants: vec![
+                                VariantColumn {
+                                    name: "info".to_string(),
+                                    tag: 0,
+                                    column: Column::Unit { count: 1 },
+                                },
+                                VariantColumn {
+                                    name: "error".to_string(),
+                                    tag: 3,
+                                    column: Column::Unit { count: 3 },
+                                },
+                            ],
+                        },
+                    },
+                ],
+            }),
+        };
+
+        let stats = BlockStats::compute(&table);
+        assert_eq!(stats.columns.len(), 4);
+        assert_eq!(
+            stats.get(&["timestamp"]),
+            Some(&ValueStats::Int {
+                min: 0,
+                max: 30,
+                default_count: 1
+            })
+        );
+        assert_eq!(
+            stats.get(&["value"]),
+            Some(&ValueStats::Double {
+                min: -2.0,
+                max: 1.5,
+                default_count: 2
+            })
+        );
+        assert_eq!(
+            stats.get(&["host"]),
+            Some(&ValueStats::Binary {
+                min_length: 0,
+                max_length: 3,
+                default_count: 1
+            })
+        );
+        assert_eq!(
+            stats.get(&["level"]),
+            Some(&ValueStats::Enum {
+                tag_counts: vec![(0, 1), (3, 3)]
+            })
+        );
+        assert_eq!(stats.get(&["missing"]), None);
+    }
+
+    #[test]
+    fn test_block_stats_skip_empty_and_nan() {
+        assert_eq!(ValueStats::for_ints(&[]), None);
+        assert_eq!(ValueStats::for_doubles(&[1.0, f64::NAN]), None);
+        assert_eq!(ValueStats::for_enum_tags(&[]), None);
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::data::{Default, Encoding, Field, Table as LogicalTable, Value};
 use crate::error::{ConversionError, StripedError};
-use crate::logical::{TableSchema, ValueSchema};
+use crate::logical::{FieldIndex, FieldSchema, TableSchema, ValueSchema, VariantSchema};
 
 /// Striped table representation - columnar storage
 #[derive(Debug, Clone, PartialEq)]
@@ -65,6 +65,12 @@ pub enum Column {
     Reversed {
         inner: Box<Column>,
     },
+    Map {
+        default: Default,
+        lengths: Vec<usize>,
+        key: Box<Column>,
+        value: Box<Column>,
+    },
 }
 
 /// Field in a striped struct
@@ -155,7 +161,7 @@ impl Table {
                     }));
                 }
 
-                let pairs = keys.into_iter().zip(values.into_iter()).collect();
+                let pairs = keys.into_iter().zip(values).collect();
                 Ok(LogicalTable::Map(pairs))
             }
         }
@@ -175,6 +181,84 @@ impl Table {
             Table::Map { key_column, .. } => key_column.row_count(),
         }
     }
+
+    /// Rebuild the schema the table was striped against
+    pub fn schema(&self) -> TableSchema {
+        match self {
+            Table::Binary {
+                default, encoding, ..
+            } => TableSchema::Binary {
+                default: default.clone(),
+                encoding: encoding.clone(),
+            },
+            Table::Array { default, column } => TableSchema::Array {
+                default: default.clone(),
+                element: Box::new(column.schema()),
+            },
+            Table::Map {
+                default,
+                key_column,
+                value_column,
+            } => TableSchema::Map {
+                default: default.clone(),
+                key: Box::new(key_column.schema()),
+                value: Box::new(value_column.schema()),
+            },
+        }
+    }
+
+    /// Check the row-count invariants of every column in the table
+    ///
+    /// Violations are reported with the path of the offending column, e.g.
+    /// `user.tags.element`, where `<table>` is the table itself.
+    pub fn validate(&self) -> Result<(), StripedError> {
+        self.validate_at("")
+    }
+
+    fn validate_at(&self, path: &str) -> Result<(), StripedError> {
+        match self {
+            Table::Binary { .. } => Ok(()),
+            Table::Array { column, .. } => column.validate_at(path),
+            Table::Map {
+                key_column,
+                value_column,
+                ..
+            } => {
+                key_column.validate_at(&child_path(path, "key"))?;
+                value_column.validate_at(&child_path(path, "value"))?;
+                let (keys, values) = (key_column.row_count(), value_column.row_count());
+                if keys != values {
+                    return Err(invariant_violation(
+                        path,
+                        format!("map has {} keys but {} values", keys, values),
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Keep only the rows whose entry in `mask` is true
+    ///
+    /// Binary tables are a single value and are returned unchanged.
+    pub fn select(&self, mask: &[bool]) -> Table {
+        match self {
+            Table::Binary { .. } => self.clone(),
+            Table::Array { default, column } => Table::Array {
+                default: default.clone(),
+                column: Box::new(column.select(mask)),
+            },
+            Table::Map {
+                default,
+                key_column,
+                value_column,
+            } => Table::Map {
+                default: default.clone(),
+                key_column: Box::new(key_column.select(mask)),
+                value_column: Box::new(value_column.select(mask)),
+            },
+        }
+    }
 }
 
 /// Convert logical values to striped columns
@@ -294,41 +378,49 @@ impl Column {
                     ));
                 }
 
-                let mut field_columns = Vec::new();
-
-                for field_schema in fields {
-                    let mut field_values = Vec::new();
+                // Scatter each row's fields into per-field value lists in one
+                // pass, matching them by name rather than position
+                let index = FieldIndex::new(fields);
+                let mut field_values: Vec<Vec<Value>> =
+                    vec![Vec::with_capacity(values.len()); fields.len()];
 
-                    for value in values {
-                        match value {
-                            Value::Struct(struct_fields) => {
-                                if let Some(field) =
-                                    struct_fields.iter().find(|f| f.name == field_schema.name)
-                                {
-                                    field_values.push(field.value.clone());
-                                } else {
-                                    return Err(ConversionError::Schema(
-                                        crate::error::SchemaError::MissingField(
-                                            field_schema.name.clone(),
-                                        ),
-                                    ));
-                                }
-                            }
-                            _ => {
-                                return Err(ConversionError::Schema(
-                                    crate::error::SchemaError::TypeMismatch {
-                                        expected: "struct".to_string(),
-                                        actual: format!("{:?}", value),
-                                    },
-                                ))
+                for (row, value) in values.iter().enumerate() {
+                    let Value::Struct(struct_fields) = value else {
+                        return Err(ConversionError::Schema(
+                            crate::error::SchemaError::TypeMismatch {
+                                expected: "struct".to_string(),
+                                actual: format!("{:?}", value),
+                            },
+                        ));
+                    };
+                    for field in struct_fields {
+                        if let Some(position) = index.position(&field.name) {
+                            // The first occurrence of a repeated field wins
+                            if field_values[position].len() == row {
+                                field_values[position].push(field.value.clone());
                             }
                         }
                     }
+                    for (field_schema, column_values) in fields.iter().zip(&mut field_values) {
+                        if column_values.len() > row {
+                            continue;
+                        }
+                        if field_schema.schema.default_policy() == Default::Allow {
+                            // Optional fields missing from a row take their default
+                            column_values.push(Value::default_for_schema(&field_schema.schema));
+                        } else {
+                            return Err(ConversionError::Schema(
+                                crate::error::SchemaError::MissingField(field_schema.name.clone()),
+                            ));
+                        }
+                    }
+                }
 
-                    let field_column = Column::from_values(&field_schema.schema, &field_values)?;
+                let mut field_columns = Vec::with_capacity(fields.len());
+                for (field_schema, column_values) in fields.iter().zip(&field_values) {
                     field_columns.push(FieldColumn {
                         name: field_schema.name.clone(),
-                        column: field_column,
+                        column: Column::from_values(&field_schema.schema, column_values)?,
                     });
                 }
 
@@ -511,6 +603,44 @@ impl Column {
                     inner: Box::new(inner_column),
                 })
             }
+            ValueSchema::Map {
+                default,
+                key,
+                value: value_schema,
+            } => {
+                let mut lengths = Vec::new();
+                let mut all_keys = Vec::new();
+                let mut all_values = Vec::new();
+
+                for value in values {
+                    match value {
+                        Value::Map(pairs) => {
+                            lengths.push(pairs.len());
+                            for (k, v) in pairs {
+                                all_keys.push(k.clone());
+                                all_values.push(v.clone());
+                            }
+                        }
+                        _ => {
+                            return Err(ConversionError::Schema(
+                                crate::error::SchemaError::TypeMismatch {
+                                    expected: "map".to_string(),
+                                    actual: format!("{:?}", value),
+                                },
+                            ))
+                        }
+                    }
+                }
+
+                let key_column = Column::from_values(key, &all_keys)?;
+                let value_column = Column::from_values(value_schema, &all_values)?;
+                Ok(Column::Map {
+                    default: default.clone(),
+                    lengths,
+                    key: Box::new(key_column),
+                    value: Box::new(value_column),
+                })
+            }
         }
     }
 
@@ -621,36 +751,31 @@ impl Column {
                     variant_values.push(variant.column.to_values()?);
                 }
 
-                // Transpose the variant columns (each row becomes a vector of values, one per variant)
-                let row_count = tags.len();
-                let mut transposed_values = Vec::new();
-                for row_idx in 0..row_count {
-                    let mut row_values = Vec::new();
-                    for (variant_idx, _variant) in variants.iter().enumerate() {
-                        let values = &variant_values[variant_idx];
-                        if row_idx < values.len() {
-                            row_values.push(values[row_idx].clone());
-                        } else {
-                            // Use default value for this variant if no value exists
-                            row_values.push(Value::Unit); // TODO: use proper default
-                        }
-                    }
-                    transposed_values.push(row_values);
-                }
+                // Variant columns hold one value per row carrying their tag, in
+                // row order, so walk each with its own cursor
+                let mut variant_values: Vec<_> = variant_values
+                    .into_iter()
+                    .map(|values| values.into_iter())
+                    .collect();
 
                 let mut result = Vec::new();
-                for (row_idx, &tag) in tags.iter().enumerate() {
-                    if let Some(variant_idx) = variants.iter().position(|v| v.tag == tag) {
-                        let row_values = &transposed_values[row_idx];
-                        result.push(Value::Enum {
-                            tag,
-                            value: Box::new(row_values[variant_idx].clone()),
-                        });
-                    } else {
-                        return Err(ConversionError::Schema(
-                            crate::error::SchemaError::UnsupportedType(format!("enum tag {}", tag)),
-                        ));
-                    }
+                for &tag in tags {
+                    let variant_idx =
+                        variants.iter().position(|v| v.tag == tag).ok_or_else(|| {
+                            ConversionError::Schema(crate::error::SchemaError::UnsupportedType(
+                                format!("enum tag {}", tag),
+                            ))
+                        })?;
+                    let value = variant_values[variant_idx].next().ok_or_else(|| {
+                        ConversionError::Striped(StripedError::ColumnMismatch {
+                            expected: tags.iter().filter(|&&t| t == tag).count(),
+                            actual: variants[variant_idx].column.row_count(),
+                        })
+                    })?;
+                    result.push(Value::Enum {
+                        tag,
+                        value: Box::new(value),
+                    });
                 }
 
                 Ok(result)
@@ -715,6 +840,28 @@ impl Column {
                     .map(|v| Value::Reversed(Box::new(v)))
                     .collect())
             }
+            Column::Map {
+                lengths,
+                key,
+                value,
+                ..
+            } => {
+                let keys = key.to_values()?;
+                let values = value.to_values()?;
+                if keys.len() != values.len() || lengths.iter().sum::<usize>() != keys.len() {
+                    return Err(ConversionError::Striped(
+                        StripedError::VectorOperationFailed(
+                            "Map entry length mismatch".to_string(),
+                        ),
+                    ));
+                }
+
+                let mut entries = keys.into_iter().zip(values);
+                Ok(lengths
+                    .iter()
+                    .map(|&length| Value::Map(entries.by_ref().take(length).collect()))
+                    .collect())
+            }
         }
     }
 
@@ -736,10 +883,394 @@ impl Column {
             Column::Enum { tags, .. } => tags.len(),
             Column::Nested { lengths, .. } => lengths.len(),
             Column::Reversed { inner } => inner.row_count(),
+            Column::Map { lengths, .. } => lengths.len(),
+        }
+    }
+
+    /// Rebuild the schema the column was striped against
+    pub fn schema(&self) -> ValueSchema {
+        match self {
+            Column::Unit { .. } => ValueSchema::Unit,
+            Column::Int {
+                default, encoding, ..
+            } => ValueSchema::Int {
+                default: default.clone(),
+                encoding: encoding.clone(),
+            },
+            Column::Double { default, .. } => ValueSchema::Double {
+                default: default.clone(),
+            },
+            Column::Binary {
+                default, encoding, ..
+            } => ValueSchema::Binary {
+                default: default.clone(),
+                encoding: encoding.clone(),
+            },
+            Column::Array {
+                default, element, ..
+            } => ValueSchema::Array {
+                default: default.clone(),
+                element: Box::new(element.schema()),
+            },
+            Column::Struct { default, fields } => ValueSchema::Struct {
+                default: default.clone(),
+                fields: fields
+                    .iter()
+                    .map(|field| FieldSchema {
+                        name: field.name.clone(),
+                        schema: field.column.schema(),
+                    })
+                    .collect(),
+            },
+            Column::Enum {
+                default, variants, ..
+            } => ValueSchema::Enum {
+                default: default.clone(),
+                variants: variants
+                    .iter()
+                    .map(|variant| VariantSchema {
+                        name: variant.name.clone(),
+                        tag: variant.tag,
+                        schema: variant.column.schema(),
+                    })
+                    .collect(),
+            },
+            Column::Nested { table, .. } => ValueSchema::Nested {
+                table: Box::new(table.schema()),
+            },
+            Column::Reversed { inner } => ValueSchema::Reversed {
+                inner: Box::new(inner.schema()),
+            },
+            Column::Map {
+                default,
+                key,
+                value,
+                ..
+            } => ValueSchema::Map {
+                default: default.clone(),
+                key: Box::new(key.schema()),
+                value: Box::new(value.schema()),
+            },
+        }
+    }
+
+    /// Whether the column has rows and every one of them is the default value
+    /// of its schema, which it must allow
+    pub fn is_all_default(&self) -> bool {
+        if self.row_count() == 0 {
+            return false;
+        }
+        let allow = |default: &Default| *default == Default::Allow;
+        match self {
+            Column::Unit { .. } => true,
+            Column::Int {
+                default, values, ..
+            } => allow(default) && values.iter().all(|v| *v == 0),
+            Column::Double { default, values } => {
+                allow(default) && values.iter().all(|v| v.to_bits() == 0)
+            }
+            Column::Binary {
+                default, lengths, ..
+            }
+            | Column::Array {
+                default, lengths, ..
+            }
+            | Column::Map {
+                default, lengths, ..
+            } => allow(default) && lengths.iter().all(|l| *l == 0),
+            Column::Nested { lengths, table } => {
+                allow(&table.schema().default_policy()) && lengths.iter().all(|l| *l == 0)
+            }
+            Column::Struct { default, fields } => {
+                allow(default) && fields.iter().all(|f| f.column.is_all_default())
+            }
+            // The default enum value is the first variant holding its default
+            Column::Enum {
+                default,
+                tags,
+                variants,
+            } if allow(default) => match variants.first() {
+                Some(first) => {
+                    tags.iter().all(|tag| *tag == first.tag) && first.column.is_all_default()
+                }
+                None => false,
+            },
+            Column::Enum { .. } => false,
+            Column::Reversed { inner } => inner.is_all_default(),
+        }
+    }
+
+    /// Check the row-count invariants of the column and its children
+    pub fn validate(&self) -> Result<(), StripedError> {
+        self.validate_at("")
+    }
+
+    fn validate_at(&self, path: &str) -> Result<(), StripedError> {
+        match self {
+            Column::Unit { .. } | Column::Int { .. } | Column::Double { .. } => Ok(()),
+            Column::Binary { lengths, data, .. } => {
+                check_lengths(path, "binary", lengths, data.len(), "bytes of data")
+            }
+            Column::Array {
+                lengths, element, ..
+            } => {
+                let element_path = child_path(path, "element");
+                element.validate_at(&element_path)?;
+                check_lengths(path, "array", lengths, element.row_count(), "elements")
+            }
+            Column::Struct { fields, .. } => {
+                let Some(first) = fields.first() else {
+                    return Err(invariant_violation(
+                        path,
+                        "struct has no fields".to_string(),
+                    ));
+                };
+                let rows = first.column.row_count();
+                for field in fields {
+                    let field_path = child_path(path, &field.name);
+                    field.column.validate_at(&field_path)?;
+                    let field_rows = field.column.row_count();
+                    if field_rows != rows {
+                        return Err(invariant_violation(
+                            &field_path,
+                            format!(
+                                "struct field has {} rows but field '{}' has {}",
+                                field_rows, first.name, rows
+                            ),
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            Column::Enum { tags, variants, .. } => {
+                if let Some(&tag) = tags
+                    .iter()
+                    .find(|&&tag| !variants.iter().any(|v| v.tag == tag))
+                {
+                    return Err(invariant_violation(
+                        path,
+                        format!("enum tag {} has no variant", tag),
+                    ));
+                }
+                for variant in variants {
+                    let variant_path = child_path(path, &variant.name);
+                    variant.column.validate_at(&variant_path)?;
+                    let tagged = tags.iter().filter(|&&tag| tag == variant.tag).count();
+                    let rows = variant.column.row_count();
+                    if tagged != rows {
+                        return Err(invariant_violation(
+                            &variant_path,
+                            format!(
+                                "{} rows carry tag {} but the variant column has {}",
+                                tagged, variant.tag, rows
+                            ),
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            Column::Nested { lengths, table } => {
+                table.validate_at(&child_path(path, "element"))?;
+                // Nested binary tables split their bytes rather than their rows
+                let (available, unit) = match table.as_ref() {
+                    Table::Binary { data, .. } => (data.len(), "bytes of data"),
+                    Table::Array { column, .. } => (column.row_count(), "elements"),
+                    Table::Map { key_column, .. } => (key_column.row_count(), "entries"),
+                };
+                check_lengths(path, "nested", lengths, available, unit)
+            }
+            Column::Reversed { inner } => inner.validate_at(path),
+            Column::Map {
+                lengths,
+                key,
+                value,
+                ..
+            } => {
+                key.validate_at(&child_path(path, "key"))?;
+                value.validate_at(&child_path(path, "value"))?;
+                let (keys, values) = (key.row_count(), value.row_count());
+                if keys != values {
+                    return Err(invariant_violation(
+                        path,
+                        format!("map has {} keys but {} values", keys, values),
+                    ));
+                }
+                check_lengths(path, "map", lengths, keys, "entries")
+            }
+        }
+    }
+
+    /// Keep only the rows whose entry in `mask` is true
+    pub fn select(&self, mask: &[bool]) -> Column {
+        debug_assert_eq!(mask.len(), self.row_count());
+        match self {
+            Column::Unit { .. } => Column::Unit {
+                count: mask.iter().filter(|&&keep| keep).count(),
+            },
+            Column::Int {
+                default,
+                encoding,
+                values,
+            } => Column::Int {
+                default: default.clone(),
+                encoding: encoding.clone(),
+                values: select_values(values, mask),
+            },
+            Column::Double { default, values } => Column::Double {
+                default: default.clone(),
+                values: select_values(values, mask),
+            },
+            Column::Binary {
+                default,
+                encoding,
+                lengths,
+                data,
+            } => {
+                let mut selected = Vec::new();
+                let mut offset = 0;
+                for (&length, &keep) in lengths.iter().zip(mask) {
+                    if keep {
+                        selected.extend_from_slice(&data[offset..offset + length]);
+                    }
+                    offset += length;
+                }
+                Column::Binary {
+                    default: default.clone(),
+                    encoding: encoding.clone(),
+                    lengths: select_values(lengths, mask),
+                    data: selected,
+                }
+            }
+            Column::Array {
+                default,
+                lengths,
+                element,
+            } => Column::Array {
+                default: default.clone(),
+                lengths: select_values(lengths, mask),
+                element: Box::new(element.select(&expand_mask(lengths, mask))),
+            },
+            Column::Map {
+                default,
+                lengths,
+                key,
+                value,
+            } => {
+                let entry_mask = expand_mask(lengths, mask);
+                Column::Map {
+                    default: default.clone(),
+                    lengths: select_values(lengths, mask),
+                    key: Box::new(key.select(&entry_mask)),
+                    value: Box::new(value.select(&entry_mask)),
+                }
+            }
+            Column::Struct { default, fields } => Column::Struct {
+                default: default.clone(),
+                fields: fields
+                    .iter()
+                    .map(|field| FieldColumn {
+                        name: field.name.clone(),
+                        column: field.column.select(mask),
+                    })
+                    .collect(),
+            },
+            Column::Enum {
+                default,
+                tags,
+                variants,
+            } => Column::Enum {
+                default: default.clone(),
+                tags: select_values(tags, mask),
+                variants: variants
+                    .iter()
+                    .map(|variant| {
+                        // Variant columns hold one row per row carrying their tag
+                        let variant_mask: Vec<bool> = tags
+                            .iter()
+                            .zip(mask)
+                            .filter(|(&tag, _)| tag == variant.tag)
+                            .map(|(_, &keep)| keep)
+                            .collect();
+                        VariantColumn {
+                            name: variant.name.clone(),
+                            tag: variant.tag,
+                            column: variant.column.select(&variant_mask),
+                        }
+                    })
+                    .collect(),
+            },
+            Column::Nested { lengths, table } => Column::Nested {
+                lengths: select_values(lengths, mask),
+                table: Box::new(table.select(&expand_mask(lengths, mask))),
+            },
+            Column::Reversed { inner } => Column::Reversed {
+                inner: Box::new(inner.select(mask)),
+            },
         }
     }
 }
 
+fn child_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn invariant_violation(path: &str, reason: String) -> StripedError {
+    let path = if path.is_empty() { "<table>" } else { path };
+    StripedError::InvariantViolation {
+        path: path.to_string(),
+        reason,
+    }
+}
+
+/// Check that per-row lengths account for exactly the child values available
+fn check_lengths(
+    path: &str,
+    kind: &str,
+    lengths: &[usize],
+    available: usize,
+    unit: &str,
+) -> Result<(), StripedError> {
+    let total = lengths
+        .iter()
+        .try_fold(0usize, |total, &length| total.checked_add(length));
+    match total {
+        Some(total) if total == available => Ok(()),
+        Some(total) => Err(invariant_violation(
+            path,
+            format!(
+                "{} lengths sum to {} but there are {} {}",
+                kind, total, available, unit
+            ),
+        )),
+        None => Err(invariant_violation(
+            path,
+            format!("{} lengths overflow", kind),
+        )),
+    }
+}
+
+fn select_values<T: Copy>(values: &[T], mask: &[bool]) -> Vec<T> {
+    values
+        .iter()
+        .zip(mask)
+        .filter(|(_, &keep)| keep)
+        .map(|(&value, _)| value)
+        .collect()
+}
+
+/// Expand a row mask to the elements of each row
+fn expand_mask(lengths: &[usize], mask: &[bool]) -> Vec<bool> {
+    lengths
+        .iter()
+        .zip(mask)
+        .flat_map(|(&length, &keep)| std::iter::repeat(keep).take(length))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -795,6 +1326,119 @@ mod tests {
         assert_eq!(reconstructed, values);
     }
 
+    #[test]
+    fn test_missing_fields_follow_default_policy() {
+        let field = |name: &str, default| FieldSchema {
+            name: name.to_string(),
+            schema: ValueSchema::Int {
+                default,
+                encoding: Encoding::Int(IntEncoding::Int),
+            },
+        };
+        let schema = ValueSchema::Struct {
+            default: Default::Allow,
+            fields: vec![field("id", Default::Deny), field("age", Default::Allow)],
+        };
+        let row = |fields: Vec<(&str, i64)>| {
+            Value::Struct(
+                fields
+                    .into_iter()
+                    .map(|(name, n)| Field {
+                        name: name.to_string(),
+                        value: Value::Int(n),
+                    })
+                    .collect(),
+            )
+        };
+
+        // Rows without the optional field get its default
+        let column = Column::from_values(
+            &schema,
+            &[row(vec![("id", 1)]), row(vec![("id", 2), ("age", 30)])],
+        )
+        .unwrap();
+        assert_eq!(
+            column.to_values().unwrap(),
+            vec![
+                row(vec![("id", 1), ("age", 0)]),
+                row(vec![("id", 2), ("age", 30)])
+            ]
+        );
+
+        // Rows without the required field are rejected
+        assert!(matches!(
+            Column::from_values(&schema, &[row(vec![("age", 30)])]),
+            Err(ConversionError::Schema(
+                crate::error::SchemaError::MissingField(name)
+            )) if name == "id"
+        ));
+    }
+
+    #[test]
+    fn test_struct_fields_keep_schema_order() {
+        let field = |name: &str| FieldSchema {
+            name: name.to_string(),
+            schema: ValueSchema::Int {
+                default: Default::Deny,
+                encoding: Encoding::Int(IntEncoding::Int),
+            },
+        };
+        let schema = ValueSchema::Struct {
+            default: Default::Allow,
+            fields: vec![field("id"), field("age")],
+        };
+        let row = |fields: &[(&str, i64)]| {
+            Value::Struct(
+                fields
+                    .iter()
+                    .map(|(name, n)| Field {
+                        name: name.to_string(),
+                        value: Value::Int(*n),
+                    })
+                    .collect(),
+            )
+        };
+
+        let column = Column::from_values(
+            &schema,
+            &[
+                row(&[("age", 30), ("id", 1)]),
+                row(&[("id", 2), ("age", 40)]),
+            ],
+        )
+        .unwrap();
+        let Column::Struct { fields, .. } = &column else {
+            panic!("Expected Struct column")
+        };
+        assert_eq!(fields[0].name, "id");
+        assert_eq!(
+            column.to_values().unwrap(),
+            vec![
+                row(&[("id", 1), ("age", 30)]),
+                row(&[("id", 2), ("age", 40)])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_all_default_columns() {
+        let int_column = |default, values| Column::Int {
+            default,
+            encoding: Encoding::Int(IntEncoding::Int),
+            values,
+        };
+        assert!(int_column(Default::Allow, vec![0, 0, 0]).is_all_default());
+        assert!(!int_column(Default::Allow, vec![0, 1, 0]).is_all_default());
+        assert!(!int_column(Default::Deny, vec![0, 0, 0]).is_all_default());
+        assert!(!int_column(Default::Allow, vec![]).is_all_default());
+
+        let schema = ValueSchema::Int {
+            default: Default::Allow,
+            encoding: Encoding::Int(IntEncoding::Int),
+        };
+        assert_eq!(int_column(Default::Allow, vec![7]).schema(), schema);
+    }
+
     #[test]
     fn test_array_column_conversion() {
         let schema = ValueSchema::Array {
@@ -832,4 +1476,292 @@ mod tests {
         let reconstructed = column.to_values().unwrap();
         assert_eq!(reconstructed, values);
     }
+
+    #[test]
+    fn test_enum_column_conversion() {
+        use crate::logical::VariantSchema;
+
+        let schema = ValueSchema::Enum {
+            default: Default::Allow,
+            variants: vec![
+                VariantSchema {
+                    name: "none".to_string(),
+                    tag: 0,
+                    schema: ValueSchema::Unit,
+                },
+                VariantSchema {
+                    name: "some".to_string(),
+                    tag: 1,
+                    schema: ValueSchema::Int {
+                        default: Default::Allow,
+                        encoding: Encoding::Int(IntEncoding::Int),
+                    },
+                },
+            ],
+        };
+        let some = |n: i64| Value::Enum {
+            tag: 1,
+            value: Box::new(Value::Int(n)),
+        };
+        let none = Value::Enum {
+            tag: 0,
+            value: Box::new(Value::Unit),
+        };
+
+        // Variant columns only hold the rows carrying their tag
+        let values = vec![some(1), none.clone(), none, some(2), some(3)];
+        let column = Column::from_values(&schema, &values).unwrap();
+        match &column {
+            Column::Enum { tags, variants, .. } => {
+                assert_eq!(tags, &vec![1, 0, 0, 1, 1]);
+                assert_eq!(variants[0].column.row_count(), 2);
+                assert_eq!(variants[1].column.row_count(), 3);
+            }
+            _ => panic!("Expected Enum column"),
+        }
+
+        let reconstructed = column.to_values().unwrap();
+        assert_eq!(reconstructed, values);
+    }
+
+    #[test]
+    fn test_map_column_conversion() {
+        let schema = ValueSchema::Map {
+            default: Default::Allow,
+            key: Box::new(ValueSchema::Binary {
+                default: Default::Allow,
+                encoding: Encoding::Binary(BinaryEncoding::Utf8),
+            }),
+            value: Box::new(ValueSchema::Int {
+                default: Default::Allow,
+                encoding: Encoding::Int(IntEncoding::Int),
+            }),
+        };
+        let map = |entries: &[(&str, i64)]| {
+            Value::Map(
+                entries
+                    .iter()
+                    .map(|(k, v)| (Value::Binary(k.as_bytes().to_vec()), Value::Int(*v)))
+                    .collect(),
+            )
+        };
+
+        let values = vec![
+            map(&[("retries", 2), ("status", 200)]),
+            map(&[]),
+            map(&[("status", 404)]),
+        ];
+        let column = Column::from_values(&schema, &values).unwrap();
+
+        match &column {
+            Column::Map {
+                lengths,
+                key,
+                value,
+                ..
+            } => {
+                assert_eq!(lengths, &vec![2, 0, 1]);
+                assert_eq!(key.row_count(), 3);
+                assert_eq!(value.row_count(), 3);
+            }
+            _ => panic!("Expected Map column"),
+        }
+        assert!(column.validate().is_ok());
+        assert_eq!(column.schema(), schema);
+        assert_eq!(column.to_values().unwrap(), values);
+
+        let selected = column.select(&[false, true, true]);
+        assert_eq!(selected.to_values().unwrap(), values[1..].to_vec());
+    }
+
+    #[test]
+    fn test_select_rows() {
+        use crate::logical::{FieldSchema, VariantSchema};
+
+        let schema = ValueSchema::Struct {
+            default: Default::Allow,
+            fields: vec![
+                FieldSchema {
+                    name: "tags".to_string(),
+                    schema: ValueSchema::Array {
+                        default: Default::Allow,
+                        element: Box::new(ValueSchema::Binary {
+                            default: Default::Allow,
+                            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+                        }),
+                    },
+                },
+                FieldSchema {
+                    name: "level".to_string(),
+                    schema: ValueSchema::Enum {
+                        default: Default::Allow,
+                        variants: vec![
+                            VariantSchema {
+                                name: "info".to_string(),
+                                tag: 0,
+                                schema: ValueSchema::Unit,
+                            },
+                            VariantSchema {
+                                name: "error".to_string(),
+                                tag: 1,
+                                schema: ValueSchema::Int {
+                                    default: Default::Allow,
+                                    encoding: Encoding::Int(IntEncoding::Int),
+                                },
+                            },
+                        ],
+                    },
+                },
+            ],
+        };
+        let row = |tags: &[&str], level: Value| {
+            Value::Struct(vec![
+                Field {
+                    name: "tags".to_string(),
+                    value: Value::Array(
+                        tags.iter()
+                            .map(|t| Value::Binary(t.as_bytes().to_vec()))
+                            .collect(),
+                    ),
+                },
+                Field {
+                    name: "level".to_string(),
+                    value: level,
+                },
+            ])
+        };
+        let error = |code: i64| Value::Enum {
+            tag: 1,
+            value: Box::new(Value::Int(code)),
+        };
+        let info = Value::Enum {
+            tag: 0,
+            value: Box::new(Value::Unit),
+        };
+
+        let values = vec![
+            row(&["a", "bc"], error(1)),
+            row(&[], info.clone()),
+            row(&["d"], error(2)),
+            row(&["ef", "g", "h"], error(3)),
+        ];
+        let column = Column::from_values(&schema, &values).unwrap();
+
+        let selected = column.select(&[false, true, true, false]);
+        assert_eq!(
+            selected.to_values().unwrap(),
+            vec![row(&[], info), row(&["d"], error(2))]
+        );
+        assert_eq!(column.select(&[false; 4]).row_count(), 0);
+    }
+
+    #[test]
+    fn test_validate_row_counts() {
+        let ints = |values: Vec<i64>| Column::Int {
+            default: Default::Allow,
+            encoding: Encoding::Int(IntEncoding::Int),
+            values,
+        };
+        let names = Column::Binary {
+            default: Default::Allow,
+            encoding: Encoding::Binary(BinaryEncoding::Utf8),
+            lengths: vec![3, 0, 5],
+            data: b"bobcarol".to_vec(),
+        };
+        let level = |tags: Vec<u32>, errors: Vec<i64>| Column::Enum {
+            default: Default::Allow,
+            tags,
+            variants: vec![
+                VariantColumn {
+                    name: "info".to_string(),
+                    tag: 0,
+                    column: Column::Unit { count: 2 },
+                },
+                VariantColumn {
+                    name: "error".to_string(),
+                    tag: 1,
+                    column: ints(errors),
+                },
+            ],
+        };
+        let table = |fields: Vec<(&str, Column)>| Table::Array {
+            default: Default::Allow,
+            column: Box::new(Column::Struct {
+                default: Default::Allow,
+                fields: fields
+                    .into_iter()
+                    .map(|(name, column)| FieldColumn {
+                        name: name.to_string(),
+                        column,
+                    })
+                    .collect(),
+            }),
+        };
+        let violation = |table: Table| match table.validate() {
+            Err(StripedError::InvariantViolation { path, .. }) => path,
+            other => panic!("expected an invariant violation, got {:?}", other),
+        };
+
+        let valid = table(vec![
+            ("name", names.clone()),
+            ("level", level(vec![0, 1, 0], vec![7])),
+            (
+                "history",
+                Column::Nested {
+                    lengths: vec![2, 0, 1],
+                    table: Box::new(Table::Array {
+                        default: Default::Allow,
+                        column: Box::new(ints(vec![1, 2, 3])),
+                    }),
+                },
+            ),
+        ]);
+        assert!(valid.validate().is_ok());
+
+        assert_eq!(
+            violation(table(vec![
+                ("name", names.clone()),
+                ("id", ints(vec![1, 2])),
+            ])),
+            "id"
+        );
+        assert_eq!(
+            violation(table(vec![("level", level(vec![0, 1, 0], vec![7, 8]))])),
+            "level.error"
+        );
+        assert_eq!(
+            violation(table(vec![("level", level(vec![0, 2, 0], vec![7]))])),
+            "level"
+        );
+        assert_eq!(
+            violation(table(vec![(
+                "history",
+                Column::Nested {
+                    lengths: vec![2, 0, 2],
+                    table: Box::new(Table::Array {
+                        default: Default::Allow,
+                        column: Box::new(ints(vec![1, 2, 3])),
+                    }),
+                },
+            )])),
+            "history"
+        );
+        assert_eq!(
+            violation(table(vec![(
+                "tags",
+                Column::Array {
+                    default: Default::Allow,
+                    lengths: vec![1, 1],
+                    element: Box::new(Column::Binary {
+                        default: Default::Allow,
+                        encoding: Encoding::Binary(BinaryEncoding::Utf8),
+                        lengths: vec![2, usize::MAX],
+                        data: b"ab".to_vec(),
+                    }),
+                },
+            )])),
+            "tags.element"
+        );
+        assert_eq!(violation(table(Vec::new())), "<table>");
+    }
 }
@@ -73,7 +73,9 @@ pub fn arb_value_depth(depth: u32) -> impl Strategy<Value = Value> {
             prop::collection::vec(inner.clone(), 0..5)
                 .prop_map(|values| Value::Nested(Box::new(Table::Array(values)))),
             // Reversed
-            inner.prop_map(|v| Value::Reversed(Box::new(v))),
+            inner.clone().prop_map(|v| Value::Reversed(Box::new(v))),
+            // Maps
+            prop::collection::vec((inner.clone(), inner), 0..5).prop_map(Value::Map),
         ]
     })
 }
@@ -156,9 +158,17 @@ pub fn arb_value_schema_depth(depth: u32) -> BoxedStrategy<ValueSchema> {
                 table: Box::new(table)
             }),
             // Reversed
-            inner.prop_map(|inner| ValueSchema::Reversed {
+            inner.clone().prop_map(|inner| ValueSchema::Reversed {
                 inner: Box::new(inner)
             }),
+            // Maps
+            (arb_default(), inner.clone(), inner).prop_map(|(default, key, value)| {
+                ValueSchema::Map {
+                    default,
+                    key: Box::new(key),
+                    value: Box::new(value),
+                }
+            }),
         ]
     })
     .boxed()
@@ -333,6 +343,13 @@ pub fn arb_value_for_schema(schema: &ValueSchema) -> BoxedStrategy<Value> {
                 .prop_map(|value| Value::Reversed(Box::new(value)))
                 .boxed()
         }
+        ValueSchema::Map { key, value, .. } => {
+            let key_gen = arb_value_for_schema(key);
+            let value_gen = arb_value_for_schema(value);
+            prop::collection::vec((key_gen, value_gen), 0..5)
+                .prop_map(Value::Map)
+                .boxed()
+        }
     }
 }
 
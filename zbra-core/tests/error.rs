@@ -22,8 +22,8 @@ proptest! {
 
         // If validation fails, it should be a SchemaError
         if let Err(SchemaError::TypeMismatch { expected, actual }) = validation_result {
-            prop_assert!(expected.len() > 0);
-            prop_assert!(actual.len() > 0);
+            prop_assert!(!expected.is_empty());
+            prop_assert!(!actual.is_empty());
             prop_assert_ne!(expected, actual);
         } else if validation_result.is_ok() {
             // If validation succeeds, the value should actually be compatible
@@ -223,7 +223,8 @@ proptest! {
         }
     }
 
-    /// Test that struct field count mismatches are detected
+    /// Test that struct field count mismatches are detected for required
+    /// fields, while unknown fields are ignored as when striping
     #[test]
     fn test_struct_field_count_mismatch_errors(
         expected_count in 1usize..10,
@@ -234,14 +235,17 @@ proptest! {
         let expected_fields: Vec<FieldSchema> = (0..expected_count)
             .map(|i| FieldSchema {
                 name: format!("field_{}", i),
-                schema: ValueSchema::Unit,
+                schema: ValueSchema::Int {
+                    default: zbra_core::data::Default::Deny,
+                    encoding: zbra_core::data::Encoding::Int(zbra_core::data::IntEncoding::Int),
+                },
             })
             .collect();
 
         let actual_fields: Vec<Field> = (0..actual_count)
             .map(|i| Field {
                 name: format!("field_{}", i),
-                value: Value::Unit,
+                value: Value::Int(i as i64),
             })
             .collect();
 
@@ -253,12 +257,12 @@ proptest! {
         let struct_value = Value::Struct(actual_fields);
         let result = struct_value.validate_schema(&schema);
 
-        // Should get TypeMismatch error for field count
-        if let Err(SchemaError::TypeMismatch { expected, actual }) = result {
-            prop_assert!(expected.contains(&expected_count.to_string()));
-            prop_assert!(actual.contains(&actual_count.to_string()));
+        // The first required field left out is reported
+        if actual_count < expected_count {
+            let missing = format!("field_{}", actual_count);
+            prop_assert!(matches!(result, Err(SchemaError::MissingField(name)) if name == missing));
         } else {
-            prop_assert!(false, "Expected TypeMismatch error");
+            prop_assert!(result.is_ok());
         }
     }
 }
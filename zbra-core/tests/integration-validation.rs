@@ -120,6 +120,8 @@ fn test_end_to_end_with_limit_dates() {
     let compression_config = CompressionConfig {
         binary_data: zbra_core::compression::CompressionAlgorithm::Zstd { level: 3 },
         strings: zbra_core::compression::CompressionAlgorithm::Zstd { level: 3 },
+        columns: Vec::new(),
+        optimization_target: zbra_core::compression::OptimizationTarget::MinimizeSpace,
     };
 
     let binary_file =
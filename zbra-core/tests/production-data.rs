@@ -414,11 +414,15 @@ fn test_user_records() {
     let no_compression = CompressionConfig {
         binary_data: zbra_core::compression::CompressionAlgorithm::None,
         strings: zbra_core::compression::CompressionAlgorithm::None,
+        columns: Vec::new(),
+        optimization_target: zbra_core::compression::OptimizationTarget::MinimizeSpace,
     };
 
     let with_compression = CompressionConfig {
         binary_data: zbra_core::compression::CompressionAlgorithm::Zstd { level: 3 },
         strings: zbra_core::compression::CompressionAlgorithm::Zstd { level: 3 },
+        columns: Vec::new(),
+        optimization_target: zbra_core::compression::OptimizationTarget::MinimizeSpace,
     };
 
     for (name, config) in [
@@ -583,7 +587,7 @@ fn test_iot_sensor_data() {
     // Generate 1000 sensor readings across 10 sensors
     for i in 0..1000 {
         let sensor_id = i % 10; // 10 sensors
-        let timestamp = base_time + (i as i64 * 1000); // 1 second intervals
+        let timestamp = base_time + (i * 1000); // 1 second intervals
 
         // Simulate realistic sensor patterns
         let temperature = match sensor_id {